@@ -1,3 +1,17 @@
-fn main() {
+use tracing::info;
+use config::Config;
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .try_init();
+
+    // Arb runs on every configured network, refuse to start if an endpoint serves another chain than its section claims
+    let cfg = Config::default();
+    cfg.verify_chain_ids().await?;
+    info!(networks = cfg.providers.len(), "chain ids verified");
+
     println!("todo!");
+    Ok(())
 }
@@ -2,11 +2,14 @@
 mod tests;
 
 use std::collections::HashMap;
-use eyre::{Context, Result};
+use std::fmt::{Display, Formatter};
+use eyre::{eyre, Context, Result};
 use serde::Deserialize;
 use std::fs;
 use std::path::{Path, PathBuf};
-use alloy::primitives::Address;
+use alloy::primitives::{Address, B256};
+use alloy::providers::{Provider, ProviderBuilder};
+use types::pool::PoolProtocol;
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
@@ -34,57 +37,288 @@ impl Config {
         let config: Config = toml::from_str(&contents)?;
         Ok(config)
     }
+
+    /// Chain id of `network`, as configured for its provider or known for the network.
+    pub fn chain_id(&self, network: &Network) -> Result<u64> {
+        self.providers
+            .get(network)
+            .and_then(|provider| provider.chain_id)
+            .or_else(|| network.chain_id())
+            .ok_or(eyre!("CHAIN_ID_NOT_CONFIGURED {network}"))
+    }
+
+    /// Symbol of the native currency of `network`, as configured or known for the network.
+    pub fn native_symbol(&self, network: &Network) -> Result<String> {
+        self.addresses
+            .get(network)
+            .and_then(|addresses| addresses.native_symbol.clone())
+            .or_else(|| network.native_symbol().map(str::to_string))
+            .ok_or(eyre!("NATIVE_SYMBOL_NOT_CONFIGURED {network}"))
+    }
+
+    /// Calls eth_chainId on the provider of `network`, failing when it serves another chain than its section claims.
+    pub async fn verify_chain_id(&self, network: &Network) -> Result<()> {
+        let provider = self.providers
+            .get(network)
+            .ok_or(eyre!("PROVIDER_NOT_CONFIGURED {network}"))?;
+        let expected = self.chain_id(network)?;
+        let actual = ProviderBuilder::new()
+            .on_http(provider.api.parse()?)
+            .get_chain_id()
+            .await
+            .with_context(|| format!("eth_chainId on {network}"))?;
+        if actual != expected {
+            return Err(eyre!("CHAIN_ID_MISMATCH {network} expects {expected}, {} serves {actual}", provider.api));
+        }
+        Ok(())
+    }
+
+    /// [`Config::verify_chain_id`] for every configured provider.
+    pub async fn verify_chain_ids(&self) -> Result<()> {
+        for network in self.providers.keys() {
+            self.verify_chain_id(network).await?;
+        }
+        Ok(())
+    }
 }
 
+/// Config sections are keyed by network name, names other than the known ones are custom networks.
 #[derive(Hash, Eq, PartialEq, Clone, Debug, Deserialize)]
+#[serde(from = "String")]
 pub enum Network {
     Ethereum,
+    /// Sei EVM mainnet, pacific-1.
+    Sei,
+    Arbitrum,
+    Base,
+    Bsc,
+    /// Any other EVM chain, whose chain id must be configured.
+    Custom(String),
+}
+
+impl From<String> for Network {
+    fn from(name: String) -> Self {
+        match name.as_str() {
+            "Ethereum" => Network::Ethereum,
+            "Sei" => Network::Sei,
+            "Arbitrum" => Network::Arbitrum,
+            "Base" => Network::Base,
+            "Bsc" | "BSC" => Network::Bsc,
+            _ => Network::Custom(name),
+        }
+    }
+}
+
+impl Display for Network {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Ethereum => "Ethereum",
+            Self::Sei => "Sei",
+            Self::Arbitrum => "Arbitrum",
+            Self::Base => "Base",
+            Self::Bsc => "Bsc",
+            Self::Custom(name) => name.as_str(),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Network {
+    pub fn chain_id(&self) -> Option<u64> {
+        match self {
+            Self::Ethereum => Some(1),
+            Self::Sei => Some(1329),
+            Self::Arbitrum => Some(42161),
+            Self::Base => Some(8453),
+            Self::Bsc => Some(56),
+            Self::Custom(_) => None,
+        }
+    }
+
+    pub fn native_symbol(&self) -> Option<&'static str> {
+        match self {
+            Self::Ethereum | Self::Arbitrum | Self::Base => Some("ETH"),
+            Self::Sei => Some("SEI"),
+            Self::Bsc => Some("BNB"),
+            Self::Custom(_) => None,
+        }
+    }
 }
 
 #[derive(Hash, Eq, PartialEq, Clone, Debug, Deserialize)]
 pub struct NetworkProvider {
     pub api: String,
+    /// Chain id the endpoint must serve, required for custom networks.
+    #[serde(default)]
+    pub chain_id: Option<u64>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct NetworkAddresses {
+    /// Symbol of the native currency, required for custom networks.
+    #[serde(default)]
+    pub native_symbol: Option<String>,
+    /// Wrapped native token, WETH on Ethereum, which WrapPool converts the native currency to.
+    #[serde(default)]
+    pub wrapped_native: Option<Address>,
+    /// Empty on networks without a Uniswap V2 deployment configured.
+    #[serde(default)]
     pub uniswap_v2: UniswapV2,
+    /// Empty on networks without a Uniswap V3 deployment configured.
+    #[serde(default)]
     pub uniswap_v3: UniswapV3,
+    /// V3 forks keyed by DEX name, e.g. PancakeSwapV3, each with the protocol picking its slot0 ABI and storage layout.
+    #[serde(default)]
+    pub uniswap_v3_forks: HashMap<String, UniswapV3>,
+    #[serde(default)]
+    pub uniswap_v4: UniswapV4,
+    #[serde(default)]
+    pub algebra: Algebra,
+    #[serde(default)]
+    pub solidly: Solidly,
+    #[serde(default)]
+    pub curve: Curve,
+    #[serde(default)]
+    pub balancer: Balancer,
+    #[serde(default)]
+    pub erc4626: Erc4626,
+    #[serde(default)]
+    pub liquidity_book: LiquidityBook,
+    #[serde(default)]
+    pub dodo: Dodo,
+    /// keccak256 of the runtime bytecode of known pool implementations, which detection trusts over probing.
+    /// Only implementations without immutables, like UniswapV2Pair, share one hash across their pools, V3 pools embed their tokens and fee.
+    #[serde(default)]
+    pub pool_code_hashes: HashMap<B256, PoolProtocol>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize)]
 pub struct UniswapV2 {
     pub pools: HashMap<String, Address>,
     pub core: UniswapV2Core,
     pub periphery: UniswapV2Periphery
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize)]
 pub struct UniswapV2Core {
     pub router02: Address,
+    /// Factory of the canonical pairs, detection marks pairs of other factories as forks.
+    #[serde(default)]
+    pub factory: Option<Address>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize)]
 pub struct UniswapV2Periphery {}
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct UniswapV3 {
+    #[serde(default = "default_uniswap_v3_protocol")]
+    pub protocol: PoolProtocol,
     pub pools: HashMap<String, Address>,
     pub core: UniswapV3Core,
     pub periphery: UniswapV3Periphery
 }
 
-#[derive(Clone, Debug, Deserialize)]
-pub struct UniswapV3Core {}
+impl Default for UniswapV3 {
+    fn default() -> Self {
+        UniswapV3 {
+            protocol: default_uniswap_v3_protocol(),
+            pools: Default::default(),
+            core: Default::default(),
+            periphery: Default::default(),
+        }
+    }
+}
 
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct UniswapV3Core {
+    #[serde(default)]
+    pub factory: Option<Address>,
+    /// Block the factory was deployed at, where PoolCreated discovery starts.
+    #[serde(default)]
+    pub factory_start_block: u64,
+    /// Fee tiers probed with getPool when discovering the pools of a token pair.
+    #[serde(default)]
+    pub fee_tiers: Vec<u32>,
+}
 
-#[derive(Clone, Debug, Deserialize)]
+
+#[derive(Clone, Debug, Default, Deserialize)]
 pub struct UniswapV3Periphery {
     pub quoter: Address,
     pub quoter_v2: Address,
-    pub tick_lens: Address,
+    /// Pools are read from raw storage slots when no TickLens is deployed.
+    pub tick_lens: Option<Address>,
+}
+
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct UniswapV4 {
+    #[serde(default)]
+    pub pool_manager: Option<Address>,
+    /// Block the PoolManager was deployed at, where Initialize discovery starts.
+    #[serde(default)]
+    pub pool_manager_start_block: u64,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Algebra {
+    #[serde(default)]
+    pub pools: HashMap<String, Address>,
+    /// Algebra V1 Quoter the pricing is checked against.
+    #[serde(default)]
+    pub quoter: Option<Address>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Solidly {
+    #[serde(default)]
+    pub pools: HashMap<String, Address>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Curve {
+    #[serde(default)]
+    pub pools: HashMap<String, Address>,
+    /// CryptoSwap pools of volatile coins, priced with a different invariant than the StableSwap ones.
+    #[serde(default)]
+    pub crypto_pools: HashMap<String, Address>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Balancer {
+    #[serde(default)]
+    pub vault: Option<Address>,
+    /// Weighted pools keyed by name, identified by their poolId.
+    #[serde(default)]
+    pub pools: HashMap<String, B256>,
 }
 
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Erc4626 {
+    #[serde(default)]
+    pub vaults: HashMap<String, Address>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct LiquidityBook {
+    #[serde(default)]
+    pub pools: HashMap<String, Address>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Dodo {
+    /// DODO V2 vending machine, stable and private pools keyed by name.
+    #[serde(default)]
+    pub pools: HashMap<String, Address>,
+    /// DVM, DSP and DPP factories, which list the pools of a token pair.
+    #[serde(default)]
+    pub factories: Vec<Address>,
+}
+
+fn default_uniswap_v3_protocol() -> PoolProtocol {
+    PoolProtocol::UniswapV3Like
+}
 
 fn workspace_dir() -> PathBuf {
     let output = std::process::Command::new(env!("CARGO"))
@@ -1,4 +1,4 @@
-use crate::Config;
+use crate::{Config, Network};
 
 #[test]
 fn test_load_from_file() {
@@ -15,4 +15,45 @@ fn test_load_from_file() {
 #[test]
 fn test_default() {
     println!("{:?}", Config::default());
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_networks() {
+    let config: Config = toml::from_str(r#"
+        [providers.Ethereum]
+        api = "http://localhost:8545"
+
+        [providers.Sei]
+        api = "http://localhost:8546"
+
+        [providers.Devnet]
+        api = "http://localhost:8547"
+        chain_id = 31337
+
+        [addresses]
+    "#).unwrap();
+
+    let sei = Network::Sei;
+    let devnet = Network::Custom("Devnet".to_string());
+    assert_eq!(config.chain_id(&Network::Ethereum).unwrap(), 1);
+    assert_eq!(config.chain_id(&sei).unwrap(), 1329);
+    assert_eq!(config.chain_id(&devnet).unwrap(), 31337);
+    assert!(config.chain_id(&Network::Custom("Unknown".to_string())).is_err());
+
+    assert_eq!(Network::from("BSC".to_string()), Network::Bsc);
+    assert_eq!(devnet.to_string(), "Devnet");
+    assert_eq!(config.native_symbol(&sei).unwrap(), "SEI");
+    assert!(config.native_symbol(&devnet).is_err());
+}
+
+/// The default endpoints serve the chains their sections claim.
+#[tokio::test]
+async fn test_verify_chain_ids() {
+    Config::default().verify_chain_ids().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_verify_chain_id_unconfigured() {
+    let config = Config::default();
+    assert!(config.verify_chain_id(&Network::Custom("Unknown".to_string())).await.is_err());
+}
@@ -17,6 +17,7 @@ async fn main() {
 
     let cfg = Config::default();
     let net = config::Network::Ethereum;
+    cfg.verify_chain_id(&net).await.unwrap();
     let provider = cfg.providers.get(&net).unwrap().clone();
     let addresses = cfg.addresses.get(&net).unwrap().clone();
     let provider = Arc::new(ProviderBuilder::new().on_http(provider.api.parse().unwrap()));
@@ -43,6 +44,7 @@ async fn main() {
     let amount_out = pool.calc_amount_out(
         amount_in,
         tok_in,
+        tok_out,
     ).unwrap();
     info!(?amount_in);
     info!(?amount_out);
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+use alloy::primitives::U256;
+use alloy::providers::ProviderBuilder;
+use tracing::info;
+use config::Config;
+use pools::uniswapv3pool::snapshot::UniswapV3PoolSnapshot;
+
+/// Records a conformance fixture for `cargo test`, see `uniswapv3pool::pricing::tests::test_conformance_snapshots`.
+#[tokio::main]
+async fn main() {
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .try_init();
+
+    let cfg = Config::default();
+    let net = config::Network::Ethereum;
+    cfg.verify_chain_id(&net).await.unwrap();
+    let provider = cfg.providers.get(&net).unwrap().clone();
+    let addresses = cfg.addresses.get(&net).unwrap().clone();
+    let provider = Arc::new(ProviderBuilder::new().on_http(provider.api.parse().unwrap()));
+
+    // Fixed blocks so that recording again gives the same fixtures, which needs an archive endpoint
+    let cases: [(&str, &str, u64, Vec<U256>); 3] = [
+        // 1 wei up to 10^12 wei, within the current tick range
+        ("USDC_WETH", "USDC_WETH", 20_000_000, exp_amounts(0..=12)),
+        // The thinner 0.3% pool crosses initialized ticks for these amounts
+        ("USDC_WETH_CROSSING", "USDC_WETH_3000", 20_000_000, exp_amounts(16..=22)),
+        // Past the available liquidity on purpose, the quoter returns the partial fill
+        ("USDC_WETH_PARTIAL", "USDC_WETH", 20_000_000, exp_amounts(26..=30)),
+    ];
+
+    for (name, pool_key, block, amounts) in cases {
+        let pool_addr = addresses.uniswap_v3.pools.get(pool_key).unwrap().clone();
+        info!("pool: {:?} {:?} {:?} block {:?}", net, pool_key, pool_addr, block);
+
+        let snapshot = UniswapV3PoolSnapshot::record(
+            name.to_string(),
+            pool_addr,
+            addresses.uniswap_v3.periphery.clone(),
+            &amounts,
+            provider.clone(),
+            block,
+        ).await.unwrap();
+
+        let path = format!("{}/fixtures/uniswapv3/{}_{}.json", env!("CARGO_MANIFEST_DIR"), name, block);
+        snapshot.save(&path).unwrap();
+        info!("snapshot saved to {path}");
+    }
+}
+
+fn exp_amounts(exps: std::ops::RangeInclusive<u64>) -> Vec<U256> {
+    exps.map(|exp| U256::from(10).pow(U256::from(exp))).collect()
+}
@@ -0,0 +1,31 @@
+use alloy::sol;
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    #[sol(rpc)]
+    contract IAlgebraPool {
+        event Swap(address indexed sender, address indexed recipient, int256 amount0, int256 amount1, uint160 price, uint128 liquidity, int24 tick);
+        event Fee(uint16 fee);
+        function token0() external view returns (address);
+        function token1() external view returns (address);
+        function factory() external view returns (address);
+        function liquidity() external view returns (uint128);
+        function tickSpacing() external view returns (int24);
+        function globalState() external view returns (
+                uint160 price,
+                int24 tick,
+                uint16 fee,
+                uint16 timepointIndex,
+                uint8 communityFeeToken0,
+                uint8 communityFeeToken1,
+                bool unlocked
+        );
+        function ticks(int24 tick) external view returns (uint128 liquidityTotal, int128 liquidityDelta, uint256 outerFeeGrowth0Token, uint256 outerFeeGrowth1Token, int56 outerTickCumulative, uint160 outerSecondsPerLiquidity, uint32 outerSecondsSpent, bool initialized);
+        function tickTable(int16 wordPosition) external view returns (uint256);
+    }
+
+    #[sol(rpc)]
+    contract IAlgebraQuoter {
+        function quoteExactInputSingle(address tokenIn, address tokenOut, uint256 amountIn, uint160 limitSqrtPrice) external returns (uint256 amountOut, uint16 fee);
+    }
+}
@@ -0,0 +1,252 @@
+mod abi;
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use alloy::eips::BlockId;
+use alloy::network::Network;
+use alloy::primitives::{Address, U256};
+use alloy::providers::Provider;
+use alloy::transports::Transport;
+use eyre::eyre;
+use tracing::{instrument, warn};
+use types::pool::{check_pair, Pool, PoolClass, PoolProtocol};
+use crate::algebrapool::abi::IAlgebraPool;
+use crate::algebrapool::abi::IAlgebraPool::{globalStateReturn, tickTableCall, ticksCall};
+use crate::rpc::{call_batch, is_revert};
+use crate::uniswapv3pool::pricing::local::{self, SwapResult, TickInfo};
+use crate::uniswapv3pool::slot0::{ProtocolFee, Slot0};
+use crate::uniswapv3pool::ticks::Ticks;
+use crate::uniswapv3pool::{UniswapV3Pool, TICK_BITMAP_WORD_RANGE};
+
+/// Tick spacing of Algebra V1 pools, which do not all expose it.
+const ALGEBRA_TICK_SPACING: u32 = 60;
+
+#[derive(Debug, Clone)]
+pub struct AlgebraPoolMetadata {
+    pub pool: Address,
+    pub protocol: PoolProtocol,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AlgebraPoolData {
+    pub factory: Address,
+    pub tok0: Address,
+    pub tok1: Address,
+    pub tick_spacing: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AlgebraPoolState {
+    /// globalState normalized to the V3 layout.
+    pub slot0: Slot0,
+    /// Current dynamic fee in hundredths of a bip, as found in globalState.
+    pub fee: u32,
+    pub liquidity: u128,
+    pub tick_bitmap: HashMap<i16, U256>,
+    pub ticks: Ticks,
+}
+
+/// Algebra V1 concentrated liquidity pool with a volatility based dynamic fee, priced with the V3 engine.
+#[derive(Debug, Clone)]
+pub struct AlgebraPool {
+    pub metadata: AlgebraPoolMetadata,
+    pub data: AlgebraPoolData,
+    pub state: AlgebraPoolState,
+}
+
+impl Pool for AlgebraPool {
+    fn get_class(&self) -> PoolClass {
+        PoolClass::UniswapV3
+    }
+
+    fn get_protocol(&self) -> PoolProtocol {
+        self.metadata.protocol
+    }
+
+    fn get_address(&self) -> Address {
+        self.metadata.pool
+    }
+
+    fn get_fee(&self) -> u32 {
+        self.state.fee
+    }
+
+    fn get_tokens(&self) -> Vec<Address> {
+        vec![
+            self.data.tok0,
+            self.data.tok1
+        ]
+    }
+
+    fn calc_amount_out(&self, amount_in: U256, tok_in: Address, tok_out: Address) -> eyre::Result<U256> {
+        check_pair(self.data.tok0, self.data.tok1, tok_in, tok_out)?;
+        Ok(self.calc_swap(amount_in, tok_in, None)?.amount_out)
+    }
+}
+
+impl From<globalStateReturn> for Slot0 {
+    fn from(value: globalStateReturn) -> Self {
+        Self {
+            tick: value.tick.try_into().unwrap(),
+            fee_protocol: 0,
+            // Algebra V1 community fee is per mille of the swap fee
+            protocol_fee: [
+                ProtocolFee { numerator: value.communityFeeToken0 as u32, denominator: 1000 },
+                ProtocolFee { numerator: value.communityFeeToken1 as u32, denominator: 1000 },
+            ],
+            sqrt_price_x96: value.price.to(),
+            unlocked: value.unlocked,
+            observation_index: value.timepointIndex,
+            observation_cardinality: 0,
+            observation_cardinality_next: 0,
+        }
+    }
+}
+
+impl AlgebraPool {
+    pub fn new(pool: Address) -> Self {
+        AlgebraPool {
+            metadata: AlgebraPoolMetadata {
+                pool,
+                protocol: PoolProtocol::Algebra,
+            },
+            data: Default::default(),
+            state: Default::default(),
+        }
+    }
+
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn sync<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        &mut self,
+        provider: P,
+        block: BlockId
+    ) -> eyre::Result<()> {
+        self.data = AlgebraPool::fetch_data(
+            &self.metadata,
+            provider.clone(),
+            block,
+        ).await?;
+
+        self.state = AlgebraPool::fetch_state(
+            &self.metadata,
+            &self.data,
+            provider.clone(),
+            block,
+        ).await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn fetch_data<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &AlgebraPoolMetadata,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<AlgebraPoolData> {
+        let algebra_pool = IAlgebraPool::IAlgebraPoolInstance::new(metadata.pool, provider.clone());
+
+        let tok0: Address = algebra_pool.token0().block(block).call().await?._0;
+        let tok1: Address = algebra_pool.token1().block(block).call().await?._0;
+        let factory: Address = algebra_pool.factory().block(block).call().await?._0;
+
+        let tick_spacing = match algebra_pool.tickSpacing().block(block).call().await {
+            Ok(ret) => {
+                let tick_spacing: i32 = ret._0.try_into()?;
+                if tick_spacing <= 0 {
+                    return Err(eyre!("INVALID_TICK_SPACING {tick_spacing}"));
+                }
+                tick_spacing as u32
+            }
+            Err(e) if is_revert(&e) => {
+                warn!(pool = ?metadata.pool, ?e, "tickSpacing() reverted, using the Algebra V1 constant");
+                ALGEBRA_TICK_SPACING
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(AlgebraPoolData {
+            factory,
+            tok0,
+            tok1,
+            tick_spacing,
+        })
+    }
+
+    #[instrument(skip(provider), level = "debug", ret)]
+    pub async fn fetch_state<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &AlgebraPoolMetadata,
+        data: &AlgebraPoolData,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<AlgebraPoolState> {
+        let algebra_pool = IAlgebraPool::IAlgebraPoolInstance::new(metadata.pool, provider.clone());
+
+        let global_state = algebra_pool.globalState().block(block).call().await?;
+        let fee = global_state.fee as u32;
+        let slot0: Slot0 = global_state.into();
+        let liquidity: u128 = algebra_pool.liquidity().block(block).call().await?._0;
+
+        // tickTable uses the same word layout as the V3 tickBitmap, without a TickLens to list the ticks
+        let tick_bitmap_index = UniswapV3Pool::get_tick_bitmap_index(
+            slot0.tick,
+            data.tick_spacing,
+        )?;
+        let word_positions: Vec<i16> = TICK_BITMAP_WORD_RANGE.map(|i| tick_bitmap_index + i).collect();
+        let words = call_batch(
+            &provider,
+            metadata.pool,
+            &word_positions.iter().map(|word_pos| tickTableCall { wordPosition: *word_pos }).collect::<Vec<_>>(),
+            block,
+        ).await?;
+        let tick_bitmap: HashMap<i16, U256> = word_positions.iter().copied().zip(words.into_iter().map(|word| word._0)).collect();
+
+        let mut initialized_ticks = Vec::new();
+        for (word_pos, word) in tick_bitmap.iter() {
+            for bit_pos in 0..256usize {
+                if word.bit(bit_pos) {
+                    initialized_ticks.push((((*word_pos as i32) << 8) + bit_pos as i32) * data.tick_spacing as i32);
+                }
+            }
+        }
+        let tick_calls = initialized_ticks
+            .iter()
+            .map(|tick| Ok(ticksCall { tick: (*tick).try_into()? }))
+            .collect::<eyre::Result<Vec<_>>>()?;
+        let tick_infos = call_batch(&provider, metadata.pool, &tick_calls, block).await?;
+        let ticks: Ticks = initialized_ticks.into_iter().zip(tick_infos).map(|(tick, tick_info)| (tick, TickInfo {
+            liquidity_gross: tick_info.liquidityTotal,
+            liquidity_net: tick_info.liquidityDelta,
+            fee_growth_outside_0_x128: tick_info.outerFeeGrowth0Token,
+            fee_growth_outside_1_x128: tick_info.outerFeeGrowth1Token,
+        })).collect();
+
+        Ok(AlgebraPoolState {
+            slot0,
+            fee,
+            liquidity,
+            tick_bitmap,
+            ticks,
+        })
+    }
+
+    /// Simulates an exact input swap at the current dynamic fee, stopping at `sqrt_price_limit_x96`, if given.
+    pub fn calc_swap(&self, amount_in: U256, tok_in: Address, sqrt_price_limit_x96: Option<U256>) -> eyre::Result<SwapResult> {
+        local::calc_amount_out(
+            amount_in,
+            tok_in,
+            sqrt_price_limit_x96,
+            local::PoolData {
+                tok0: self.data.tok0,
+                fee: self.state.fee,
+                tick_spacing: self.data.tick_spacing,
+            },
+            local::PoolState {
+                slot0: &self.state.slot0,
+                liquidity: self.state.liquidity,
+                tick_bitmap: &self.state.tick_bitmap,
+                ticks: &self.state.ticks,
+            },
+        )
+    }
+}
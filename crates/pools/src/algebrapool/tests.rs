@@ -0,0 +1,43 @@
+use alloy::primitives::U256;
+use tracing::info;
+use config::Config;
+use crate::algebrapool::abi::IAlgebraQuoter;
+use crate::algebrapool::AlgebraPool;
+use crate::test_utils::{test_networks, TestNetwork};
+
+/// Parity with the Algebra Quoter for every configured pool, both directions.
+#[tokio::test]
+async fn test_calc_swap() {
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .try_init();
+
+    let cfg = Config::default();
+    let mut checked = 0;
+    for TestNetwork { net, addresses, provider, block } in test_networks(&cfg, |addresses| addresses.algebra.quoter.is_some() && !addresses.algebra.pools.is_empty()).await {
+        let quoter = IAlgebraQuoter::new(addresses.algebra.quoter.unwrap(), provider.clone());
+
+        for (name, pool) in addresses.algebra.pools.iter() {
+            let mut pool = AlgebraPool::new(*pool);
+            pool.sync(provider.clone(), block.into()).await.unwrap();
+
+            for (tok_in, tok_out) in [(pool.data.tok0, pool.data.tok1), (pool.data.tok1, pool.data.tok0)] {
+                let amount_in = U256::from(10).pow(U256::from(6));
+                let quote = quoter.quoteExactInputSingle(tok_in, tok_out, amount_in, U256::ZERO.to())
+                    .block(block.into())
+                    .call()
+                    .await
+                    .unwrap();
+                // The first swap of a block writes a timepoint which may move the dynamic fee, price at the fee the quoter swapped at
+                let mut quoted_pool = pool.clone();
+                quoted_pool.state.fee = quote.fee as u32;
+                let amount_out_local = quoted_pool.calc_swap(amount_in, tok_in, None).unwrap().amount_out;
+                info!(?net, %name, ?tok_in, fee = pool.state.fee, fee_quoter = quote.fee, ?amount_out_local, amount_out_quoter = ?quote.amountOut);
+
+                assert_eq!(amount_out_local, quote.amountOut);
+                checked += 1;
+            }
+        }
+    }
+    assert!(checked > 0, "no Algebra pool with a quoter configured");
+}
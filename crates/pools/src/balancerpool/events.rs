@@ -0,0 +1,90 @@
+use alloy::primitives::{Address, B256, I256, U256};
+use alloy::rpc::types::Log;
+use alloy::sol_types::SolEvent;
+use eyre::eyre;
+use types::pool::Pool;
+use crate::balancerpool::abi::IVault;
+use crate::balancerpool::BalancerPool;
+
+/// Vault events that change the balances of a pool.
+///
+/// Swap fee and weight updates are emitted by the pool itself and need a fetch_state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VaultEvent {
+    Swap(IVault::Swap),
+    PoolBalanceChanged(IVault::PoolBalanceChanged),
+}
+
+impl VaultEvent {
+    /// Decodes a Vault log, None for the events which do not touch pool balances.
+    pub fn decode(log: &Log) -> eyre::Result<Option<Self>> {
+        let event = match log.topic0() {
+            Some(topic) if *topic == IVault::Swap::SIGNATURE_HASH => {
+                Self::Swap(log.log_decode::<IVault::Swap>()?.inner.data)
+            }
+            Some(topic) if *topic == IVault::PoolBalanceChanged::SIGNATURE_HASH => {
+                Self::PoolBalanceChanged(log.log_decode::<IVault::PoolBalanceChanged>()?.inner.data)
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(event))
+    }
+
+    /// PoolId of the pool the event belongs to.
+    pub fn pool_id(&self) -> B256 {
+        match self {
+            Self::Swap(event) => event.poolId,
+            Self::PoolBalanceChanged(event) => event.poolId,
+        }
+    }
+}
+
+impl BalancerPool {
+    /// Applies an event of this pool to the balances, events of other pools are ignored.
+    pub fn apply_event(&mut self, event: &VaultEvent) -> eyre::Result<()> {
+        if event.pool_id() != self.get_id() {
+            return Ok(());
+        }
+        if self.state.balances.len() != self.data.tokens.len() {
+            return Err(eyre!("POOL_NOT_SYNCED {}", self.metadata.pool_id));
+        }
+
+        match event {
+            VaultEvent::Swap(event) => {
+                let i = self.token_index(event.tokenIn)?;
+                let j = self.token_index(event.tokenOut)?;
+                // The amount in includes the swap fee, which stays in the pool
+                let balances = &mut self.state.balances;
+                balances[i] += event.amountIn;
+                balances[j] = balances[j].checked_sub(event.amountOut).ok_or(eyre!("BALANCE_UNDERFLOW"))?;
+            }
+            VaultEvent::PoolBalanceChanged(event) => {
+                if event.tokens != self.data.tokens
+                    || event.deltas.len() != event.tokens.len()
+                    || event.protocolFeeAmounts.len() != event.tokens.len()
+                {
+                    return Err(eyre!("TOKENS_MISMATCH {}", event.poolId));
+                }
+                // Joins and exits both pay the protocol fees due out of the pool balances
+                for ((balance, delta), protocol_fee) in self.state.balances.iter_mut().zip(&event.deltas).zip(&event.protocolFeeAmounts) {
+                    *balance = apply_delta(*balance, *delta)
+                        .and_then(|balance| balance.checked_sub(*protocol_fee))
+                        .ok_or(eyre!("BALANCE_UNDERFLOW"))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn token_index(&self, token: Address) -> eyre::Result<usize> {
+        self.data.tokens.iter().position(|tok| *tok == token).ok_or(eyre!("TOKEN_NOT_IN_POOL"))
+    }
+}
+
+fn apply_delta(balance: U256, delta: I256) -> Option<U256> {
+    if delta.is_negative() {
+        balance.checked_sub(delta.unsigned_abs())
+    } else {
+        balance.checked_add(delta.into_raw())
+    }
+}
@@ -0,0 +1,191 @@
+//! Port of Balancer's LogExpMath, exponentiation and logarithm with 18 decimal fixed point numbers.
+//!
+//! Every step, including the truncating signed divisions, follows the contract so powers are bit-exact.
+
+use alloy::primitives::{I256, U256};
+use eyre::eyre;
+use lazy_static::lazy_static;
+
+fn int(value: &str) -> I256 {
+    I256::from_dec_str(value).unwrap()
+}
+
+lazy_static! {
+    static ref ONE_18: I256 = int("1000000000000000000");
+    static ref ONE_20: I256 = int("100000000000000000000");
+    static ref ONE_36: I256 = int("1000000000000000000000000000000000000");
+
+    static ref MAX_NATURAL_EXPONENT: I256 = int("130000000000000000000");
+    static ref MIN_NATURAL_EXPONENT: I256 = int("-41000000000000000000");
+
+    // ln(x) of x in this range is computed with 36 decimals, as the 18 decimal version is too imprecise around 1
+    static ref LN_36_LOWER_BOUND: I256 = int("900000000000000000");
+    static ref LN_36_UPPER_BOUND: I256 = int("1100000000000000000");
+
+    static ref MILD_EXPONENT_BOUND: U256 = (U256::from(1) << 254) / U256::from(100_000_000_000_000_000_000u128);
+
+    // 18 decimal exponents, e^x without decimals
+    static ref X0: I256 = int("128000000000000000000");
+    static ref A0: I256 = int("38877084059945950922200000000000000000000000000000000000");
+    static ref X1: I256 = int("64000000000000000000");
+    static ref A1: I256 = int("6235149080811616882910000000");
+
+    // 20 decimal exponents 2^5 to 2^-4 and e^x
+    static ref XS: [I256; 10] = [
+        int("3200000000000000000000"),
+        int("1600000000000000000000"),
+        int("800000000000000000000"),
+        int("400000000000000000000"),
+        int("200000000000000000000"),
+        int("100000000000000000000"),
+        int("50000000000000000000"),
+        int("25000000000000000000"),
+        int("12500000000000000000"),
+        int("6250000000000000000"),
+    ];
+    static ref AS: [I256; 10] = [
+        int("7896296018268069516100000000000000"),
+        int("888611052050787263676000000"),
+        int("298095798704172827474000"),
+        int("5459815003314423907810"),
+        int("738905609893065022723"),
+        int("271828182845904523536"),
+        int("164872127070012814685"),
+        int("128402541668774148407"),
+        int("113314845306682631683"),
+        int("106449445891785942956"),
+    ];
+}
+
+/// x^y, both with 18 decimals, as LogExpMath.pow, exp(ln(x) * y).
+pub fn pow(x: U256, y: U256) -> eyre::Result<U256> {
+    if y.is_zero() {
+        return Ok(ONE_18.into_raw());
+    }
+    if x.is_zero() {
+        return Ok(U256::ZERO);
+    }
+    if x.bit(255) {
+        return Err(eyre!("X_OUT_OF_BOUNDS"));
+    }
+    let x = I256::from_raw(x);
+    if y >= *MILD_EXPONENT_BOUND {
+        return Err(eyre!("Y_OUT_OF_BOUNDS"));
+    }
+    let y = I256::from_raw(y);
+
+    let mut logx_times_y = if *LN_36_LOWER_BOUND < x && x < *LN_36_UPPER_BOUND {
+        let ln_36_x = ln_36(x);
+        // ln_36_x has 36 decimals, multiplying by y in two parts keeps the product in range
+        (ln_36_x / *ONE_18) * y + ((ln_36_x % *ONE_18) * y) / *ONE_18
+    } else {
+        ln(x) * y
+    };
+    logx_times_y /= *ONE_18;
+
+    if logx_times_y < *MIN_NATURAL_EXPONENT || logx_times_y > *MAX_NATURAL_EXPONENT {
+        return Err(eyre!("PRODUCT_OUT_OF_BOUNDS"));
+    }
+    Ok(exp(logx_times_y)?.into_raw())
+}
+
+/// e^x with 18 decimals, as LogExpMath.exp.
+pub fn exp(x: I256) -> eyre::Result<I256> {
+    if x < *MIN_NATURAL_EXPONENT || x > *MAX_NATURAL_EXPONENT {
+        return Err(eyre!("INVALID_EXPONENT"));
+    }
+    if x.is_negative() {
+        return Ok((*ONE_18 * *ONE_18) / exp(-x)?);
+    }
+
+    // e^x = e^(x0) * e^(x1) * ... with the largest terms stored without decimals
+    let mut x = x;
+    let first_an = if x >= *X0 {
+        x -= *X0;
+        *A0
+    } else if x >= *X1 {
+        x -= *X1;
+        *A1
+    } else {
+        I256::ONE
+    };
+
+    // 20 decimals from here on
+    x *= I256::try_from(100).unwrap();
+    let mut product = *ONE_20;
+    for (xn, an) in XS.iter().zip(AS.iter()).take(8) {
+        if x >= *xn {
+            x -= *xn;
+            product = (product * *an) / *ONE_20;
+        }
+    }
+
+    // Taylor series of the remainder, which is below 2^-3
+    let mut series_sum = *ONE_20;
+    let mut term = x;
+    series_sum += term;
+    for k in 2..=12 {
+        term = ((term * x) / *ONE_20) / I256::try_from(k).unwrap();
+        series_sum += term;
+    }
+
+    Ok((((product * series_sum) / *ONE_20) * first_an) / I256::try_from(100).unwrap())
+}
+
+/// ln(a) with 18 decimals, as LogExpMath._ln.
+fn ln(a: I256) -> I256 {
+    if a < *ONE_18 {
+        return -ln((*ONE_18 * *ONE_18) / a);
+    }
+
+    let mut a = a;
+    let mut sum = I256::ZERO;
+    if a >= *A0 * *ONE_18 {
+        a /= *A0;
+        sum += *X0;
+    }
+    if a >= *A1 * *ONE_18 {
+        a /= *A1;
+        sum += *X1;
+    }
+
+    // 20 decimals from here on
+    let hundred = I256::try_from(100).unwrap();
+    sum *= hundred;
+    a *= hundred;
+    for (xn, an) in XS.iter().zip(AS.iter()) {
+        if a >= *an {
+            a = (a * *ONE_20) / *an;
+            sum += *xn;
+        }
+    }
+
+    // ln(a) = 2 * artanh(z) of z = (a - 1) / (a + 1), odd terms of the series
+    let z = ((a - *ONE_20) * *ONE_20) / (a + *ONE_20);
+    let z_squared = (z * z) / *ONE_20;
+    let mut num = z;
+    let mut series_sum = num;
+    for k in [3, 5, 7, 9, 11] {
+        num = (num * z_squared) / *ONE_20;
+        series_sum += num / I256::try_from(k).unwrap();
+    }
+    series_sum *= I256::try_from(2).unwrap();
+
+    (sum + series_sum) / hundred
+}
+
+/// ln(x) with 36 decimals of x close to 1, as LogExpMath._ln_36.
+fn ln_36(x: I256) -> I256 {
+    let x = x * *ONE_18;
+
+    let z = ((x - *ONE_36) * *ONE_36) / (x + *ONE_36);
+    let z_squared = (z * z) / *ONE_36;
+    let mut num = z;
+    let mut series_sum = num;
+    for k in [3, 5, 7, 9, 11, 13, 15] {
+        num = (num * z_squared) / *ONE_36;
+        series_sum += num / I256::try_from(k).unwrap();
+    }
+
+    series_sum * I256::try_from(2).unwrap()
+}
@@ -0,0 +1,86 @@
+use alloy::primitives::U256;
+use eyre::eyre;
+use crate::balancerpool::log_exp_math;
+
+/// 1.0 in 18 decimal fixed point, which weights, fees and scaling factors are expressed in.
+pub const ONE: U256 = U256::from_limbs([1_000_000_000_000_000_000, 0, 0, 0]);
+const TWO: U256 = U256::from_limbs([2_000_000_000_000_000_000, 0, 0, 0]);
+const FOUR: U256 = U256::from_limbs([4_000_000_000_000_000_000, 0, 0, 0]);
+/// Relative error of LogExpMath.pow, 1e-14, which powUp rounds up by.
+const MAX_POW_RELATIVE_ERROR: U256 = U256::from_limbs([10_000, 0, 0, 0]);
+/// Swaps cannot take in more than 30% of the balance of the token in.
+const MAX_IN_RATIO: U256 = U256::from_limbs([300_000_000_000_000_000, 0, 0, 0]);
+
+pub fn mul_down(a: U256, b: U256) -> U256 {
+    a * b / ONE
+}
+
+pub fn mul_up(a: U256, b: U256) -> U256 {
+    let product = a * b;
+    if product.is_zero() {
+        U256::ZERO
+    } else {
+        (product - U256::from(1)) / ONE + U256::from(1)
+    }
+}
+
+pub fn div_down(a: U256, b: U256) -> eyre::Result<U256> {
+    if b.is_zero() {
+        return Err(eyre!("ZERO_DIVISION"));
+    }
+    Ok(a * ONE / b)
+}
+
+pub fn div_up(a: U256, b: U256) -> eyre::Result<U256> {
+    if b.is_zero() {
+        return Err(eyre!("ZERO_DIVISION"));
+    }
+    if a.is_zero() {
+        return Ok(U256::ZERO);
+    }
+    Ok((a * ONE - U256::from(1)) / b + U256::from(1))
+}
+
+pub fn complement(x: U256) -> U256 {
+    if x < ONE { ONE - x } else { U256::ZERO }
+}
+
+/// x^y rounded up, as FixedPoint.powUp.
+///
+/// Legacy pools always go through LogExpMath, the current FixedPoint special cases exponents 1, 2 and 4.
+pub fn pow_up(x: U256, y: U256, legacy: bool) -> eyre::Result<U256> {
+    if !legacy {
+        if y == ONE {
+            return Ok(x);
+        } else if y == TWO {
+            return Ok(mul_up(x, x));
+        } else if y == FOUR {
+            let square = mul_up(x, x);
+            return Ok(mul_up(square, square));
+        }
+    }
+    let raw = log_exp_math::pow(x, y)?;
+    let max_error = mul_up(raw, MAX_POW_RELATIVE_ERROR) + U256::from(1);
+    Ok(raw + max_error)
+}
+
+/// WeightedMath._calcOutGivenIn, upscaled balances and amount with the swap fee already taken.
+pub fn calc_out_given_in(
+    balance_in: U256,
+    weight_in: U256,
+    balance_out: U256,
+    weight_out: U256,
+    amount_in: U256,
+    legacy: bool,
+) -> eyre::Result<U256> {
+    if amount_in > mul_down(balance_in, MAX_IN_RATIO) {
+        return Err(eyre!("MAX_IN_RATIO"));
+    }
+
+    // balance_out * (1 - (balance_in / (balance_in + amount_in))^(weight_in / weight_out))
+    let base = div_up(balance_in, balance_in + amount_in)?;
+    let exponent = div_down(weight_in, weight_out)?;
+    let power = pow_up(base, exponent, legacy)?;
+
+    Ok(mul_down(balance_out, complement(power)))
+}
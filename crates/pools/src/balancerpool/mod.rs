@@ -0,0 +1,203 @@
+mod abi;
+pub mod events;
+pub mod log_exp_math;
+pub mod math;
+#[cfg(test)]
+mod tests;
+
+use alloy::eips::BlockId;
+use alloy::network::Network;
+use alloy::primitives::{Address, B256, U256};
+use alloy::providers::Provider;
+use alloy::transports::Transport;
+use eyre::eyre;
+use tracing::instrument;
+use types::pool::{Pool, PoolClass, PoolProtocol};
+use crate::balancerpool::abi::{IERC20, IVault, IWeightedPool};
+use crate::balancerpool::math::mul_up;
+use crate::rpc::is_revert;
+
+#[derive(Debug, Clone)]
+pub struct BalancerPoolMetadata {
+    pub vault: Address,
+    pub pool_id: B256,
+    /// Pool contract, the first 20 bytes of the poolId.
+    pub pool: Address,
+    pub protocol: PoolProtocol,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BalancerPoolData {
+    pub tokens: Vec<Address>,
+    /// 10^(18 - decimals) of each token, upscaling amounts to 18 decimals.
+    pub scaling_factors: Vec<U256>,
+    /// Normalized weights with 18 decimals, summing to 1e18.
+    pub weights: Vec<U256>,
+    /// Pools without version() use the FixedPoint.powUp that always calls LogExpMath.
+    pub legacy: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BalancerPoolState {
+    /// Balances held by the Vault for the pool, in token decimals.
+    pub balances: Vec<U256>,
+    /// Swap fee with 18 decimals, 3e15 being 0.3%.
+    pub swap_fee: U256,
+}
+
+/// Balancer V2 weighted pool, whose balances live in the Vault and which is identified by its poolId.
+#[derive(Debug, Clone)]
+pub struct BalancerPool {
+    pub metadata: BalancerPoolMetadata,
+    pub data: BalancerPoolData,
+    pub state: BalancerPoolState,
+}
+
+impl Pool for BalancerPool {
+    fn get_class(&self) -> PoolClass {
+        PoolClass::Balancer
+    }
+
+    fn get_protocol(&self) -> PoolProtocol {
+        self.metadata.protocol
+    }
+
+    fn get_address(&self) -> Address {
+        self.metadata.pool
+    }
+
+    fn get_id(&self) -> B256 {
+        self.metadata.pool_id
+    }
+
+    /// Swap fee in hundredths of a bip, as V3 fee tiers.
+    fn get_fee(&self) -> u32 {
+        (self.state.swap_fee / U256::from(1_000_000_000_000u64)).saturating_to::<u32>()
+    }
+
+    fn get_tokens(&self) -> Vec<Address> {
+        self.data.tokens.clone()
+    }
+
+    fn calc_amount_out(&self, amount_in: U256, tok_in: Address, tok_out: Address) -> eyre::Result<U256> {
+        let token_index = |token: Address| self.data.tokens.iter().position(|tok| *tok == token);
+        match (token_index(tok_in), token_index(tok_out)) {
+            (Some(i), Some(j)) if i != j => self.calc_out_given_in(i, j, amount_in),
+            _ => Err(eyre!("TOKEN_NOT_IN_POOL")),
+        }
+    }
+}
+
+impl BalancerPool {
+    pub fn new(vault: Address, pool_id: B256) -> Self {
+        BalancerPool {
+            metadata: BalancerPoolMetadata {
+                vault,
+                pool_id,
+                pool: Address::from_slice(&pool_id[..20]),
+                protocol: PoolProtocol::BalancerV2,
+            },
+            data: Default::default(),
+            state: Default::default(),
+        }
+    }
+
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn sync<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        &mut self,
+        provider: P,
+        block: BlockId
+    ) -> eyre::Result<()> {
+        self.data = BalancerPool::fetch_data(
+            &self.metadata,
+            provider.clone(),
+            block,
+        ).await?;
+
+        self.state = BalancerPool::fetch_state(
+            &self.metadata,
+            provider.clone(),
+            block,
+        ).await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn fetch_data<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &BalancerPoolMetadata,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<BalancerPoolData> {
+        let vault = IVault::new(metadata.vault, provider.clone());
+        let pool = IWeightedPool::new(metadata.pool, provider.clone());
+
+        let tokens = vault.getPoolTokens(metadata.pool_id).block(block).call().await?.tokens;
+        let mut scaling_factors = Vec::with_capacity(tokens.len());
+        for token in tokens.iter() {
+            let decimals = IERC20::new(*token, provider.clone()).decimals().block(block).call().await?._0;
+            let exponent = 18u8.checked_sub(decimals).ok_or(eyre!("UNSUPPORTED_DECIMALS {decimals}"))?;
+            scaling_factors.push(U256::from(10).pow(U256::from(exponent)));
+        }
+
+        let weights = pool.getNormalizedWeights().block(block).call().await?._0;
+        if weights.len() != tokens.len() {
+            return Err(eyre!("NOT_A_WEIGHTED_POOL {}", metadata.pool_id));
+        }
+        // Pools deployed before version() use the legacy pow approximation
+        let legacy = match pool.version().block(block).call().await {
+            Ok(_) => false,
+            Err(e) if is_revert(&e) => true,
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(BalancerPoolData {
+            tokens,
+            scaling_factors,
+            weights,
+            legacy,
+        })
+    }
+
+    #[instrument(skip(provider), level = "debug", ret)]
+    pub async fn fetch_state<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &BalancerPoolMetadata,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<BalancerPoolState> {
+        let vault = IVault::new(metadata.vault, provider.clone());
+        let pool = IWeightedPool::new(metadata.pool, provider.clone());
+
+        let balances = vault.getPoolTokens(metadata.pool_id).block(block).call().await?.balances;
+        let swap_fee = pool.getSwapFeePercentage().block(block).call().await?._0;
+
+        Ok(BalancerPoolState {
+            balances,
+            swap_fee,
+        })
+    }
+
+    /// Amount of token `j` out for `amount_in` of token `i`, as BaseMinimalSwapInfoPool.onSwap given in.
+    ///
+    /// The fee is taken on the raw amount in, before upscaling, and the amount out is rounded down.
+    pub fn calc_out_given_in(&self, i: usize, j: usize, amount_in: U256) -> eyre::Result<U256> {
+        let n = self.data.tokens.len();
+        if i >= n || j >= n || self.state.balances.len() != n {
+            return Err(eyre!("INVALID_TOKEN_INDEX"));
+        }
+        let amount_in = amount_in - mul_up(amount_in, self.state.swap_fee);
+
+        let scaling_in = self.data.scaling_factors[i];
+        let scaling_out = self.data.scaling_factors[j];
+        let amount_out = math::calc_out_given_in(
+            self.state.balances[i] * scaling_in,
+            self.data.weights[i],
+            self.state.balances[j] * scaling_out,
+            self.data.weights[j],
+            amount_in * scaling_in,
+            self.data.legacy,
+        )?;
+
+        Ok(amount_out / scaling_out)
+    }
+}
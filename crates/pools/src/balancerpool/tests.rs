@@ -0,0 +1,161 @@
+use alloy::primitives::{Address, Bytes, B256, I256, U256};
+use tracing::info;
+use config::Config;
+use types::pool::Pool;
+use crate::balancerpool::abi::IVault;
+use crate::balancerpool::events::VaultEvent;
+use crate::balancerpool::{log_exp_math, BalancerPool};
+use crate::test_utils::{ether, test_networks, units, TestNetwork};
+
+/// Parity with Vault.queryBatchSwap for every token pair of the configured pools.
+#[tokio::test]
+async fn test_calc_amount_out() {
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .try_init();
+
+    let cfg = Config::default();
+    for TestNetwork { net, addresses, provider, block } in test_networks(&cfg, |addresses| addresses.balancer.vault.is_some() && !addresses.balancer.pools.is_empty()).await {
+        let vault = addresses.balancer.vault.unwrap();
+
+        for (name, pool_id) in addresses.balancer.pools.iter() {
+            let mut pool = BalancerPool::new(vault, *pool_id);
+            pool.sync(provider.clone(), block.into()).await.unwrap();
+            let contract = IVault::new(vault, provider.clone());
+
+            for i in 0..pool.data.tokens.len() {
+                for j in 0..pool.data.tokens.len() {
+                    if i == j {
+                        continue;
+                    }
+                    // 0.1% of the balance in, well within the 30% max in ratio
+                    let amount_in = pool.state.balances[i] / U256::from(1000);
+                    let amount_out_local = pool.calc_amount_out(amount_in, pool.data.tokens[i], pool.data.tokens[j]).unwrap();
+
+                    let swaps = vec![IVault::BatchSwapStep {
+                        poolId: *pool_id,
+                        assetInIndex: U256::from(i),
+                        assetOutIndex: U256::from(j),
+                        amount: amount_in,
+                        userData: Bytes::new(),
+                    }];
+                    let funds = IVault::FundManagement {
+                        sender: Address::ZERO,
+                        fromInternalBalance: false,
+                        recipient: Address::ZERO,
+                        toInternalBalance: false,
+                    };
+                    let deltas = contract.queryBatchSwap(0, swaps, pool.data.tokens.clone(), funds)
+                        .block(block.into())
+                        .call()
+                        .await
+                        .unwrap()
+                        .assetDeltas;
+                    let amount_out_vault = (-deltas[j]).into_raw();
+                    info!(?net, %name, i, j, legacy = pool.data.legacy, ?amount_out_local, ?amount_out_vault);
+
+                    assert_eq!(amount_out_local, amount_out_vault);
+                }
+            }
+        }
+    }
+}
+
+/// Reference values from a line by line port of LogExpMath.
+#[test]
+fn test_log_exp_math() {
+    let one = ether(1);
+    assert_eq!(log_exp_math::pow(ether(2), one / U256::from(2)).unwrap(), U256::from(1414213562373095047u64));
+    assert_eq!(log_exp_math::pow(one / U256::from(2), ether(3)).unwrap(), U256::from(125000000000000000u64));
+    assert_eq!(log_exp_math::pow(ether(3), ether(5) / U256::from(2)).unwrap(), U256::from(15588457268119895593u64));
+    // Base close to 1 goes through the 36 decimal logarithm
+    assert_eq!(log_exp_math::pow(U256::from(1050000000000000000u64), U256::from(2500000000000000000u64)).unwrap(), U256::from(1129726321947045720u64));
+    assert_eq!(log_exp_math::pow(ether(7), U256::ZERO).unwrap(), one);
+
+    assert_eq!(log_exp_math::exp(I256::try_from(10u64.pow(18)).unwrap()).unwrap(), I256::try_from(2718281828459045235u64).unwrap());
+    assert_eq!(log_exp_math::exp(I256::try_from(-3 * 10i64.pow(18)).unwrap()).unwrap(), I256::try_from(49787068367863942u64).unwrap());
+    assert!(log_exp_math::exp(I256::try_from(131u128 * 10u128.pow(18)).unwrap()).is_err());
+}
+
+fn bal_weth_pool(legacy: bool) -> BalancerPool {
+    let mut pool = BalancerPool::new(Address::ZERO, B256::repeat_byte(1));
+    pool.data.tokens = vec![Address::repeat_byte(0xba), Address::repeat_byte(0xee)];
+    pool.data.scaling_factors = vec![U256::from(1), U256::from(1)];
+    pool.data.weights = vec![ether(8) / U256::from(10), ether(2) / U256::from(10)];
+    pool.data.legacy = legacy;
+    pool.state.balances = vec![ether(30_000_000), ether(15_000)];
+    pool.state.swap_fee = ether(1) / U256::from(100);
+    pool
+}
+
+#[test]
+fn test_weighted_pool() {
+    // Weight ratio 4 is special cased by the current FixedPoint.powUp only
+    let legacy = bal_weth_pool(true);
+    let pool = bal_weth_pool(false);
+    let (bal, weth) = (pool.data.tokens[0], pool.data.tokens[1]);
+    assert_eq!(legacy.calc_amount_out(ether(1000), bal, weth).unwrap(), U256::from(1979836660630425000u64));
+    assert_eq!(pool.calc_amount_out(ether(1000), bal, weth).unwrap(), U256::from(1979836660780410000u64));
+    assert_eq!(legacy.calc_amount_out(ether(1), weth, bal).unwrap(), U256::from(494979581960610000000u128));
+    assert_eq!(pool.calc_amount_out(ether(1), weth, bal).unwrap(), U256::from(494979581960610000000u128));
+    assert_eq!(pool.get_fee(), 10_000);
+
+    // Above 30% of the balance in
+    assert!(pool.calc_amount_out(ether(5000), weth, bal).is_err());
+    assert!(pool.calc_amount_out(ether(1), weth, weth).is_err());
+    assert!(pool.calc_amount_out(ether(1), weth, Address::ZERO).is_err());
+
+    // WETH/USDC/WBTC 50/25/25 with 0.3% fee
+    let mut pool = BalancerPool::new(Address::ZERO, B256::repeat_byte(2));
+    pool.data.tokens = vec![Address::repeat_byte(0), Address::repeat_byte(1), Address::repeat_byte(2)];
+    pool.data.scaling_factors = vec![U256::from(1), units(1, 12), units(1, 10)];
+    pool.data.weights = vec![ether(5) / U256::from(10), ether(25) / U256::from(100), ether(25) / U256::from(100)];
+    pool.state.balances = vec![ether(2000), units(4_000_000, 6), units(100, 8)];
+    pool.state.swap_fee = ether(3) / U256::from(1000);
+    assert_eq!(pool.calc_out_given_in(1, 2, units(10_000, 6)).unwrap(), U256::from(24863028u64));
+    assert_eq!(pool.calc_out_given_in(0, 1, ether(1)).unwrap(), U256::from(3985019953u64));
+}
+
+#[test]
+fn test_apply_events() {
+    let mut pool = bal_weth_pool(false);
+    let pool_id = pool.get_id();
+    let (bal, weth) = (pool.data.tokens[0], pool.data.tokens[1]);
+
+    let amount_out = pool.calc_amount_out(ether(1000), bal, weth).unwrap();
+    pool.apply_event(&VaultEvent::Swap(IVault::Swap {
+        poolId: pool_id,
+        tokenIn: bal,
+        tokenOut: weth,
+        amountIn: ether(1000),
+        amountOut: amount_out,
+    })).unwrap();
+    assert_eq!(pool.state.balances, vec![ether(30_001_000), ether(15_000) - amount_out]);
+
+    // Events of other pools are ignored
+    let mut other = VaultEvent::Swap(IVault::Swap {
+        poolId: B256::repeat_byte(2),
+        tokenIn: bal,
+        tokenOut: weth,
+        amountIn: ether(1000),
+        amountOut: amount_out,
+    });
+    pool.apply_event(&other).unwrap();
+    assert_eq!(pool.state.balances[0], ether(30_001_000));
+
+    // Exit paying 1 BAL of protocol fees
+    pool.apply_event(&VaultEvent::PoolBalanceChanged(IVault::PoolBalanceChanged {
+        poolId: pool_id,
+        liquidityProvider: Address::ZERO,
+        tokens: pool.data.tokens.clone(),
+        deltas: vec![-I256::try_from(ether(1000)).unwrap(), I256::ZERO],
+        protocolFeeAmounts: vec![ether(1), U256::ZERO],
+    })).unwrap();
+    assert_eq!(pool.state.balances[0], ether(29_999_999));
+
+    if let VaultEvent::Swap(event) = &mut other {
+        event.poolId = pool_id;
+        event.amountOut = ether(20_000);
+    }
+    assert!(pool.apply_event(&other).is_err());
+}
@@ -0,0 +1,45 @@
+use alloy::sol;
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    #[sol(rpc)]
+    contract ICurveCryptoPool {
+        event TokenExchange(address indexed buyer, uint256 sold_id, uint256 tokens_sold, uint256 bought_id, uint256 tokens_bought);
+        function coins(uint256 i) external view returns (address);
+        function balances(uint256 i) external view returns (uint256);
+        function D() external view returns (uint256);
+        function initial_A_gamma() external view returns (uint256);
+        function future_A_gamma() external view returns (uint256);
+        function initial_A_gamma_time() external view returns (uint256);
+        function future_A_gamma_time() external view returns (uint256);
+        function mid_fee() external view returns (uint256);
+        function out_fee() external view returns (uint256);
+        function fee_gamma() external view returns (uint256);
+        function get_dy(uint256 i, uint256 j, uint256 dx) external view returns (uint256);
+        function exchange(uint256 i, uint256 j, uint256 dx, uint256 min_dy) external payable returns (uint256);
+    }
+}
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    #[sol(rpc)]
+    contract ICurveTriCryptoPool {
+        function price_scale(uint256 k) external view returns (uint256);
+    }
+}
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    #[sol(rpc)]
+    contract ICurveTwoCryptoPool {
+        function price_scale() external view returns (uint256);
+    }
+}
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    #[sol(rpc)]
+    contract IERC20 {
+        function decimals() external view returns (uint8);
+    }
+}
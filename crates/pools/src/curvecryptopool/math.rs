@@ -0,0 +1,256 @@
+use alloy::primitives::U256;
+use eyre::eyre;
+use crate::curvepool::math::PRECISION;
+
+/// A is stored multiplied by N^N and this.
+pub const A_MULTIPLIER: U256 = U256::from_limbs([10_000, 0, 0, 0]);
+const MIN_GAMMA: U256 = U256::from_limbs([10_000_000_000, 0, 0, 0]);
+/// The two coin math allows a narrower gamma than tricrypto.
+const MAX_GAMMA_TWO_COINS: U256 = U256::from_limbs([20_000_000_000_000_000, 0, 0, 0]);
+const MAX_GAMMA: U256 = U256::from_limbs([50_000_000_000_000_000, 0, 0, 0]);
+/// Newton iterations of newton_D, newton_y and geometric_mean before they give up.
+const MAX_ITERATIONS: usize = 255;
+
+/// A and gamma ramping linearly between two packed values, as CurveCryptoSwap._A_gamma.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AGammaRamp {
+    pub initial_a: U256,
+    pub initial_gamma: U256,
+    pub future_a: U256,
+    pub future_gamma: U256,
+    pub initial_time: u64,
+    pub future_time: u64,
+}
+
+impl AGammaRamp {
+    /// Splits A << 128 | gamma.
+    pub fn unpack(a_gamma: U256) -> (U256, U256) {
+        (a_gamma >> 128, a_gamma & U256::from(u128::MAX))
+    }
+
+    pub fn at(&self, timestamp: u64) -> (U256, U256) {
+        if timestamp >= self.future_time || self.future_time <= self.initial_time {
+            return (self.future_a, self.future_gamma);
+        }
+        let t1 = U256::from(self.future_time - self.initial_time);
+        let t0 = U256::from(timestamp.saturating_sub(self.initial_time));
+        let t2 = t1 - t0;
+        (
+            (self.initial_a * t2 + self.future_a * t0) / t1,
+            (self.initial_gamma * t2 + self.future_gamma * t0) / t1,
+        )
+    }
+}
+
+pub(crate) fn check_a_gamma(ann: U256, gamma: U256, n: usize) -> eyre::Result<()> {
+    let nn = U256::from(n.pow(n as u32));
+    // CurveCryptoSwap2 bounds A wider and gamma tighter than CurveCryptoSwap (tricrypto)
+    let (min_a, max_a, max_gamma) = if n == 2 {
+        (nn * A_MULTIPLIER / U256::from(10), nn * A_MULTIPLIER * U256::from(100_000), MAX_GAMMA_TWO_COINS)
+    } else {
+        (nn * A_MULTIPLIER / U256::from(100), nn * A_MULTIPLIER * U256::from(1000), MAX_GAMMA)
+    };
+    if ann < min_a || ann > max_a {
+        return Err(eyre!("UNSAFE_A"));
+    }
+    if gamma < MIN_GAMMA || gamma > max_gamma {
+        return Err(eyre!("UNSAFE_GAMMA"));
+    }
+    Ok(())
+}
+
+/// x * 1e18 / d within [1e16, 1e20], what newton_y needs to converge.
+fn check_frac(x: U256, d: U256) -> eyre::Result<()> {
+    let frac = x * PRECISION / d;
+    if frac < U256::from(10_000_000_000_000_000u64) || frac > U256::from(100_000_000_000_000_000_000u128) {
+        return Err(eyre!("UNSAFE_X"));
+    }
+    Ok(())
+}
+
+fn sorted(x: &[U256]) -> Vec<U256> {
+    let mut x = x.to_vec();
+    x.sort_by(|a, b| b.cmp(a));
+    x
+}
+
+/// |1e18 + gamma - K0| + 1.
+fn g1k0(gamma: U256, k0: U256) -> U256 {
+    let g1k0 = gamma + PRECISION;
+    if g1k0 > k0 { g1k0 - k0 + U256::from(1) } else { k0 - g1k0 + U256::from(1) }
+}
+
+/// D / (A * N^N) * g1k0^2 / gamma^2.
+fn mul1(d: U256, gamma: U256, g1k0: U256, ann: U256) -> U256 {
+    PRECISION * d / gamma * g1k0 / gamma * g1k0 * A_MULTIPLIER / ann
+}
+
+/// Geometric mean of `x`, which must be sorted from high to low unless `sort`.
+///
+/// Two coin pools inline a cheaper iteration that rounds differently, both are reproduced.
+pub fn geometric_mean(x: &[U256], sort: bool) -> eyre::Result<U256> {
+    let n = x.len();
+    let x = if sort { sorted(x) } else { x.to_vec() };
+    let mut d = x[0];
+    for _ in 0..MAX_ITERATIONS {
+        let d_prev = d;
+        d = if n == 2 {
+            (d + x[0] * x[1] / d) / U256::from(2)
+        } else {
+            let tmp = x.iter().fold(PRECISION, |tmp, x| tmp * *x / d);
+            d * (U256::from(n - 1) * PRECISION + tmp) / (U256::from(n) * PRECISION)
+        };
+        let diff = d.abs_diff(d_prev);
+        if diff <= U256::from(1) || diff * PRECISION < d {
+            return Ok(d);
+        }
+    }
+    Err(eyre!("GEOMETRIC_MEAN_DID_NOT_CONVERGE"))
+}
+
+/// fee_gamma / (fee_gamma + 1 - K) with K = prod(x) / (sum(x) / N)^N, 1e18 when the pool is balanced.
+pub fn reduction_coefficient(x: &[U256], fee_gamma: U256) -> U256 {
+    let n = x.len();
+    let s = x.iter().fold(U256::ZERO, |s, x| s + *x);
+    let k = if n == 2 {
+        U256::from(4) * PRECISION * x[0] / s * x[1] / s
+    } else {
+        x.iter().fold(PRECISION, |k, x| k * U256::from(n) * *x / s)
+    };
+    if fee_gamma.is_zero() {
+        return k;
+    }
+    fee_gamma * PRECISION / (fee_gamma + PRECISION - k)
+}
+
+/// Invariant D of the balances `x_unsorted` in 18 decimals and price scale, as Math.newton_D.
+pub fn newton_d(ann: U256, gamma: U256, x_unsorted: &[U256]) -> eyre::Result<U256> {
+    let n = x_unsorted.len();
+    check_a_gamma(ann, gamma, n)?;
+    let x = sorted(x_unsorted);
+
+    if x[0] < U256::from(1_000_000_000) || x[0] > U256::from(10).pow(U256::from(33)) {
+        return Err(eyre!("UNSAFE_X"));
+    }
+    // x[i] / x[0] above 1e-4 for two coins, 1e-7 otherwise
+    let min_frac = U256::from(if n == 2 { 100_000_000_000_000u64 } else { 100_000_000_000 });
+    if x[1..].iter().any(|x_i| *x_i * PRECISION / x[0] < min_frac) {
+        return Err(eyre!("UNSAFE_X"));
+    }
+
+    let n_coins = U256::from(n);
+    let mut d = n_coins * geometric_mean(&x, false)?;
+    let s = x.iter().fold(U256::ZERO, |s, x| s + *x);
+
+    for _ in 0..MAX_ITERATIONS {
+        let d_prev = d;
+
+        let k0 = if n == 2 {
+            U256::from(4) * PRECISION * x[0] / d * x[1] / d
+        } else {
+            x.iter().fold(PRECISION, |k0, x| k0 * *x * n_coins / d)
+        };
+        let g1k0 = g1k0(gamma, k0);
+        let mul1 = mul1(d, gamma, g1k0, ann);
+        // 2 * N * K0 / g1k0
+        let mul2 = U256::from(2) * PRECISION * n_coins * k0 / g1k0;
+
+        let neg_fprime = (s + s * mul2 / PRECISION) + mul1 * n_coins / k0 - mul2 * d / PRECISION;
+
+        let d_plus = d * (neg_fprime + s) / neg_fprime;
+        let mut d_minus = d * d / neg_fprime;
+        if PRECISION > k0 {
+            d_minus += d * (mul1 / neg_fprime) / PRECISION * (PRECISION - k0) / k0;
+        } else {
+            d_minus = d_minus
+                .checked_sub(d * (mul1 / neg_fprime) / PRECISION * (k0 - PRECISION) / k0)
+                .ok_or(eyre!("D_UNDERFLOW"))?;
+        }
+        d = if d_plus > d_minus { d_plus - d_minus } else { (d_minus - d_plus) / U256::from(2) };
+
+        let diff = d.abs_diff(d_prev);
+        if diff * U256::from(100_000_000_000_000u64) < d.max(U256::from(10_000_000_000_000_000u64)) {
+            for x_i in x.iter() {
+                check_frac(*x_i, d)?;
+            }
+            return Ok(d);
+        }
+    }
+    Err(eyre!("NEWTON_D_DID_NOT_CONVERGE"))
+}
+
+/// Balance of coin `i` keeping the invariant at `d` given the others, as Math.newton_y.
+pub fn newton_y(ann: U256, gamma: U256, x: &[U256], d: U256, i: usize) -> eyre::Result<U256> {
+    let n = x.len();
+    check_a_gamma(ann, gamma, n)?;
+    if d < U256::from(100_000_000_000_000_000u64) || d > U256::from(10).pow(U256::from(33)) {
+        return Err(eyre!("UNSAFE_D"));
+    }
+
+    let n_coins = U256::from(n);
+    let (mut y, k0_i, s_i, convergence_limit) = if n == 2 {
+        let x_j = x[1 - i];
+        let k0_i = U256::from(2) * PRECISION * x_j / d;
+        if k0_i < U256::from(20_000_000_000_000_000u64) || k0_i > U256::from(200_000_000_000_000_000_000u128) {
+            return Err(eyre!("UNSAFE_X"));
+        }
+        let convergence_limit = (x_j / U256::from(100_000_000_000_000u64))
+            .max(d / U256::from(100_000_000_000_000u64))
+            .max(U256::from(100));
+        (d * d / (x_j * U256::from(4)), k0_i, x_j, convergence_limit)
+    } else {
+        for (k, x_k) in x.iter().enumerate() {
+            if k != i {
+                check_frac(*x_k, d)?;
+            }
+        }
+        let mut x_sorted = x.to_vec();
+        x_sorted[i] = U256::ZERO;
+        let x_sorted = sorted(&x_sorted);
+
+        let convergence_limit = (x_sorted[0] / U256::from(100_000_000_000_000u64))
+            .max(d / U256::from(100_000_000_000_000u64))
+            .max(U256::from(100));
+        let mut y = d / n_coins;
+        let mut s_i = U256::ZERO;
+        // Small x first
+        for x_k in x_sorted[..n - 1].iter().rev() {
+            y = y * d / (*x_k * n_coins);
+            s_i += *x_k;
+        }
+        // Large x first
+        let k0_i = x_sorted[..n - 1].iter().fold(PRECISION, |k0_i, x_k| k0_i * *x_k * n_coins / d);
+        (y, k0_i, s_i, convergence_limit)
+    };
+
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+
+        let k0 = k0_i * y * n_coins / d;
+        let s = s_i + y;
+        let g1k0 = g1k0(gamma, k0);
+        let mul1 = mul1(d, gamma, g1k0, ann);
+        // 1 + 2 * K0 / g1k0
+        let mul2 = PRECISION + U256::from(2) * PRECISION * k0 / g1k0;
+
+        let mut yfprime = PRECISION * y + s * mul2 + mul1;
+        let dyfprime = d * mul2;
+        if yfprime < dyfprime {
+            y = y_prev / U256::from(2);
+            continue;
+        }
+        yfprime -= dyfprime;
+        let fprime = yfprime / y;
+
+        let mut y_minus = mul1 / fprime;
+        let y_plus = (yfprime + PRECISION * d) / fprime + y_minus * PRECISION / k0;
+        y_minus += PRECISION * s / fprime;
+        y = if y_plus < y_minus { y_prev / U256::from(2) } else { y_plus - y_minus };
+
+        if y.abs_diff(y_prev) < convergence_limit.max(y / U256::from(100_000_000_000_000u64)) {
+            check_frac(y, d)?;
+            return Ok(y);
+        }
+    }
+    Err(eyre!("NEWTON_Y_DID_NOT_CONVERGE"))
+}
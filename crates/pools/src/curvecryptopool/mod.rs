@@ -0,0 +1,269 @@
+mod abi;
+pub mod math;
+#[cfg(test)]
+mod tests;
+
+use alloy::eips::BlockId;
+use alloy::network::{BlockResponse, HeaderResponse, Network};
+use alloy::primitives::{Address, U256};
+use alloy::providers::Provider;
+use alloy::rpc::types::BlockTransactionsKind;
+use alloy::transports::Transport;
+use eyre::eyre;
+use tracing::instrument;
+use types::pool::{Pool, PoolClass, PoolProtocol};
+use crate::curvecryptopool::abi::{ICurveCryptoPool, ICurveTriCryptoPool, ICurveTwoCryptoPool, IERC20};
+use crate::curvecryptopool::math::AGammaRamp;
+use crate::curvepool::math::{FEE_DENOMINATOR, PRECISION};
+use crate::curvepool::ETH_ADDRESS;
+use crate::rpc::is_revert;
+
+/// CryptoSwap pools hold two or three coins.
+const MAX_COINS: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct CurveCryptoPoolMetadata {
+    pub pool: Address,
+    pub protocol: PoolProtocol,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CurveCryptoPoolData {
+    pub coins: Vec<Address>,
+    /// 10^(18 - decimals) of each coin, normalizing balances to 18 decimals.
+    pub precisions: Vec<U256>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CurveCryptoPoolState {
+    pub balances: Vec<U256>,
+    /// Price of every coin but the first in the first one, over 1e18.
+    pub price_scale: Vec<U256>,
+    /// Invariant stored by the last exchange, recomputed by get_dy once A and gamma have ramped.
+    pub d: U256,
+    pub a_gamma: AGammaRamp,
+    /// Fees over 1e10 at and far from balance, fee_gamma over 1e18 sets how fast they move between the two.
+    pub mid_fee: U256,
+    pub out_fee: U256,
+    pub fee_gamma: U256,
+    /// Timestamp of the synced block, where A and gamma are evaluated.
+    pub timestamp: u64,
+}
+
+/// Curve CryptoSwap V2 pool of two or three volatile coins, concentrated around an internal price scale.
+///
+/// Tricrypto2 and the two coin pools like crvETH, the -ng pools solve for y differently and are not reproduced.
+#[derive(Debug, Clone)]
+pub struct CurveCryptoPool {
+    pub metadata: CurveCryptoPoolMetadata,
+    pub data: CurveCryptoPoolData,
+    pub state: CurveCryptoPoolState,
+}
+
+impl Pool for CurveCryptoPool {
+    fn get_class(&self) -> PoolClass {
+        PoolClass::CurveCrypto
+    }
+
+    fn get_protocol(&self) -> PoolProtocol {
+        self.metadata.protocol
+    }
+
+    fn get_address(&self) -> Address {
+        self.metadata.pool
+    }
+
+    /// Fee at balance in hundredths of a bip, growing towards out_fee as the pool moves away from its price scale.
+    fn get_fee(&self) -> u32 {
+        (self.state.mid_fee / U256::from(10_000)).saturating_to::<u32>()
+    }
+
+    fn get_tokens(&self) -> Vec<Address> {
+        self.data.coins.clone()
+    }
+
+    fn calc_amount_out(&self, amount_in: U256, tok_in: Address, tok_out: Address) -> eyre::Result<U256> {
+        let coin_index = |token: Address| self.data.coins.iter().position(|coin| *coin == token);
+        match (coin_index(tok_in), coin_index(tok_out)) {
+            (Some(i), Some(j)) => self.get_dy(i, j, amount_in),
+            _ => Err(eyre!("TOKEN_NOT_IN_POOL")),
+        }
+    }
+}
+
+impl CurveCryptoPool {
+    pub fn new(pool: Address) -> Self {
+        CurveCryptoPool {
+            metadata: CurveCryptoPoolMetadata {
+                pool,
+                protocol: PoolProtocol::CurveCrypto,
+            },
+            data: Default::default(),
+            state: Default::default(),
+        }
+    }
+
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn sync<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        &mut self,
+        provider: P,
+        block: BlockId
+    ) -> eyre::Result<()> {
+        self.data = CurveCryptoPool::fetch_data(
+            &self.metadata,
+            provider.clone(),
+            block,
+        ).await?;
+
+        self.state = CurveCryptoPool::fetch_state(
+            &self.metadata,
+            &self.data,
+            provider.clone(),
+            block,
+        ).await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn fetch_data<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &CurveCryptoPoolMetadata,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<CurveCryptoPoolData> {
+        let pool = ICurveCryptoPool::new(metadata.pool, provider.clone());
+
+        // coins() reverts past the last coin
+        let mut coins = Vec::new();
+        for i in 0..MAX_COINS {
+            match pool.coins(U256::from(i)).block(block).call().await {
+                Ok(ret) => coins.push(ret._0),
+                Err(e) if is_revert(&e) => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        if coins.len() < 2 {
+            return Err(eyre!("NOT_A_CURVE_CRYPTO_POOL {}", metadata.pool));
+        }
+
+        let mut precisions = Vec::with_capacity(coins.len());
+        for coin in coins.iter() {
+            let decimals = if *coin == ETH_ADDRESS {
+                18
+            } else {
+                IERC20::new(*coin, provider.clone()).decimals().block(block).call().await?._0
+            };
+            let exponent = 18u8.checked_sub(decimals).ok_or(eyre!("UNSUPPORTED_DECIMALS {decimals}"))?;
+            precisions.push(U256::from(10).pow(U256::from(exponent)));
+        }
+
+        Ok(CurveCryptoPoolData {
+            coins,
+            precisions,
+        })
+    }
+
+    #[instrument(skip(provider), level = "debug", ret)]
+    pub async fn fetch_state<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &CurveCryptoPoolMetadata,
+        data: &CurveCryptoPoolData,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<CurveCryptoPoolState> {
+        let pool = ICurveCryptoPool::new(metadata.pool, provider.clone());
+
+        let mut balances = Vec::with_capacity(data.coins.len());
+        for i in 0..data.coins.len() {
+            balances.push(pool.balances(U256::from(i)).block(block).call().await?._0);
+        }
+
+        // Two coin pools have a single price_scale() without an index
+        let price_scale = if data.coins.len() == 2 {
+            vec![ICurveTwoCryptoPool::new(metadata.pool, provider.clone()).price_scale().block(block).call().await?._0]
+        } else {
+            let pool = ICurveTriCryptoPool::new(metadata.pool, provider.clone());
+            let mut price_scale = Vec::with_capacity(data.coins.len() - 1);
+            for k in 0..data.coins.len() - 1 {
+                price_scale.push(pool.price_scale(U256::from(k)).block(block).call().await?._0);
+            }
+            price_scale
+        };
+
+        let (initial_a, initial_gamma) = AGammaRamp::unpack(pool.initial_A_gamma().block(block).call().await?._0);
+        let (future_a, future_gamma) = AGammaRamp::unpack(pool.future_A_gamma().block(block).call().await?._0);
+        let a_gamma = AGammaRamp {
+            initial_a,
+            initial_gamma,
+            future_a,
+            future_gamma,
+            initial_time: pool.initial_A_gamma_time().block(block).call().await?._0.try_into()?,
+            future_time: pool.future_A_gamma_time().block(block).call().await?._0.try_into()?,
+        };
+        let timestamp = provider
+            .get_block(block, BlockTransactionsKind::Hashes)
+            .await?
+            .ok_or(eyre!("BLOCK_NOT_FOUND"))?
+            .header()
+            .timestamp();
+
+        Ok(CurveCryptoPoolState {
+            balances,
+            price_scale,
+            d: pool.D().block(block).call().await?._0,
+            a_gamma,
+            mid_fee: pool.mid_fee().block(block).call().await?._0,
+            out_fee: pool.out_fee().block(block).call().await?._0,
+            fee_gamma: pool.fee_gamma().block(block).call().await?._0,
+            timestamp,
+        })
+    }
+
+    /// Balances in 18 decimals and valued in the first coin through the price scale.
+    pub fn xp(&self, balances: &[U256]) -> Vec<U256> {
+        balances
+            .iter()
+            .zip(self.data.precisions.iter())
+            .enumerate()
+            .map(|(k, (balance, precision))| match k {
+                0 => *balance * *precision,
+                _ => *balance * self.state.price_scale[k - 1] * *precision / PRECISION,
+            })
+            .collect()
+    }
+
+    /// Dynamic fee over 1e10 of the pool at `xp`, mid_fee when balanced and out_fee far from it.
+    pub fn fee(&self, xp: &[U256]) -> U256 {
+        let f = math::reduction_coefficient(xp, self.state.fee_gamma);
+        (self.state.mid_fee * f + self.state.out_fee * (PRECISION - f)) / PRECISION
+    }
+
+    /// CurveCryptoSwap.get_dy, amount of coin `j` out for `dx` of coin `i`.
+    pub fn get_dy(&self, i: usize, j: usize, dx: U256) -> eyre::Result<U256> {
+        let n = self.state.balances.len();
+        if i == j || i >= n || j >= n {
+            return Err(eyre!("INVALID_COIN_INDEX"));
+        }
+        let (a, gamma) = self.state.a_gamma.at(self.state.timestamp);
+
+        // The stored D is stale while A and gamma ramp, the pool recomputes it for good once a ramp was ever started
+        let d = if self.state.a_gamma.future_time > 0 {
+            math::newton_d(a, gamma, &self.xp(&self.state.balances))?
+        } else {
+            self.state.d
+        };
+
+        let mut balances = self.state.balances.clone();
+        balances[i] += dx;
+        let mut xp = self.xp(&balances);
+
+        let y = math::newton_y(a, gamma, &xp, d, j)?;
+        let mut dy = xp[j].checked_sub(y + U256::from(1)).ok_or(eyre!("INSUFFICIENT_BALANCE"))?;
+        xp[j] = y;
+        if j > 0 {
+            dy = dy * PRECISION / self.state.price_scale[j - 1];
+        }
+        dy /= self.data.precisions[j];
+
+        Ok(dy - self.fee(&xp) * dy / FEE_DENOMINATOR)
+    }
+}
@@ -0,0 +1,144 @@
+use alloy::primitives::{Address, U256};
+use tracing::info;
+use config::Config;
+use types::pool::Pool;
+use crate::curvecryptopool::abi::ICurveCryptoPool;
+use crate::curvecryptopool::math::{self, AGammaRamp};
+use crate::curvecryptopool::CurveCryptoPool;
+use crate::test_utils::{test_networks, units, TestNetwork};
+
+/// Parity with get_dy for every coin pair of every configured CryptoSwap pool.
+#[tokio::test]
+async fn test_get_dy() {
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .try_init();
+
+    let cfg = Config::default();
+    for TestNetwork { net, addresses, provider, block } in test_networks(&cfg, |addresses| !addresses.curve.crypto_pools.is_empty()).await {
+        for (name, address) in addresses.curve.crypto_pools.iter() {
+            let mut pool = CurveCryptoPool::new(*address);
+            pool.sync(provider.clone(), block.into()).await.unwrap();
+            let contract = ICurveCryptoPool::new(*address, provider.clone());
+
+            for i in 0..pool.data.coins.len() {
+                for j in 0..pool.data.coins.len() {
+                    if i == j {
+                        continue;
+                    }
+                    let dx = pool.state.balances[i] / U256::from(1000);
+                    let dy_local = pool.calc_amount_out(dx, pool.data.coins[i], pool.data.coins[j]).unwrap();
+                    let dy_pool = contract.get_dy(U256::from(i), U256::from(j), dx).block(block.into()).call().await.unwrap()._0;
+                    info!(?net, %name, i, j, ?dy_local, ?dy_pool);
+
+                    assert_eq!(dy_local, dy_pool);
+                }
+            }
+        }
+    }
+}
+
+/// USDT/WBTC/WETH at 30000 and 2000 with tricrypto2 parameters, balanced.
+fn tricrypto() -> CurveCryptoPool {
+    let mut pool = CurveCryptoPool::new(Address::repeat_byte(3));
+    pool.data.coins = vec![Address::repeat_byte(0), Address::repeat_byte(1), Address::repeat_byte(2)];
+    pool.data.precisions = vec![units(1, 12), units(1, 10), U256::from(1)];
+    pool.state.balances = vec![units(30_000_000, 6), units(1000, 8), units(15_000, 18)];
+    pool.state.price_scale = vec![units(30_000, 18), units(2000, 18)];
+    pool.state.d = units(90_000_000, 18);
+    pool.state.a_gamma.future_a = U256::from(1_707_629);
+    pool.state.a_gamma.future_gamma = U256::from(11_809_167_828_997u64);
+    pool.state.mid_fee = U256::from(3_000_000);
+    pool.state.out_fee = U256::from(30_000_000);
+    pool.state.fee_gamma = U256::from(500_000_000_000_000u64);
+    pool
+}
+
+/// WETH/CRV at 0.0005 with crvETH parameters, balanced.
+fn two_crypto() -> CurveCryptoPool {
+    let mut pool = CurveCryptoPool::new(Address::repeat_byte(3));
+    pool.data.coins = vec![Address::repeat_byte(0), Address::repeat_byte(1)];
+    pool.data.precisions = vec![U256::from(1), U256::from(1)];
+    pool.state.balances = vec![units(5000, 18), units(10_000_000, 18)];
+    pool.state.price_scale = vec![units(5, 14)];
+    pool.state.d = units(10_000, 18);
+    pool.state.a_gamma.future_a = U256::from(400_000);
+    pool.state.a_gamma.future_gamma = U256::from(145_000_000_000_000u64);
+    pool.state.mid_fee = U256::from(26_000_000);
+    pool.state.out_fee = U256::from(45_000_000);
+    pool.state.fee_gamma = U256::from(230_000_000_000_000u64);
+    pool
+}
+
+#[test]
+fn test_a_gamma_ramp() {
+    let packed = (U256::from(1_707_629) << 128) | U256::from(11_809_167_828_997u64);
+    assert_eq!(AGammaRamp::unpack(packed), (U256::from(1_707_629), U256::from(11_809_167_828_997u64)));
+
+    let ramp = AGammaRamp {
+        initial_a: U256::from(1_000_000),
+        initial_gamma: U256::from(20_000_000_000_000u64),
+        future_a: U256::from(2_000_000),
+        future_gamma: U256::from(10_000_000_000_000u64),
+        initial_time: 1000,
+        future_time: 2000,
+    };
+    assert_eq!(ramp.at(1000), (U256::from(1_000_000), U256::from(20_000_000_000_000u64)));
+    assert_eq!(ramp.at(1250), (U256::from(1_250_000), U256::from(17_500_000_000_000u64)));
+    assert_eq!(ramp.at(3000), (U256::from(2_000_000), U256::from(10_000_000_000_000u64)));
+}
+
+/// Reference values from a line by line port of CurveCryptoMath3 and CurveCryptoSwap2ETH.
+#[test]
+fn test_math() {
+    let pool = tricrypto();
+    let xp = pool.xp(&pool.state.balances);
+    assert_eq!(math::geometric_mean(&xp, true).unwrap(), units(30_000_000, 18));
+    assert_eq!(math::newton_d(pool.state.a_gamma.future_a, pool.state.a_gamma.future_gamma, &xp).unwrap(), units(90_000_000, 18));
+    assert_eq!(math::reduction_coefficient(&xp, pool.state.fee_gamma), units(1, 18));
+
+    let pool = two_crypto();
+    let xp = pool.xp(&pool.state.balances);
+    assert_eq!(math::geometric_mean(&xp, true).unwrap(), units(5000, 18));
+    assert_eq!(math::newton_d(pool.state.a_gamma.future_a, pool.state.a_gamma.future_gamma, &xp).unwrap(), units(10_000, 18));
+}
+
+#[test]
+fn test_tricrypto() {
+    let mut pool = tricrypto();
+    assert_eq!(pool.get_dy(0, 1, units(100_000, 6)).unwrap(), U256::from(333_148_496u64));
+    assert_eq!(pool.get_dy(1, 2, units(1, 8)).unwrap(), U256::from(14_995_116_435_628_962_527u64));
+    assert_eq!(pool.calc_amount_out(units(10, 18), pool.data.coins[2], pool.data.coins[0]).unwrap(), U256::from(19_993_704_924u64));
+
+    // Away from balance after a ramp, D is recomputed rather than read
+    pool.state.balances = vec![units(31_000_000, 6), units(990, 8), units(14_800, 18)];
+    pool.state.a_gamma.future_time = 1;
+    pool.state.timestamp = 2;
+    assert_eq!(pool.get_dy(0, 1, units(100_000, 6)).unwrap(), U256::from(318_984_913u64));
+    assert_eq!(pool.get_dy(2, 0, units(10, 18)).unwrap(), U256::from(20_802_862_017u64));
+
+    assert!(pool.get_dy(0, 0, units(1, 6)).is_err());
+    assert!(pool.get_dy(0, 3, units(1, 6)).is_err());
+}
+
+#[test]
+fn test_two_crypto() {
+    let pool = two_crypto();
+    assert_eq!(pool.get_dy(0, 1, units(10, 18)).unwrap(), U256::from(19_945_355_116_874_347_537_791u128));
+    assert_eq!(pool.get_dy(1, 0, units(100_000, 18)).unwrap(), U256::from(49_784_121_454_421_317_354u128));
+    assert_eq!(pool.get_fee(), 2600);
+}
+
+#[test]
+fn test_check_a_gamma() {
+    let gamma = U256::from(30_000_000_000_000_000u64);
+    // 0.03 is past the two coin bound but within the tricrypto one
+    assert!(math::check_a_gamma(U256::from(400_000), gamma, 2).is_err());
+    assert!(math::check_a_gamma(U256::from(1_707_629), gamma, 3).is_ok());
+
+    // Two coin pools go down to A = N^N * A_MULTIPLIER / 10, tricrypto stops at / 100
+    assert!(math::check_a_gamma(U256::from(4_000), U256::from(145_000_000_000_000u64), 2).is_ok());
+    assert!(math::check_a_gamma(U256::from(3_999), U256::from(145_000_000_000_000u64), 2).is_err());
+    assert!(math::check_a_gamma(U256::from(2_700), gamma, 3).is_ok());
+    assert!(math::check_a_gamma(U256::from(2_699), gamma, 3).is_err());
+}
@@ -0,0 +1,116 @@
+use alloy::primitives::U256;
+use eyre::eyre;
+
+pub const PRECISION: U256 = U256::from_limbs([1_000_000_000_000_000_000, 0, 0, 0]);
+/// Fees and admin fees are fractions of 1e10.
+pub const FEE_DENOMINATOR: U256 = U256::from_limbs([10_000_000_000, 0, 0, 0]);
+/// Newton iterations of get_D and get_y before they give up.
+const MAX_ITERATIONS: usize = 255;
+
+/// Amplification coefficient ramping linearly from `initial_a` to `future_a`, as StableSwap._A.
+///
+/// Values are scaled by A_PRECISION on the pools that have it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AmpRamp {
+    pub initial_a: U256,
+    pub future_a: U256,
+    pub initial_a_time: u64,
+    pub future_a_time: u64,
+}
+
+impl AmpRamp {
+    pub fn at(&self, timestamp: u64) -> U256 {
+        if timestamp >= self.future_a_time || self.future_a_time <= self.initial_a_time {
+            return self.future_a;
+        }
+        let elapsed = U256::from(timestamp.saturating_sub(self.initial_a_time));
+        let duration = U256::from(self.future_a_time - self.initial_a_time);
+        if self.future_a > self.initial_a {
+            self.initial_a + (self.future_a - self.initial_a) * elapsed / duration
+        } else {
+            self.initial_a - (self.initial_a - self.future_a) * elapsed / duration
+        }
+    }
+}
+
+/// StableSwap invariant D of the normalized balances `xp`.
+pub fn get_d(xp: &[U256], amp: U256, a_precision: U256) -> eyre::Result<U256> {
+    let n = U256::from(xp.len());
+    let s = xp.iter().fold(U256::ZERO, |s, x| s + *x);
+    if s.is_zero() {
+        return Ok(U256::ZERO);
+    }
+    if xp.iter().any(|x| x.is_zero()) {
+        return Err(eyre!("ZERO_BALANCE"));
+    }
+
+    let ann = amp * n;
+    let mut d = s;
+    for _ in 0..MAX_ITERATIONS {
+        let mut d_p = d;
+        for x in xp {
+            d_p = d_p * d / (*x * n);
+        }
+        let d_prev = d;
+        let numerator = (ann * s / a_precision + d_p * n) * d;
+        let denominator = (ann - a_precision) * d / a_precision + (n + U256::from(1)) * d_p;
+        d = numerator / denominator;
+        if d.abs_diff(d_prev) <= U256::from(1) {
+            return Ok(d);
+        }
+    }
+    Err(eyre!("D_NOT_CONVERGED"))
+}
+
+/// Balance of coin `j` keeping D once coin `i` is at `x`, as StableSwap.get_y.
+pub fn get_y(i: usize, j: usize, x: U256, xp: &[U256], amp: U256, a_precision: U256) -> eyre::Result<U256> {
+    if i == j || i >= xp.len() || j >= xp.len() {
+        return Err(eyre!("INVALID_COIN_INDEX"));
+    }
+    let d = get_d(xp, amp, a_precision)?;
+    let others: Vec<U256> = xp.iter().enumerate()
+        .filter(|(k, _)| *k != j)
+        .map(|(k, balance)| if k == i { x } else { *balance })
+        .collect();
+    solve_y(&others, xp.len(), amp, a_precision, d)
+}
+
+/// Balance of coin `i` for the invariant `d` with the other balances unchanged, as StableSwap.get_y_D.
+pub fn get_y_d(amp: U256, a_precision: U256, i: usize, xp: &[U256], d: U256) -> eyre::Result<U256> {
+    if i >= xp.len() {
+        return Err(eyre!("INVALID_COIN_INDEX"));
+    }
+    let others: Vec<U256> = xp.iter().enumerate()
+        .filter(|(k, _)| *k != i)
+        .map(|(_, balance)| *balance)
+        .collect();
+    solve_y(&others, xp.len(), amp, a_precision, d)
+}
+
+/// Newton's method on `y^2 + (b - D) * y = c` given the `n - 1` other balances.
+fn solve_y(others: &[U256], n: usize, amp: U256, a_precision: U256, d: U256) -> eyre::Result<U256> {
+    let n = U256::from(n);
+    let ann = amp * n;
+    let mut c = d;
+    let mut s = U256::ZERO;
+    for x in others {
+        if x.is_zero() {
+            return Err(eyre!("ZERO_BALANCE"));
+        }
+        s += *x;
+        c = c * d / (*x * n);
+    }
+    c = c * d * a_precision / (ann * n);
+    let b = s + d * a_precision / ann;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        let denominator = (U256::from(2) * y + b).checked_sub(d).ok_or(eyre!("Y_UNDERFLOW"))?;
+        y = (y * y + c) / denominator;
+        if y.abs_diff(y_prev) <= U256::from(1) {
+            return Ok(y);
+        }
+    }
+    Err(eyre!("Y_NOT_CONVERGED"))
+}
@@ -0,0 +1,455 @@
+mod abi;
+pub mod math;
+#[cfg(test)]
+mod tests;
+
+use alloy::eips::BlockId;
+use alloy::network::{BlockResponse, HeaderResponse, Network};
+use alloy::primitives::{address, Address, U256};
+use alloy::providers::Provider;
+use alloy::rpc::types::BlockTransactionsKind;
+use alloy::transports::Transport;
+use eyre::eyre;
+use tracing::instrument;
+use types::pool::{Pool, PoolClass, PoolProtocol};
+use crate::curvepool::abi::{ICurvePool, IERC20};
+use crate::curvepool::math::{AmpRamp, FEE_DENOMINATOR, PRECISION};
+use crate::rpc::is_revert;
+
+/// Placeholder Curve uses for the native token.
+pub const ETH_ADDRESS: Address = address!("EeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE");
+/// StableSwap pools hold at most 8 coins.
+const MAX_COINS: usize = 8;
+/// Metapools reuse the base pool virtual price they cached for this long.
+const BASE_CACHE_EXPIRES: u64 = 10 * 60;
+
+#[derive(Debug, Clone)]
+pub struct CurvePoolMetadata {
+    pub pool: Address,
+    pub protocol: PoolProtocol,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CurvePoolData {
+    pub coins: Vec<Address>,
+    /// 10^(18 - decimals) of each coin, normalizing balances to 18 decimals.
+    pub precision_multipliers: Vec<U256>,
+    /// A_PRECISION, 1 on the early pools without A_precise(), which also round get_dy before the fee.
+    pub a_precision: U256,
+    /// Swap fee and admin share of it, both over 1e10.
+    pub fee: U256,
+    pub admin_fee: U256,
+    pub lp_token: Address,
+    /// Base pool of a metapool, whose LP token is the last coin.
+    pub base_pool: Option<Address>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CurvePoolState {
+    pub balances: Vec<U256>,
+    pub amp: AmpRamp,
+    /// Timestamp of the synced block, where the amplification ramp is evaluated.
+    pub timestamp: u64,
+    pub total_supply: U256,
+    /// Base pool virtual price cached by a metapool and when it was cached.
+    pub base_cache: Option<(U256, u64)>,
+}
+
+/// Curve StableSwap pool with N coins, plain or metapool over a base pool.
+#[derive(Debug, Clone)]
+pub struct CurvePool {
+    pub metadata: CurvePoolMetadata,
+    pub data: CurvePoolData,
+    pub state: CurvePoolState,
+    /// Synced base pool of a metapool, needed for exchange_underlying.
+    pub base: Option<Box<CurvePool>>,
+}
+
+impl Pool for CurvePool {
+    fn get_class(&self) -> PoolClass {
+        PoolClass::Curve
+    }
+
+    fn get_protocol(&self) -> PoolProtocol {
+        self.metadata.protocol
+    }
+
+    fn get_address(&self) -> Address {
+        self.metadata.pool
+    }
+
+    /// Fee in hundredths of a bip like the other pools, the pool stores it over 1e10.
+    fn get_fee(&self) -> u32 {
+        (self.data.fee / U256::from(10_000)).saturating_to::<u32>()
+    }
+
+    /// Coins, then the underlying coins of the base pool for metapools.
+    fn get_tokens(&self) -> Vec<Address> {
+        let mut tokens = self.data.coins.clone();
+        if let Some(base) = &self.base {
+            tokens.extend(base.data.coins.iter().copied());
+        }
+        tokens
+    }
+
+    /// Prices `exchange` between two coins, `exchange_underlying` when a base pool coin is involved.
+    fn calc_amount_out(&self, amount_in: U256, tok_in: Address, tok_out: Address) -> eyre::Result<U256> {
+        let coin_index = |token: Address| self.data.coins.iter().position(|coin| *coin == token);
+        if let (Some(i), Some(j)) = (coin_index(tok_in), coin_index(tok_out)) {
+            return self.get_dy(i, j, amount_in);
+        }
+
+        let underlying = self.underlying_coins();
+        let underlying_index = |token: Address| underlying.iter().position(|coin| *coin == token);
+        match (underlying_index(tok_in), underlying_index(tok_out)) {
+            (Some(i), Some(j)) => self.get_dy_underlying(i, j, amount_in),
+            _ => Err(eyre!("TOKEN_NOT_IN_POOL")),
+        }
+    }
+}
+
+impl CurvePool {
+    pub fn new(pool: Address) -> Self {
+        CurvePool {
+            metadata: CurvePoolMetadata {
+                pool,
+                protocol: PoolProtocol::Curve,
+            },
+            data: Default::default(),
+            state: Default::default(),
+            base: None,
+        }
+    }
+
+    /// Syncs the pool and, for a metapool, its base pool.
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn sync<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        &mut self,
+        provider: P,
+        block: BlockId
+    ) -> eyre::Result<()> {
+        self.data = CurvePool::fetch_data(
+            &self.metadata,
+            provider.clone(),
+            block,
+        ).await?;
+
+        self.state = CurvePool::fetch_state(
+            &self.metadata,
+            &self.data,
+            provider.clone(),
+            block,
+        ).await?;
+
+        self.base = match self.data.base_pool {
+            Some(base_pool) => {
+                let mut base = CurvePool::new(base_pool);
+                base.data = CurvePool::fetch_data(&base.metadata, provider.clone(), block).await?;
+                base.state = CurvePool::fetch_state(&base.metadata, &base.data, provider.clone(), block).await?;
+                Some(Box::new(base))
+            }
+            None => None,
+        };
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn fetch_data<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &CurvePoolMetadata,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<CurvePoolData> {
+        let pool = ICurvePool::new(metadata.pool, provider.clone());
+
+        // coins() reverts past the last coin
+        let mut coins = Vec::new();
+        for i in 0..MAX_COINS {
+            match pool.coins(U256::from(i)).block(block).call().await {
+                Ok(ret) => coins.push(ret._0),
+                Err(e) if is_revert(&e) => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        if coins.len() < 2 {
+            return Err(eyre!("NOT_A_CURVE_POOL {}", metadata.pool));
+        }
+
+        let mut precision_multipliers = Vec::with_capacity(coins.len());
+        for coin in coins.iter() {
+            let decimals = if *coin == ETH_ADDRESS {
+                18
+            } else {
+                IERC20::new(*coin, provider.clone()).decimals().block(block).call().await?._0
+            };
+            let exponent = 18u8.checked_sub(decimals).ok_or(eyre!("UNSUPPORTED_DECIMALS {decimals}"))?;
+            precision_multipliers.push(U256::from(10).pow(U256::from(exponent)));
+        }
+
+        let a_precision = match pool.A_precise().block(block).call().await {
+            Ok(_) => U256::from(100),
+            Err(e) if is_revert(&e) => U256::from(1),
+            Err(e) => return Err(e.into()),
+        };
+        let fee: U256 = pool.fee().block(block).call().await?._0;
+        let admin_fee: U256 = pool.admin_fee().block(block).call().await?._0;
+
+        // Early pools have a separate LP token, newer ones are their own token
+        let lp_token = match pool.token().block(block).call().await {
+            Ok(ret) => ret._0,
+            Err(e) if is_revert(&e) => match pool.lp_token().block(block).call().await {
+                Ok(ret) => ret._0,
+                Err(e) if is_revert(&e) => metadata.pool,
+                Err(e) => return Err(e.into()),
+            },
+            Err(e) => return Err(e.into()),
+        };
+        let base_pool = match pool.base_pool().block(block).call().await {
+            Ok(ret) if ret._0 != Address::ZERO => Some(ret._0),
+            Ok(_) => None,
+            Err(e) if is_revert(&e) => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(CurvePoolData {
+            coins,
+            precision_multipliers,
+            a_precision,
+            fee,
+            admin_fee,
+            lp_token,
+            base_pool,
+        })
+    }
+
+    #[instrument(skip(provider), level = "debug", ret)]
+    pub async fn fetch_state<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &CurvePoolMetadata,
+        data: &CurvePoolData,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<CurvePoolState> {
+        let pool = ICurvePool::new(metadata.pool, provider.clone());
+
+        let mut balances = Vec::with_capacity(data.coins.len());
+        for i in 0..data.coins.len() {
+            balances.push(pool.balances(U256::from(i)).block(block).call().await?._0);
+        }
+
+        let amp = AmpRamp {
+            initial_a: pool.initial_A().block(block).call().await?._0,
+            future_a: pool.future_A().block(block).call().await?._0,
+            initial_a_time: pool.initial_A_time().block(block).call().await?._0.try_into()?,
+            future_a_time: pool.future_A_time().block(block).call().await?._0.try_into()?,
+        };
+        let timestamp = provider
+            .get_block(block, BlockTransactionsKind::Hashes)
+            .await?
+            .ok_or(eyre!("BLOCK_NOT_FOUND"))?
+            .header()
+            .timestamp();
+        let total_supply: U256 = IERC20::new(data.lp_token, provider.clone()).totalSupply().block(block).call().await?._0;
+
+        let base_cache = match data.base_pool {
+            Some(_) => {
+                // Only some metapools cache the base virtual price
+                match pool.base_virtual_price().block(block).call().await {
+                    Ok(price) => {
+                        let updated = pool.base_cache_updated().block(block).call().await?._0;
+                        Some((price._0, updated.try_into()?))
+                    }
+                    Err(e) if is_revert(&e) => None,
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            None => None,
+        };
+
+        Ok(CurvePoolState {
+            balances,
+            amp,
+            timestamp,
+            total_supply,
+            base_cache,
+        })
+    }
+
+    /// Coins of a metapool with its LP coin replaced by the base pool coins, as indexed by exchange_underlying.
+    pub fn underlying_coins(&self) -> Vec<Address> {
+        match &self.base {
+            Some(base) => {
+                let mut coins = self.data.coins[..self.data.coins.len() - 1].to_vec();
+                coins.extend(base.data.coins.iter().copied());
+                coins
+            }
+            None => Vec::new(),
+        }
+    }
+
+    pub fn amp(&self) -> U256 {
+        self.state.amp.at(self.state.timestamp)
+    }
+
+    /// Rate of each coin over 1e18 scaling balances to 18 decimals, the base pool virtual price for the LP coin of metapools.
+    pub fn rates(&self) -> eyre::Result<Vec<U256>> {
+        let mut rates: Vec<U256> = self.data.precision_multipliers.iter().map(|multiplier| PRECISION * *multiplier).collect();
+        if let Some(base) = &self.base {
+            let vp_rate = match self.state.base_cache {
+                Some((price, updated)) if self.state.timestamp <= updated + BASE_CACHE_EXPIRES => price,
+                _ => base.virtual_price()?,
+            };
+            *rates.last_mut().ok_or(eyre!("NO_COINS"))? = vp_rate;
+        }
+        Ok(rates)
+    }
+
+    fn xp(&self, balances: &[U256], rates: &[U256]) -> Vec<U256> {
+        balances.iter().zip(rates).map(|(balance, rate)| *balance * *rate / PRECISION).collect()
+    }
+
+    /// Value of one LP token, D over the LP supply.
+    pub fn virtual_price(&self) -> eyre::Result<U256> {
+        if self.state.total_supply.is_zero() {
+            return Err(eyre!("ZERO_TOTAL_SUPPLY"));
+        }
+        let xp = self.xp(&self.state.balances, &self.rates()?);
+        let d = math::get_d(&xp, self.amp(), self.data.a_precision)?;
+        Ok(d * PRECISION / self.state.total_supply)
+    }
+
+    /// Output of swapping `dx` of coin `i` for coin `j`, normalized to 18 decimals, before and as the fee.
+    fn swap_xp(&self, i: usize, j: usize, dx: U256, xp: &[U256], rates: &[U256]) -> eyre::Result<(U256, U256)> {
+        if i >= xp.len() || j >= xp.len() {
+            return Err(eyre!("INVALID_COIN_INDEX"));
+        }
+        let x = xp[i] + dx * rates[i] / PRECISION;
+        let y = math::get_y(i, j, x, xp, self.amp(), self.data.a_precision)?;
+        let dy = xp[j].checked_sub(y + U256::from(1)).ok_or(eyre!("INSUFFICIENT_BALANCE"))?;
+        Ok((dy, dy * self.data.fee / FEE_DENOMINATOR))
+    }
+
+    /// StableSwap.get_dy, amount of coin `j` out for `dx` of coin `i`.
+    pub fn get_dy(&self, i: usize, j: usize, dx: U256) -> eyre::Result<U256> {
+        let rates = self.rates()?;
+        let xp = self.xp(&self.state.balances, &rates);
+        let (dy, fee) = self.swap_xp(i, j, dx, &xp, &rates)?;
+
+        if self.data.a_precision == U256::from(1) {
+            // Early pools scale back before taking the fee
+            let dy = dy * PRECISION / rates[j];
+            Ok(dy - dy * self.data.fee / FEE_DENOMINATOR)
+        } else {
+            Ok((dy - fee) * PRECISION / rates[j])
+        }
+    }
+
+    /// StableSwap.exchange, updating the balances with the admin share of the fee leaving the pool.
+    pub fn exchange(&mut self, i: usize, j: usize, dx: U256) -> eyre::Result<U256> {
+        let rates = self.rates()?;
+        let xp = self.xp(&self.state.balances, &rates);
+        let (dy, fee) = self.swap_xp(i, j, dx, &xp, &rates)?;
+
+        let dy = (dy - fee) * PRECISION / rates[j];
+        let admin_fee = fee * self.data.admin_fee / FEE_DENOMINATOR * PRECISION / rates[j];
+        self.state.balances[i] += dx;
+        self.state.balances[j] = self.state.balances[j].checked_sub(dy + admin_fee).ok_or(eyre!("INSUFFICIENT_BALANCE"))?;
+        Ok(dy)
+    }
+
+    /// StableSwap.calc_token_amount, LP tokens minted or burnt for the given balance changes, fees excluded.
+    pub fn calc_token_amount(&self, amounts: &[U256], deposit: bool) -> eyre::Result<U256> {
+        if amounts.len() != self.state.balances.len() {
+            return Err(eyre!("INVALID_AMOUNTS"));
+        }
+        let rates = self.rates()?;
+        let amp = self.amp();
+        let d0 = math::get_d(&self.xp(&self.state.balances, &rates), amp, self.data.a_precision)?;
+
+        let mut balances = self.state.balances.clone();
+        for (balance, amount) in balances.iter_mut().zip(amounts) {
+            *balance = if deposit {
+                *balance + *amount
+            } else {
+                balance.checked_sub(*amount).ok_or(eyre!("INSUFFICIENT_BALANCE"))?
+            };
+        }
+        let d1 = math::get_d(&self.xp(&balances, &rates), amp, self.data.a_precision)?;
+
+        let diff = if deposit { d1.checked_sub(d0) } else { d0.checked_sub(d1) }.ok_or(eyre!("D_DECREASED"))?;
+        if d0.is_zero() {
+            return Err(eyre!("ZERO_D"));
+        }
+        Ok(diff * self.state.total_supply / d0)
+    }
+
+    /// StableSwap.calc_withdraw_one_coin, amount of coin `i` for burning `token_amount` LP tokens, imbalance fee included.
+    pub fn calc_withdraw_one_coin(&self, token_amount: U256, i: usize) -> eyre::Result<U256> {
+        let n = self.state.balances.len();
+        if i >= n {
+            return Err(eyre!("INVALID_COIN_INDEX"));
+        }
+        if self.state.total_supply.is_zero() {
+            return Err(eyre!("ZERO_TOTAL_SUPPLY"));
+        }
+        let amp = self.amp();
+        let fee = self.data.fee * U256::from(n) / U256::from(4 * (n - 1));
+        let rates = self.rates()?;
+        let xp = self.xp(&self.state.balances, &rates);
+
+        let d0 = math::get_d(&xp, amp, self.data.a_precision)?;
+        let d1 = d0.checked_sub(token_amount * d0 / self.state.total_supply).ok_or(eyre!("INSUFFICIENT_SUPPLY"))?;
+        let new_y = math::get_y_d(amp, self.data.a_precision, i, &xp, d1)?;
+
+        let mut xp_reduced = xp.clone();
+        for (j, reduced) in xp_reduced.iter_mut().enumerate() {
+            let dx_expected = if j == i {
+                (xp[j] * d1 / d0).saturating_sub(new_y)
+            } else {
+                xp[j] - xp[j] * d1 / d0
+            };
+            *reduced = reduced.checked_sub(fee * dx_expected / FEE_DENOMINATOR).ok_or(eyre!("INSUFFICIENT_BALANCE"))?;
+        }
+
+        let dy = xp_reduced[i].checked_sub(math::get_y_d(amp, self.data.a_precision, i, &xp_reduced, d1)?).ok_or(eyre!("INSUFFICIENT_BALANCE"))?;
+        Ok(dy.saturating_sub(U256::from(1)) * PRECISION / rates[i])
+    }
+
+    /// Metapool get_dy_underlying, `i` and `j` indexing [`CurvePool::underlying_coins`].
+    ///
+    /// Like the pool, it prices the base pool deposit without its imbalance fee, which it approximates with half the swap fee.
+    pub fn get_dy_underlying(&self, i: usize, j: usize, dx: U256) -> eyre::Result<U256> {
+        let base = self.base.as_ref().ok_or(eyre!("NOT_A_METAPOOL"))?;
+        let max_coin = self.data.coins.len() - 1;
+        let underlying_len = max_coin + base.data.coins.len();
+        if i == j || i >= underlying_len || j >= underlying_len {
+            return Err(eyre!("INVALID_COIN_INDEX"));
+        }
+        let rates = self.rates()?;
+        let xp = self.xp(&self.state.balances, &rates);
+
+        let base_i = i.checked_sub(max_coin);
+        let base_j = j.checked_sub(max_coin);
+        let x = match (base_i, base_j) {
+            (None, _) => xp[i] + dx * rates[i] / PRECISION,
+            (Some(base_i), None) => {
+                let mut base_inputs = vec![U256::ZERO; base.data.coins.len()];
+                base_inputs[base_i] = dx;
+                let x = base.calc_token_amount(&base_inputs, true)? * rates[max_coin] / PRECISION;
+                x - x * base.data.fee / (U256::from(2) * FEE_DENOMINATOR) + xp[max_coin]
+            }
+            // Both in the base pool, the metapool is not involved
+            (Some(base_i), Some(base_j)) => return base.get_dy(base_i, base_j, dx),
+        };
+
+        let meta_i = i.min(max_coin);
+        let meta_j = j.min(max_coin);
+        let y = math::get_y(meta_i, meta_j, x, &xp, self.amp(), self.data.a_precision)?;
+        let dy = xp[meta_j].checked_sub(y + U256::from(1)).ok_or(eyre!("INSUFFICIENT_BALANCE"))?;
+        let dy = dy - self.data.fee * dy / FEE_DENOMINATOR;
+
+        match base_j {
+            None => Ok(dy * PRECISION / rates[meta_j]),
+            Some(base_j) => base.calc_withdraw_one_coin(dy * PRECISION / rates[max_coin], base_j),
+        }
+    }
+}
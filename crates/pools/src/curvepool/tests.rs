@@ -0,0 +1,182 @@
+use std::sync::Arc;
+use alloy::primitives::{Address, U256};
+use alloy::providers::{Provider, ProviderBuilder};
+use tracing::info;
+use config::Config;
+use types::pool::Pool;
+use crate::curvepool::abi::ICurvePool;
+use crate::curvepool::math::AmpRamp;
+use crate::curvepool::CurvePool;
+use crate::test_utils::units;
+
+/// Parity with get_dy for every coin pair of 3pool, whose A_PRECISION is 1.
+#[tokio::test]
+async fn test_get_dy() {
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .try_init();
+
+    let cfg = Config::default();
+    let net = config::Network::Ethereum;
+    let provider = cfg.providers.get(&net).unwrap().clone();
+    let addresses = cfg.addresses.get(&net).unwrap().clone();
+    let provider = Arc::new(ProviderBuilder::new().on_http(provider.api.parse().unwrap()));
+
+    let block = provider
+        .get_block_number()
+        .await
+        .unwrap();
+
+    let address = *addresses.curve.pools.get("3POOL").unwrap();
+    let mut pool = CurvePool::new(address);
+    pool.sync(provider.clone(), block.into()).await.unwrap();
+    let contract = ICurvePool::new(address, provider.clone());
+
+    for i in 0..pool.data.coins.len() {
+        for j in 0..pool.data.coins.len() {
+            if i == j {
+                continue;
+            }
+            let dx = U256::from(1000) * unit(&pool, i);
+            let dy_local = pool.calc_amount_out(dx, pool.data.coins[i], pool.data.coins[j]).unwrap();
+            let dy_pool = contract.get_dy(i as i128, j as i128, dx).block(block.into()).call().await.unwrap()._0;
+            info!(i, j, ?dy_local, ?dy_pool);
+
+            assert_eq!(dy_local, dy_pool);
+        }
+    }
+}
+
+/// Parity with get_dy and get_dy_underlying of the GUSD metapool over 3pool.
+#[tokio::test]
+async fn test_get_dy_underlying() {
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .try_init();
+
+    let cfg = Config::default();
+    let net = config::Network::Ethereum;
+    let provider = cfg.providers.get(&net).unwrap().clone();
+    let addresses = cfg.addresses.get(&net).unwrap().clone();
+    let provider = Arc::new(ProviderBuilder::new().on_http(provider.api.parse().unwrap()));
+
+    let block = provider
+        .get_block_number()
+        .await
+        .unwrap();
+
+    let address = *addresses.curve.pools.get("GUSD_3CRV").unwrap();
+    let mut pool = CurvePool::new(address);
+    pool.sync(provider.clone(), block.into()).await.unwrap();
+    assert!(pool.base.is_some());
+    let contract = ICurvePool::new(address, provider.clone());
+
+    let dx = U256::from(1000) * unit(&pool, 0);
+    let dy_local = pool.calc_amount_out(dx, pool.data.coins[0], pool.data.coins[1]).unwrap();
+    let dy_pool = contract.get_dy(0, 1, dx).block(block.into()).call().await.unwrap()._0;
+    assert_eq!(dy_local, dy_pool);
+
+    let underlying = pool.underlying_coins();
+    let base = pool.base.as_ref().unwrap();
+    let underlying_unit = |i: usize| if i == 0 { unit(&pool, 0) } else { unit(base, i - 1) };
+    for i in 0..underlying.len() {
+        for j in 0..underlying.len() {
+            if i == j {
+                continue;
+            }
+            let dx = U256::from(1000) * underlying_unit(i);
+            let dy_local = pool.calc_amount_out(dx, underlying[i], underlying[j]).unwrap();
+            let dy_pool = contract.get_dy_underlying(i as i128, j as i128, dx).block(block.into()).call().await.unwrap()._0;
+            info!(i, j, ?dy_local, ?dy_pool);
+
+            assert_eq!(dy_local, dy_pool);
+        }
+    }
+}
+
+/// One whole coin `i`, 10^decimals.
+fn unit(pool: &CurvePool, i: usize) -> U256 {
+    U256::from(10).pow(U256::from(18)) / pool.data.precision_multipliers[i]
+}
+
+/// DAI/USDC/USDT pool with 3pool parameters, A 2000, fee 0.01% and admin fee 50%.
+fn three_pool(a_precision: u64) -> CurvePool {
+    let mut pool = CurvePool::new(Address::repeat_byte(3));
+    pool.data.coins = vec![Address::repeat_byte(0), Address::repeat_byte(1), Address::repeat_byte(2)];
+    pool.data.precision_multipliers = vec![U256::from(1), units(1, 12), units(1, 12)];
+    pool.data.a_precision = U256::from(a_precision);
+    pool.data.fee = U256::from(1_000_000);
+    pool.data.admin_fee = U256::from(5_000_000_000u64);
+    pool.state.balances = vec![units(300_000_000, 18), units(310_000_000, 6), units(120_000_000, 6)];
+    pool.state.amp.future_a = U256::from(2000 * a_precision);
+    pool.state.total_supply = units(700_000_000, 18);
+    pool
+}
+
+#[test]
+fn test_amp_ramp() {
+    let ramp = AmpRamp {
+        initial_a: U256::from(10_000),
+        future_a: U256::from(20_000),
+        initial_a_time: 1000,
+        future_a_time: 2000,
+    };
+    assert_eq!(ramp.at(1000), U256::from(10_000));
+    assert_eq!(ramp.at(1500), U256::from(15_000));
+    assert_eq!(ramp.at(2500), U256::from(20_000));
+
+    let ramp = AmpRamp {
+        initial_a: U256::from(20_000),
+        future_a: U256::from(10_000),
+        ..ramp
+    };
+    assert_eq!(ramp.at(1250), U256::from(17_500));
+}
+
+/// Reference amounts from a line by line port of the StableSwap contracts.
+#[test]
+fn test_plain_pool() {
+    let legacy = three_pool(1);
+    assert_eq!(legacy.get_dy(1, 0, units(1_000_000, 6)).unwrap(), U256::from(999881434030912477597202u128));
+    assert_eq!(legacy.get_dy(0, 2, units(1_000_000, 18)).unwrap(), U256::from(999107546019u64));
+    assert_eq!(legacy.virtual_price().unwrap(), U256::from(1042806591936797824u64));
+    assert_eq!(legacy.calc_withdraw_one_coin(units(1_000_000, 18), 1).unwrap(), U256::from(1042956120478u64));
+
+    // Rounding differs by the fee ordering only
+    let mut pool = three_pool(100);
+    assert_eq!(pool.get_dy(0, 2, units(1_000_000, 18)).unwrap(), U256::from(999107546018u64));
+
+    let dy = pool.get_dy(1, 0, units(1_000_000, 6)).unwrap();
+    assert_eq!(pool.exchange(1, 0, units(1_000_000, 6)).unwrap(), dy);
+    assert_eq!(pool.state.balances[0], U256::from_str_radix("299000068566897478815908269", 10).unwrap());
+    assert_eq!(pool.state.balances[1], units(311_000_000, 6));
+
+    assert!(pool.get_dy(0, 0, units(1, 18)).is_err());
+    assert!(pool.get_dy(0, 3, units(1, 18)).is_err());
+}
+
+#[test]
+fn test_metapool() {
+    let mut base = three_pool(1);
+    base.data.lp_token = Address::repeat_byte(6);
+    let mut pool = CurvePool::new(Address::repeat_byte(4));
+    pool.data.coins = vec![Address::repeat_byte(5), base.data.lp_token];
+    pool.data.precision_multipliers = vec![units(1, 16), U256::from(1)];
+    pool.data.a_precision = U256::from(100);
+    pool.data.fee = U256::from(4_000_000);
+    pool.data.admin_fee = U256::from(5_000_000_000u64);
+    pool.data.base_pool = Some(base.metadata.pool);
+    pool.state.balances = vec![units(5_000_000, 2), units(4_000_000, 18)];
+    pool.state.amp.future_a = U256::from(1000 * 100);
+    pool.state.total_supply = units(9_000_000, 18);
+    pool.base = Some(Box::new(base));
+
+    let underlying = pool.underlying_coins();
+    assert_eq!(underlying.len(), 4);
+    assert_eq!(pool.get_tokens().len(), 5);
+
+    assert_eq!(pool.get_dy(0, 1, units(10_000, 2)).unwrap(), U256::from(9583889068999927608305u128));
+    assert_eq!(pool.calc_amount_out(units(10_000, 2), underlying[0], underlying[2]).unwrap(), U256::from(9995582231u64));
+    assert_eq!(pool.calc_amount_out(units(10_000, 6), underlying[3], underlying[0]).unwrap(), U256::from(1000345u64));
+    assert_eq!(pool.calc_amount_out(units(10_000, 18), underlying[1], underlying[2]).unwrap(), U256::from(9999168596u64));
+}
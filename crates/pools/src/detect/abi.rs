@@ -0,0 +1,21 @@
+use alloy::sol;
+
+sol! {
+    /// Views that tell the supported pool contracts apart, each only answered by some of them.
+    ///
+    /// Outputs are cut to the words shared by every version, a probe only needs to decode.
+    #[derive(Debug, PartialEq, Eq)]
+    #[sol(rpc)]
+    contract IPoolProbe {
+        function getPoolId() external view returns (bytes32);
+        function getVault() external view returns (address);
+        function coins(uint256 i) external view returns (address);
+        function gamma() external view returns (uint256);
+        function token0() external view returns (address);
+        function factory() external view returns (address);
+        function metadata() external view returns (uint256 dec0, uint256 dec1, uint256 r0, uint256 r1, bool st, address t0, address t1);
+        function getReserves() external view returns (uint256 reserve0, uint256 reserve1, uint256 blockTimestampLast);
+        function globalState() external view returns (uint160 price, int24 tick);
+        function tickSpacing() external view returns (int24);
+    }
+}
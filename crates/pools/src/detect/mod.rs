@@ -0,0 +1,248 @@
+mod abi;
+#[cfg(test)]
+mod tests;
+
+use alloy::eips::BlockId;
+use alloy::network::Network;
+use alloy::primitives::{keccak256, Address, B256, U256};
+use alloy::providers::Provider;
+use alloy::transports::Transport;
+use eyre::eyre;
+use tracing::instrument;
+use config::NetworkAddresses;
+use types::pool::{Pool, PoolClass, PoolProtocol};
+use crate::algebrapool::AlgebraPool;
+use crate::balancerpool::BalancerPool;
+use crate::curvecryptopool::CurveCryptoPool;
+use crate::curvepool::CurvePool;
+use crate::detect::abi::IPoolProbe;
+use crate::rpc::is_revert;
+use crate::solidlypool::SolidlyPool;
+use crate::uniswapv2pool::UniswapV2Pool;
+use crate::uniswapv3pool::UniswapV3Pool;
+
+/// Pool of any protocol [`detect`] tells apart.
+#[derive(Debug, Clone)]
+pub enum DetectedPool {
+    UniswapV2(UniswapV2Pool),
+    UniswapV3(UniswapV3Pool),
+    Algebra(AlgebraPool),
+    Solidly(SolidlyPool),
+    Curve(CurvePool),
+    CurveCrypto(CurveCryptoPool),
+    Balancer(BalancerPool),
+}
+
+impl Pool for DetectedPool {
+    fn get_class(&self) -> PoolClass {
+        self.as_pool().get_class()
+    }
+
+    fn get_protocol(&self) -> PoolProtocol {
+        self.as_pool().get_protocol()
+    }
+
+    fn get_address(&self) -> Address {
+        self.as_pool().get_address()
+    }
+
+    fn get_id(&self) -> B256 {
+        self.as_pool().get_id()
+    }
+
+    fn get_fee(&self) -> u32 {
+        self.as_pool().get_fee()
+    }
+
+    fn get_gas(&self) -> Option<u64> {
+        self.as_pool().get_gas()
+    }
+
+    fn get_tokens(&self) -> Vec<Address> {
+        self.as_pool().get_tokens()
+    }
+
+    fn calc_amount_out(&self, amount_in: U256, tok_in: Address, tok_out: Address) -> eyre::Result<U256> {
+        self.as_pool().calc_amount_out(amount_in, tok_in, tok_out)
+    }
+
+    fn can_flash_swap(&self) -> bool {
+        self.as_pool().can_flash_swap()
+    }
+}
+
+impl DetectedPool {
+    pub fn as_pool(&self) -> &dyn Pool {
+        match self {
+            DetectedPool::UniswapV2(pool) => pool,
+            DetectedPool::UniswapV3(pool) => pool,
+            DetectedPool::Algebra(pool) => pool,
+            DetectedPool::Solidly(pool) => pool,
+            DetectedPool::Curve(pool) => pool,
+            DetectedPool::CurveCrypto(pool) => pool,
+            DetectedPool::Balancer(pool) => pool,
+        }
+    }
+
+    /// Builds the pool of `protocol` at `address`, unsynced.
+    pub async fn new<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        protocol: PoolProtocol,
+        address: Address,
+        addresses: &NetworkAddresses,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<Self> {
+        let probe = IPoolProbe::new(address, provider.clone());
+        let pool = match protocol {
+            PoolProtocol::UniswapV2 | PoolProtocol::UniswapV2Like => {
+                let mut pool = UniswapV2Pool::new(address, addresses.uniswap_v2.core.clone());
+                pool.metadata.protocol = protocol;
+                DetectedPool::UniswapV2(pool)
+            }
+            PoolProtocol::UniswapV3 | PoolProtocol::UniswapV3Like | PoolProtocol::PancakeSwapV3 => {
+                let factory = probe.factory().block(block).call().await?._0;
+                let periphery = uniswap_v3_periphery(factory, addresses);
+                DetectedPool::UniswapV3(UniswapV3Pool::new_with_protocol(address, periphery, protocol))
+            }
+            PoolProtocol::Algebra => DetectedPool::Algebra(AlgebraPool::new(address)),
+            PoolProtocol::Solidly => DetectedPool::Solidly(SolidlyPool::new(address)),
+            PoolProtocol::Curve => DetectedPool::Curve(CurvePool::new(address)),
+            PoolProtocol::CurveCrypto => DetectedPool::CurveCrypto(CurveCryptoPool::new(address)),
+            PoolProtocol::BalancerV2 => {
+                let pool_id = probe.getPoolId().block(block).call().await?._0;
+                let vault = probe.getVault().block(block).call().await?._0;
+                DetectedPool::Balancer(BalancerPool::new(vault, pool_id))
+            }
+            _ => return Err(eyre!("UNSUPPORTED_PROTOCOL {protocol}")),
+        };
+        Ok(pool)
+    }
+
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn sync<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        &mut self,
+        provider: P,
+        block: BlockId
+    ) -> eyre::Result<()> {
+        match self {
+            DetectedPool::UniswapV2(pool) => pool.sync(provider, block).await,
+            DetectedPool::UniswapV3(pool) => pool.sync(provider, block).await,
+            DetectedPool::Algebra(pool) => pool.sync(provider, block).await,
+            DetectedPool::Solidly(pool) => pool.sync(provider, block).await,
+            DetectedPool::Curve(pool) => pool.sync(provider, block).await,
+            DetectedPool::CurveCrypto(pool) => pool.sync(provider, block).await,
+            DetectedPool::Balancer(pool) => pool.sync(provider, block).await,
+        }
+    }
+}
+
+/// Detects the protocol of the pool at `address` and returns it synced at `block`.
+#[instrument(skip(addresses, provider), level = "debug")]
+pub async fn detect<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+    address: Address,
+    addresses: &NetworkAddresses,
+    provider: P,
+    block: BlockId,
+) -> eyre::Result<DetectedPool> {
+    let protocol = detect_protocol(address, addresses, provider.clone(), block).await?;
+    let mut pool = DetectedPool::new(protocol, address, addresses, provider.clone(), block).await?;
+    pool.sync(provider, block).await?;
+    Ok(pool)
+}
+
+/// Protocol of the pool at `address`, from its bytecode hash when known and probing its views otherwise.
+///
+/// Curve is probed before the Uniswap likes, the fallback of its ETH pools accepts any call.
+/// A probe that reverts moves on to the next one, any other error is returned.
+#[instrument(skip(addresses, provider), level = "debug", ret)]
+pub async fn detect_protocol<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+    address: Address,
+    addresses: &NetworkAddresses,
+    provider: P,
+    block: BlockId,
+) -> eyre::Result<PoolProtocol> {
+    let code = provider.get_code_at(address).block_id(block).await?;
+    if code.is_empty() {
+        return Err(eyre!("NOT_A_CONTRACT {address}"));
+    }
+    if let Some(protocol) = addresses.pool_code_hashes.get(&keccak256(&code)) {
+        return Ok(*protocol);
+    }
+
+    let probe = IPoolProbe::new(address, provider.clone());
+    match probe.getPoolId().block(block).call().await {
+        Ok(_) => return Ok(PoolProtocol::BalancerV2),
+        Err(e) if is_revert(&e) => {}
+        Err(e) => return Err(e.into()),
+    }
+    match probe.coins(U256::ZERO).block(block).call().await {
+        Ok(_) => {
+            return match probe.gamma().block(block).call().await {
+                Ok(_) => Ok(PoolProtocol::CurveCrypto),
+                Err(e) if is_revert(&e) => Ok(PoolProtocol::Curve),
+                Err(e) => Err(e.into()),
+            };
+        }
+        Err(e) if is_revert(&e) => {}
+        Err(e) => return Err(e.into()),
+    }
+    match probe.token0().block(block).call().await {
+        Ok(_) => {}
+        Err(e) if is_revert(&e) => return Err(eyre!("UNKNOWN_PROTOCOL {address}")),
+        Err(e) => return Err(e.into()),
+    }
+    match probe.metadata().block(block).call().await {
+        Ok(_) => return Ok(PoolProtocol::Solidly),
+        Err(e) if is_revert(&e) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    let factory = probe.factory().block(block).call().await?._0;
+    match probe.getReserves().block(block).call().await {
+        Ok(_) => return Ok(uniswap_v2_protocol(factory, addresses)),
+        Err(e) if is_revert(&e) => {}
+        Err(e) => return Err(e.into()),
+    }
+    match probe.globalState().block(block).call().await {
+        Ok(_) => return Ok(PoolProtocol::Algebra),
+        Err(e) if is_revert(&e) => {}
+        Err(e) => return Err(e.into()),
+    }
+    match probe.tickSpacing().block(block).call().await {
+        Ok(_) => return Ok(uniswap_v3_protocol(factory, addresses)),
+        Err(e) if is_revert(&e) => {}
+        Err(e) => return Err(e.into()),
+    }
+    Err(eyre!("UNKNOWN_PROTOCOL {address}"))
+}
+
+/// UniswapV2 for pairs of the configured factory, UniswapV2Like for the forks.
+pub fn uniswap_v2_protocol(factory: Address, addresses: &NetworkAddresses) -> PoolProtocol {
+    if addresses.uniswap_v2.core.factory == Some(factory) {
+        PoolProtocol::UniswapV2
+    } else {
+        PoolProtocol::UniswapV2Like
+    }
+}
+
+/// UniswapV3 for pools of the configured factory, the protocol of the fork whose factory deployed it otherwise.
+pub fn uniswap_v3_protocol(factory: Address, addresses: &NetworkAddresses) -> PoolProtocol {
+    if addresses.uniswap_v3.core.factory == Some(factory) {
+        return PoolProtocol::UniswapV3;
+    }
+    addresses
+        .uniswap_v3_forks
+        .values()
+        .find(|fork| fork.core.factory == Some(factory))
+        .map(|fork| fork.protocol)
+        .unwrap_or(PoolProtocol::UniswapV3Like)
+}
+
+/// Quoter and TickLens of the deployment the factory belongs to, none for unknown factories whose pools are then read from storage.
+pub fn uniswap_v3_periphery(factory: Address, addresses: &NetworkAddresses) -> config::UniswapV3Periphery {
+    std::iter::once(&addresses.uniswap_v3)
+        .chain(addresses.uniswap_v3_forks.values())
+        .find(|deployment| deployment.core.factory == Some(factory))
+        .map(|deployment| deployment.periphery.clone())
+        .unwrap_or_default()
+}
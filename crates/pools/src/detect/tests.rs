@@ -0,0 +1,90 @@
+use alloy::primitives::{address, keccak256, Address};
+use alloy::providers::Provider;
+use tracing::info;
+use config::Config;
+use types::pool::{Pool, PoolProtocol};
+use crate::detect::{detect, uniswap_v2_protocol, uniswap_v3_periphery, uniswap_v3_protocol};
+use crate::test_utils::{test_networks, TestNetwork};
+
+/// Every configured pool is detected as the protocol of its config section.
+#[tokio::test]
+async fn test_detect() {
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .try_init();
+
+    let cfg = Config::default();
+    let has_pools = |addresses: &config::NetworkAddresses| {
+        !addresses.uniswap_v2.pools.is_empty()
+            || !addresses.uniswap_v3.pools.is_empty()
+            || !addresses.algebra.pools.is_empty()
+            || !addresses.solidly.pools.is_empty()
+            || !addresses.curve.pools.is_empty()
+            || !addresses.curve.crypto_pools.is_empty()
+            || !addresses.balancer.pools.is_empty()
+    };
+    for TestNetwork { net, addresses, provider, block } in test_networks(&cfg, has_pools).await {
+        let uniswap_v2 = addresses.uniswap_v2.pools.values().map(|pool| (*pool, PoolProtocol::UniswapV2));
+        let uniswap_v3 = addresses.uniswap_v3.pools.values().map(|pool| (*pool, PoolProtocol::UniswapV3));
+        let algebra = addresses.algebra.pools.values().map(|pool| (*pool, PoolProtocol::Algebra));
+        let solidly = addresses.solidly.pools.values().map(|pool| (*pool, PoolProtocol::Solidly));
+        let curve = addresses.curve.pools.values().map(|pool| (*pool, PoolProtocol::Curve));
+        let curve_crypto = addresses.curve.crypto_pools.values().map(|pool| (*pool, PoolProtocol::CurveCrypto));
+        // The pool address is the first 20 bytes of the poolId
+        let balancer = addresses.balancer.pools.values().map(|pool_id| (Address::from_slice(&pool_id[..20]), PoolProtocol::BalancerV2));
+
+        let pools: Vec<(Address, PoolProtocol)> = uniswap_v2
+            .chain(uniswap_v3)
+            .chain(algebra)
+            .chain(solidly)
+            .chain(curve)
+            .chain(curve_crypto)
+            .chain(balancer)
+            .collect();
+        for (address, protocol) in pools {
+            let pool = detect(address, &addresses, provider.clone(), block.into()).await.unwrap();
+            info!(?net, %address, ?protocol, tokens = ?pool.get_tokens());
+
+            assert_eq!(pool.get_protocol(), protocol);
+            assert_eq!(pool.get_address(), address);
+            assert!(!pool.get_tokens().is_empty());
+        }
+
+        // Known bytecode skips probing, a V2 pair has no immutables so all of them share it
+        if let Some(pair) = addresses.uniswap_v2.pools.values().next() {
+            let code = provider.get_code_at(*pair).block_id(block.into()).await.unwrap();
+            let mut addresses = addresses.clone();
+            addresses.pool_code_hashes.insert(keccak256(&code), PoolProtocol::UniswapV2Like);
+            let pool = detect(*pair, &addresses, provider.clone(), block.into()).await.unwrap();
+            assert_eq!(pool.get_protocol(), PoolProtocol::UniswapV2Like);
+        }
+
+        // Tokens are not pools
+        let weth = addresses.wrapped_native.unwrap();
+        assert!(detect(weth, &addresses, provider.clone(), block.into()).await.is_err());
+    }
+}
+
+#[test]
+fn test_factory_protocols() {
+    let cfg = Config::default();
+    let addresses = cfg.addresses.get(&config::Network::Ethereum).unwrap().clone();
+    let other = Address::repeat_byte(1);
+
+    let uniswap_v2_factory = address!("5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f");
+    assert_eq!(uniswap_v2_protocol(uniswap_v2_factory, &addresses), PoolProtocol::UniswapV2);
+    assert_eq!(uniswap_v2_protocol(other, &addresses), PoolProtocol::UniswapV2Like);
+
+    let uniswap_v3_factory = address!("1F98431c8aD98523631AE4a59f267346ea31F984");
+    assert_eq!(uniswap_v3_protocol(uniswap_v3_factory, &addresses), PoolProtocol::UniswapV3);
+    assert_eq!(uniswap_v3_protocol(other, &addresses), PoolProtocol::UniswapV3Like);
+    assert_eq!(uniswap_v3_periphery(uniswap_v3_factory, &addresses).tick_lens, addresses.uniswap_v3.periphery.tick_lens);
+    assert_eq!(uniswap_v3_periphery(other, &addresses).tick_lens, None);
+
+    let mut fork = addresses.uniswap_v3.clone();
+    fork.protocol = PoolProtocol::PancakeSwapV3;
+    fork.core.factory = Some(other);
+    let mut addresses = addresses;
+    addresses.uniswap_v3_forks.insert("PancakeSwapV3".to_string(), fork);
+    assert_eq!(uniswap_v3_protocol(other, &addresses), PoolProtocol::PancakeSwapV3);
+}
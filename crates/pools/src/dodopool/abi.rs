@@ -0,0 +1,30 @@
+use alloy::sol;
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    #[sol(rpc)]
+    contract IDODOV2 {
+        struct PMMState {
+            uint256 i;
+            uint256 K;
+            uint256 B;
+            uint256 Q;
+            uint256 B0;
+            uint256 Q0;
+            uint8 R;
+        }
+
+        event DODOSwap(address fromToken, address toToken, uint256 fromAmount, uint256 toAmount, address trader, address receiver);
+        function _BASE_TOKEN_() external view returns (address);
+        function _QUOTE_TOKEN_() external view returns (address);
+        function getPMMState() external view returns (PMMState memory state);
+        function getUserFeeRate(address user) external view returns (uint256 lpFeeRate, uint256 mtFeeRate);
+        function querySellBase(address trader, uint256 payBaseAmount) external view returns (uint256 receiveQuoteAmount, uint256 mtFee);
+        function querySellQuote(address trader, uint256 payQuoteAmount) external view returns (uint256 receiveBaseAmount, uint256 mtFee);
+    }
+
+    #[sol(rpc)]
+    contract IDODOV2Factory {
+        function getDODOPool(address baseToken, address quoteToken) external view returns (address[] memory machines);
+    }
+}
@@ -0,0 +1,113 @@
+use alloy::primitives::U256;
+use eyre::eyre;
+
+/// 1.0 in the 18 decimals fixed point of DecimalMath.
+pub const ONE: U256 = U256::from_limbs([1_000_000_000_000_000_000, 0, 0, 0]);
+/// 1e36, ONE squared.
+pub const ONE2: U256 = U256::from_limbs([0xb34b9f1000000000, 0xc097ce7bc90715, 0, 0]);
+
+fn add(a: U256, b: U256) -> eyre::Result<U256> {
+    a.checked_add(b).ok_or(eyre!("ADD_ERROR"))
+}
+
+fn sub(a: U256, b: U256) -> eyre::Result<U256> {
+    a.checked_sub(b).ok_or(eyre!("SUB_ERROR"))
+}
+
+fn mul(a: U256, b: U256) -> eyre::Result<U256> {
+    a.checked_mul(b).ok_or(eyre!("MUL_ERROR"))
+}
+
+fn div(a: U256, b: U256) -> eyre::Result<U256> {
+    a.checked_div(b).ok_or(eyre!("DIVIDING_ERROR"))
+}
+
+/// Division rounded up, as SafeMath.divCeil.
+fn div_ceil(a: U256, b: U256) -> eyre::Result<U256> {
+    let quotient = div(a, b)?;
+    Ok(if a - quotient * b > U256::ZERO { quotient + U256::from(1) } else { quotient })
+}
+
+pub fn mul_floor(target: U256, d: U256) -> eyre::Result<U256> {
+    Ok(mul(target, d)? / ONE)
+}
+
+pub fn div_floor(target: U256, d: U256) -> eyre::Result<U256> {
+    div(mul(target, ONE)?, d)
+}
+
+pub fn div_ceil_decimal(target: U256, d: U256) -> eyre::Result<U256> {
+    div_ceil(mul(target, ONE)?, d)
+}
+
+pub fn reciprocal_floor(target: U256) -> eyre::Result<U256> {
+    div(ONE2, target)
+}
+
+/// Babylonian square root as DODO's Math.sqrt, which starts from x / 2 + 1 and so returns 2 for x = 2.
+pub fn sqrt(x: U256) -> U256 {
+    let mut z = x / U256::from(2) + U256::from(1);
+    let mut y = x;
+    while z < y {
+        y = z;
+        z = (x / z + z) / U256::from(2);
+    }
+    y
+}
+
+/// Integral of the PMM price from v2 to v1, i * (v1 - v2) * (1 - k + k * v0^2 / v1 / v2), as DODOMath._GeneralIntegrate.
+pub fn general_integrate(v0: U256, v1: U256, v2: U256, i: U256, k: U256) -> eyre::Result<U256> {
+    if v0.is_zero() {
+        return Err(eyre!("TARGET_IS_ZERO"));
+    }
+    let fair_amount = mul(i, sub(v1, v2)?)?;
+    if k.is_zero() {
+        return Ok(fair_amount / ONE);
+    }
+    let v0v0v1v2 = div_floor(div(mul(v0, v0)?, v1)?, v2)?;
+    let penalty = mul_floor(k, v0v0v1v2)?;
+    Ok(mul(add(sub(ONE, k)?, penalty)?, fair_amount)? / ONE2)
+}
+
+/// Amount of the reserve at `v1` paid out for `delta` of the other token, as DODOMath._SolveQuadraticFunctionForTrade.
+///
+/// Solves (1 - k) * v2^2 + b * v2 - k * v0^2 = 0 for the reserve after the trade, with b = k * v0^2 / v1 - i * delta - (1 - k) * v1.
+pub fn solve_quadratic_function_for_trade(v0: U256, v1: U256, delta: U256, i: U256, k: U256) -> eyre::Result<U256> {
+    if v0.is_zero() {
+        return Err(eyre!("TARGET_IS_ZERO"));
+    }
+    if delta.is_zero() {
+        return Ok(U256::ZERO);
+    }
+    if k.is_zero() {
+        return Ok(mul_floor(i, delta)?.min(v1));
+    }
+    if k == ONE {
+        // v1 - v2 = v1 * temp / (1 + temp) with temp = i * delta * v1 / v0^2, reordered when the product overflows
+        let i_delta = mul(i, delta)?;
+        let temp = if i_delta.is_zero() {
+            U256::ZERO
+        } else if let Some(product) = i_delta.checked_mul(v1) {
+            div(product, mul(v0, v0)?)?
+        } else {
+            div(mul(div(mul(delta, v1)?, v0)?, i)?, v0)?
+        };
+        return div(mul(v1, temp)?, add(temp, ONE)?);
+    }
+
+    // |b| and whether b is positive
+    let part2 = add(mul(div(mul(k, v0)?, v1)?, v0)?, mul(i, delta)?)?;
+    let part1 = mul(sub(ONE, k)?, v1)?;
+    let (b_abs, b_sig) = if part1 >= part2 { (part1 - part2, false) } else { (part2 - part1, true) };
+    let b_abs = b_abs / ONE;
+
+    // sqrt(b^2 + 4 * (1 - k) * k * v0^2)
+    let square_root = mul_floor(mul(sub(ONE, k)?, U256::from(4))?, mul(mul_floor(k, v0)?, v0)?)?;
+    let square_root = sqrt(add(mul(b_abs, b_abs)?, square_root)?);
+
+    let denominator = mul(sub(ONE, k)?, U256::from(2))?;
+    let numerator = if b_sig { sub(square_root, b_abs)? } else { add(b_abs, square_root)? };
+
+    let v2 = div_ceil_decimal(numerator, denominator)?;
+    Ok(if v2 > v1 { U256::ZERO } else { v1 - v2 })
+}
@@ -0,0 +1,206 @@
+mod abi;
+pub mod math;
+pub mod pmm;
+#[cfg(test)]
+mod tests;
+
+use alloy::eips::BlockId;
+use alloy::network::Network;
+use alloy::primitives::{Address, U256};
+use alloy::providers::Provider;
+use alloy::transports::Transport;
+use eyre::eyre;
+use tracing::instrument;
+use types::pool::{check_pair, Pool, PoolClass, PoolProtocol};
+use crate::dodopool::abi::{IDODOV2, IDODOV2Factory};
+use crate::dodopool::math::mul_floor;
+use crate::dodopool::pmm::PmmState;
+
+#[derive(Debug, Clone)]
+pub struct DodoPoolMetadata {
+    pub pool: Address,
+    /// Account the maintainer fee rate is read for, the fee rate model may discount some traders.
+    pub trader: Address,
+    pub protocol: PoolProtocol,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DodoPoolData {
+    pub base_token: Address,
+    pub quote_token: Address,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DodoPoolState {
+    pub pmm: PmmState,
+    /// Fee rates of the trader over 1e18, the owner of a private pool can change them along with i and K.
+    pub lp_fee_rate: U256,
+    pub mt_fee_rate: U256,
+}
+
+/// DODO V2 vending machine, stable or private pool, priced on the PMM curve around the guide price i.
+#[derive(Debug, Clone)]
+pub struct DodoPool {
+    pub metadata: DodoPoolMetadata,
+    pub data: DodoPoolData,
+    pub state: DodoPoolState,
+}
+
+impl Pool for DodoPool {
+    fn get_class(&self) -> PoolClass {
+        PoolClass::Dodo
+    }
+
+    fn get_protocol(&self) -> PoolProtocol {
+        self.metadata.protocol
+    }
+
+    fn get_address(&self) -> Address {
+        self.metadata.pool
+    }
+
+    /// LP and maintainer fees in hundredths of a bip.
+    fn get_fee(&self) -> u32 {
+        ((self.state.lp_fee_rate + self.state.mt_fee_rate) / U256::from(1_000_000_000_000u64)).saturating_to::<u32>()
+    }
+
+    fn get_tokens(&self) -> Vec<Address> {
+        vec![
+            self.data.base_token,
+            self.data.quote_token
+        ]
+    }
+
+    fn calc_amount_out(&self, amount_in: U256, tok_in: Address, tok_out: Address) -> eyre::Result<U256> {
+        check_pair(self.data.base_token, self.data.quote_token, tok_in, tok_out)?;
+        let (amount_out, _) = if tok_in == self.data.base_token {
+            self.query_sell_base(amount_in)?
+        } else {
+            self.query_sell_quote(amount_in)?
+        };
+        if amount_out.is_zero() {
+            return Err(eyre!("OUT_AMOUNT_IS_ZERO"));
+        }
+        Ok(amount_out)
+    }
+}
+
+impl DodoPool {
+    pub fn new(pool: Address) -> Self {
+        DodoPool {
+            metadata: DodoPoolMetadata {
+                pool,
+                trader: Address::ZERO,
+                protocol: PoolProtocol::DodoV2,
+            },
+            data: Default::default(),
+            state: Default::default(),
+        }
+    }
+
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn sync<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        &mut self,
+        provider: P,
+        block: BlockId
+    ) -> eyre::Result<()> {
+        self.data = DodoPool::fetch_data(
+            &self.metadata,
+            provider.clone(),
+            block,
+        ).await?;
+
+        self.state = DodoPool::fetch_state(
+            &self.metadata,
+            provider.clone(),
+            block,
+        ).await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn fetch_data<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &DodoPoolMetadata,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<DodoPoolData> {
+        let pool = IDODOV2::new(metadata.pool, provider.clone());
+
+        let base_token: Address = pool._BASE_TOKEN_().block(block).call().await?._0;
+        let quote_token: Address = pool._QUOTE_TOKEN_().block(block).call().await?._0;
+
+        Ok(DodoPoolData {
+            base_token,
+            quote_token,
+        })
+    }
+
+    /// Pools the DVM, DSP and DPP factories list for the pair, with either token as base.
+    #[instrument(skip(provider), level = "debug", ret)]
+    pub async fn discover_pair_pools<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        factories: &[Address],
+        tok_a: Address,
+        tok_b: Address,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<Vec<Address>> {
+        let mut pools = Vec::new();
+        for factory in factories {
+            let factory = IDODOV2Factory::new(*factory, provider.clone());
+            for (base_token, quote_token) in [(tok_a, tok_b), (tok_b, tok_a)] {
+                pools.extend(factory.getDODOPool(base_token, quote_token).block(block).call().await?.machines);
+            }
+        }
+        Ok(pools)
+    }
+
+    /// Reads the PMM state and the fee rates, to be refreshed after every oracle or parameter update as well as swaps.
+    #[instrument(skip(provider), level = "debug", ret)]
+    pub async fn fetch_state<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &DodoPoolMetadata,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<DodoPoolState> {
+        let pool = IDODOV2::new(metadata.pool, provider.clone());
+
+        let state = pool.getPMMState().block(block).call().await?.state;
+        let fee_rates = pool.getUserFeeRate(metadata.trader).block(block).call().await?;
+
+        Ok(DodoPoolState {
+            pmm: PmmState {
+                i: state.i,
+                k: state.K,
+                b: state.B,
+                q: state.Q,
+                b0: state.B0,
+                q0: state.Q0,
+                r: state.R.try_into()?,
+            },
+            lp_fee_rate: fee_rates.lpFeeRate,
+            mt_fee_rate: fee_rates.mtFeeRate,
+        })
+    }
+
+    /// Quote received for `pay_base_amount` and the maintainer fee, as querySellBase.
+    pub fn query_sell_base(&self, pay_base_amount: U256) -> eyre::Result<(U256, U256)> {
+        let (receive_quote_amount, _) = self.state.pmm.sell_base_token(pay_base_amount)?;
+        self.take_fees(receive_quote_amount)
+    }
+
+    /// Base received for `pay_quote_amount` and the maintainer fee, as querySellQuote.
+    pub fn query_sell_quote(&self, pay_quote_amount: U256) -> eyre::Result<(U256, U256)> {
+        let (receive_base_amount, _) = self.state.pmm.sell_quote_token(pay_quote_amount)?;
+        self.take_fees(receive_base_amount)
+    }
+
+    /// Both fees are taken out of the amount received.
+    fn take_fees(&self, receive_amount: U256) -> eyre::Result<(U256, U256)> {
+        let mt_fee = mul_floor(receive_amount, self.state.mt_fee_rate)?;
+        let receive_amount = receive_amount
+            .checked_sub(mul_floor(receive_amount, self.state.lp_fee_rate)?)
+            .and_then(|amount| amount.checked_sub(mt_fee))
+            .ok_or(eyre!("SUB_ERROR"))?;
+        Ok((receive_amount, mt_fee))
+    }
+}
@@ -0,0 +1,100 @@
+use alloy::primitives::U256;
+use eyre::eyre;
+use crate::dodopool::math::{general_integrate, reciprocal_floor, solve_quadratic_function_for_trade};
+
+/// Which side of its targets the pool is on, as PMMPricing.RState.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RState {
+    /// Both reserves at their targets.
+    #[default]
+    One,
+    /// Quote sold to the pool, base below its target and quote above.
+    AboveOne,
+    /// Base sold to the pool, quote below its target and base above.
+    BelowOne,
+}
+
+impl TryFrom<u8> for RState {
+    type Error = eyre::Report;
+
+    fn try_from(r: u8) -> eyre::Result<Self> {
+        match r {
+            0 => Ok(RState::One),
+            1 => Ok(RState::AboveOne),
+            2 => Ok(RState::BelowOne),
+            _ => Err(eyre!("INVALID_R_STATE {r}")),
+        }
+    }
+}
+
+/// Curve parameters and reserves as getPMMState, with the targets already adjusted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PmmState {
+    /// Oracle or guide price of base in quote, 18 decimals.
+    pub i: U256,
+    /// Slippage factor, 0 is a constant price and 1e18 a constant product.
+    pub k: U256,
+    pub b: U256,
+    pub q: U256,
+    pub b0: U256,
+    pub q0: U256,
+    pub r: RState,
+}
+
+impl PmmState {
+    /// Quote paid for `pay_base_amount` before fees and the resulting R state, as PMMPricing.sellBaseToken.
+    pub fn sell_base_token(&self, pay_base_amount: U256) -> eyre::Result<(U256, RState)> {
+        match self.r {
+            RState::One => Ok((self.r_one_sell_base_token(pay_base_amount)?, RState::BelowOne)),
+            RState::AboveOne => {
+                let back_to_one_pay_base = self.b0.checked_sub(self.b).ok_or(eyre!("SUB_ERROR"))?;
+                let back_to_one_receive_quote = self.q.checked_sub(self.q0).ok_or(eyre!("SUB_ERROR"))?;
+                if pay_base_amount < back_to_one_pay_base {
+                    let receive_quote_amount = general_integrate(self.b0, self.b.checked_add(pay_base_amount).ok_or(eyre!("ADD_ERROR"))?, self.b, self.i, self.k)?;
+                    Ok((receive_quote_amount.min(back_to_one_receive_quote), RState::AboveOne))
+                } else if pay_base_amount == back_to_one_pay_base {
+                    Ok((back_to_one_receive_quote, RState::One))
+                } else {
+                    let receive_quote_amount = self.r_one_sell_base_token(pay_base_amount - back_to_one_pay_base)?;
+                    Ok((back_to_one_receive_quote + receive_quote_amount, RState::BelowOne))
+                }
+            }
+            RState::BelowOne => Ok((
+                solve_quadratic_function_for_trade(self.q0, self.q, pay_base_amount, self.i, self.k)?,
+                RState::BelowOne,
+            )),
+        }
+    }
+
+    /// Base paid for `pay_quote_amount` before fees and the resulting R state, as PMMPricing.sellQuoteToken.
+    pub fn sell_quote_token(&self, pay_quote_amount: U256) -> eyre::Result<(U256, RState)> {
+        match self.r {
+            RState::One => Ok((self.r_one_sell_quote_token(pay_quote_amount)?, RState::AboveOne)),
+            RState::AboveOne => Ok((
+                solve_quadratic_function_for_trade(self.b0, self.b, pay_quote_amount, reciprocal_floor(self.i)?, self.k)?,
+                RState::AboveOne,
+            )),
+            RState::BelowOne => {
+                let back_to_one_pay_quote = self.q0.checked_sub(self.q).ok_or(eyre!("SUB_ERROR"))?;
+                let back_to_one_receive_base = self.b.checked_sub(self.b0).ok_or(eyre!("SUB_ERROR"))?;
+                if pay_quote_amount < back_to_one_pay_quote {
+                    let receive_base_amount = general_integrate(self.q0, self.q.checked_add(pay_quote_amount).ok_or(eyre!("ADD_ERROR"))?, self.q, reciprocal_floor(self.i)?, self.k)?;
+                    Ok((receive_base_amount.min(back_to_one_receive_base), RState::BelowOne))
+                } else if pay_quote_amount == back_to_one_pay_quote {
+                    Ok((back_to_one_receive_base, RState::One))
+                } else {
+                    let receive_base_amount = self.r_one_sell_quote_token(pay_quote_amount - back_to_one_pay_quote)?;
+                    Ok((back_to_one_receive_base + receive_base_amount, RState::AboveOne))
+                }
+            }
+        }
+    }
+
+    fn r_one_sell_base_token(&self, pay_base_amount: U256) -> eyre::Result<U256> {
+        solve_quadratic_function_for_trade(self.q0, self.q0, pay_base_amount, self.i, self.k)
+    }
+
+    fn r_one_sell_quote_token(&self, pay_quote_amount: U256) -> eyre::Result<U256> {
+        solve_quadratic_function_for_trade(self.b0, self.b0, pay_quote_amount, reciprocal_floor(self.i)?, self.k)
+    }
+}
@@ -0,0 +1,136 @@
+use alloy::primitives::{address, Address, U256};
+use tracing::info;
+use config::Config;
+use types::pool::Pool;
+use crate::dodopool::abi::IDODOV2;
+use crate::dodopool::math::{sqrt, ONE};
+use crate::dodopool::pmm::{PmmState, RState};
+use crate::dodopool::DodoPool;
+use crate::test_utils::{test_networks, TestNetwork};
+
+/// Parity with querySellBase and querySellQuote for every configured pool and the WETH/USDC pools of the configured factories.
+#[tokio::test]
+async fn test_query_sell() {
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .try_init();
+
+    let usdc = address!("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+    let cfg = Config::default();
+    let mut checked = 0;
+    for TestNetwork { net, addresses, provider, block } in test_networks(&cfg, |addresses| !addresses.dodo.pools.is_empty() || !addresses.dodo.factories.is_empty()).await {
+        let mut pools: Vec<(String, Address)> = addresses.dodo.pools.clone().into_iter().collect();
+        if net == config::Network::Ethereum {
+            let discovered = DodoPool::discover_pair_pools(
+                &addresses.dodo.factories,
+                addresses.wrapped_native.unwrap(),
+                usdc,
+                provider.clone(),
+                block.into(),
+            ).await.unwrap();
+            pools.extend(discovered.into_iter().map(|pool| (format!("WETH_USDC_{pool}"), pool)));
+        }
+
+        for (name, address) in pools.iter() {
+            let mut pool = DodoPool::new(*address);
+            pool.sync(provider.clone(), block.into()).await.unwrap();
+            // Drained private pools are listed too
+            if pool.state.pmm.b.is_zero() || pool.state.pmm.q.is_zero() {
+                continue;
+            }
+            let contract = IDODOV2::new(*address, provider.clone());
+
+            // A hundredth of each reserve, past the targets in whichever state the pool is in
+            let pay_base_amount = pool.state.pmm.b / U256::from(100);
+            let (receive_quote_amount, mt_fee) = pool.query_sell_base(pay_base_amount).unwrap();
+            let quote = contract.querySellBase(pool.metadata.trader, pay_base_amount).block(block.into()).call().await.unwrap();
+            info!(?net, %name, %pay_base_amount, %receive_quote_amount, %quote.receiveQuoteAmount);
+            assert_eq!(receive_quote_amount, quote.receiveQuoteAmount);
+            assert_eq!(mt_fee, quote.mtFee);
+
+            let pay_quote_amount = pool.state.pmm.q / U256::from(100);
+            let (receive_base_amount, mt_fee) = pool.query_sell_quote(pay_quote_amount).unwrap();
+            let quote = contract.querySellQuote(pool.metadata.trader, pay_quote_amount).block(block.into()).call().await.unwrap();
+            info!(?net, %name, %pay_quote_amount, %receive_base_amount, %quote.receiveBaseAmount);
+            assert_eq!(receive_base_amount, quote.receiveBaseAmount);
+            assert_eq!(mt_fee, quote.mtFee);
+            checked += 1;
+        }
+    }
+    assert!(checked > 0, "no DODO V2 pool configured or discovered");
+}
+
+fn base(amount: u64) -> U256 {
+    U256::from(amount) * ONE
+}
+
+fn quote(amount: u64) -> U256 {
+    U256::from(amount) * U256::from(1_000_000)
+}
+
+/// WETH against USDC at 2000, k = 0.1, with base and quote `b` and `q` away from targets of 1000 WETH and 2000000 USDC.
+fn state(b: u64, q: u64, r: RState) -> PmmState {
+    PmmState {
+        i: U256::from(2_000_000_000u64),
+        k: U256::from(100_000_000_000_000_000u64),
+        b: base(b),
+        q: quote(q),
+        b0: base(1000),
+        q0: quote(2_000_000),
+        r,
+    }
+}
+
+#[test]
+fn test_sqrt() {
+    assert_eq!(sqrt(U256::ZERO), U256::ZERO);
+    assert_eq!(sqrt(U256::from(2)), U256::from(2));
+    assert_eq!(sqrt(U256::from(3)), U256::from(1));
+    assert_eq!(sqrt(U256::from(10).pow(U256::from(20))), U256::from(10_000_000_000u64));
+}
+
+/// Reference values from a line by line port of PMMPricing and DODOMath.
+#[test]
+fn test_pmm_pricing() {
+    let at_targets = state(1000, 2_000_000, RState::One);
+    assert_eq!(at_targets.sell_base_token(base(10)).unwrap(), (U256::from(19_979_838_894u64), RState::BelowOne));
+    assert_eq!(at_targets.sell_quote_token(quote(20_000)).unwrap(), (U256::from(9_989_919_447_032_049_723u64), RState::AboveOne));
+
+    // Base below its target, selling base moves back towards it and then past it
+    let above = state(990, 2_020_000, RState::AboveOne);
+    assert_eq!(above.sell_base_token(base(5)).unwrap(), (U256::from(10_015_176_894u64), RState::AboveOne));
+    assert_eq!(above.sell_base_token(base(10)).unwrap(), (quote(20_000), RState::One));
+    assert_eq!(above.sell_base_token(base(25)).unwrap(), (U256::from(49_954_454_386u64), RState::BelowOne));
+    assert_eq!(above.sell_quote_token(quote(20_000)).unwrap(), (U256::from(9_969_410_712_414_386_362u64), RState::AboveOne));
+
+    // Quote below its target
+    let below = state(1010, 1_980_000, RState::BelowOne);
+    assert_eq!(below.sell_quote_token(quote(5000)).unwrap(), (U256::from(2_504_433_503_803_661_615u64), RState::BelowOne));
+    assert_eq!(below.sell_quote_token(quote(50_000)).unwrap(), (U256::from(24_977_227_193_057_350_650u128), RState::AboveOne));
+    assert_eq!(below.sell_base_token(base(1)).unwrap(), (U256::from(1_995_742_383u64), RState::BelowOne));
+
+    // Constant price and constant product
+    let constant_price = PmmState { k: U256::ZERO, ..at_targets };
+    assert_eq!(constant_price.sell_base_token(base(1)).unwrap().0, quote(2000));
+    assert_eq!(constant_price.sell_quote_token(quote(2000)).unwrap().0, base(1));
+    let constant_product = PmmState { k: ONE, ..at_targets };
+    assert_eq!(constant_product.sell_base_token(base(1)).unwrap().0, U256::from(1_998_001_998u64));
+    assert_eq!(constant_product.sell_quote_token(quote(2000)).unwrap().0, U256::from(999_000_999_000_999_000u64));
+}
+
+#[test]
+fn test_fees() {
+    let mut pool = DodoPool::new(Address::ZERO);
+    pool.data.base_token = Address::repeat_byte(1);
+    pool.data.quote_token = Address::repeat_byte(2);
+    pool.state.pmm = state(1000, 2_000_000, RState::One);
+    // 0.3% to LPs and 0.1% to the maintainer
+    pool.state.lp_fee_rate = U256::from(3_000_000_000_000_000u64);
+    pool.state.mt_fee_rate = U256::from(1_000_000_000_000_000u64);
+
+    assert_eq!(pool.get_fee(), 4000);
+    assert_eq!(pool.query_sell_base(base(10)).unwrap(), (U256::from(19_899_919_540u64), U256::from(19_979_838u64)));
+    assert_eq!(pool.query_sell_quote(quote(20_000)).unwrap(), (U256::from(9_949_959_769_243_921_525u64), U256::from(9_989_919_447_032_049u64)));
+    assert_eq!(pool.calc_amount_out(base(10), pool.data.base_token, pool.data.quote_token).unwrap(), U256::from(19_899_919_540u64));
+    assert!(pool.calc_amount_out(U256::ZERO, pool.data.quote_token, pool.data.base_token).is_err());
+}
@@ -0,0 +1,198 @@
+mod abi;
+pub mod pricing;
+
+use alloy::eips::BlockId;
+use alloy::{
+    network::Network,
+    primitives::{Address, U256},
+    providers::Provider,
+    transports::Transport,
+};
+use eyre::eyre;
+use tracing::instrument;
+use types::pool::{check_pair, Pool, PoolClass, PoolProtocol};
+use crate::erc4626pool::abi::{IERC20, IERC4626};
+use crate::erc4626pool::pricing::local::{self, RATE_SHARES};
+
+/// How a vault converts between assets and shares, which the local math must follow to match its previews.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ShareMath {
+    /// OpenZeppelin, with 10^offset virtual shares and one virtual asset.
+    Virtual { decimals_offset: u8 },
+    /// Solmate, proportional to totalSupply over totalAssets and 1:1 while empty.
+    #[default]
+    Proportional,
+    /// Fixed rate vaults like sDAI, whose totals are derived from an accumulator rather than the other way round.
+    Rate,
+}
+
+#[derive(Debug, Clone)]
+pub struct PoolMetadata {
+    pub vault: Address,
+    /// Account maxDeposit and maxWithdraw are read for, the executor redeeming the shares.
+    ///
+    /// Without one, redemptions are only capped by totalAssets.
+    pub account: Option<Address>,
+    pub protocol: PoolProtocol,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PoolData {
+    pub asset: Address,
+    /// The vault is also the share token.
+    pub vault: Address,
+    pub share_math: ShareMath,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PoolState {
+    pub total_assets: U256,
+    pub total_supply: U256,
+    /// previewRedeem of 1e27 shares, what [`ShareMath::Rate`] vaults are priced with.
+    pub assets_per_share: U256,
+    pub max_deposit: U256,
+    pub max_withdraw: Option<U256>,
+}
+
+/// ERC-4626 vault as a pool between its asset and its shares, deposit one way and redeem the other.
+#[derive(Debug, Clone)]
+pub struct Erc4626Pool {
+    pub metadata: PoolMetadata,
+    pub data: PoolData,
+    pub state: PoolState,
+}
+
+impl Pool for Erc4626Pool {
+    fn get_class(&self) -> PoolClass {
+        PoolClass::Erc4626
+    }
+
+    fn get_protocol(&self) -> PoolProtocol {
+        self.metadata.protocol
+    }
+
+    fn get_address(&self) -> Address {
+        self.metadata.vault
+    }
+
+    fn get_tokens(&self) -> Vec<Address> {
+        vec![
+            self.data.asset,
+            self.data.vault
+        ]
+    }
+
+    fn calc_amount_out(&self, amount_in: U256, tok_in: Address, tok_out: Address) -> eyre::Result<U256> {
+        check_pair(self.data.asset, self.data.vault, tok_in, tok_out)?;
+        local::calc_amount_out(
+            amount_in,
+            tok_in,
+            (&self.data).into(),
+            (&self.state).into()
+        )
+    }
+}
+
+impl Erc4626Pool {
+    pub fn new(vault: Address) -> Self {
+        Erc4626Pool {
+            metadata: PoolMetadata {
+                vault,
+                account: None,
+                protocol: PoolProtocol::Erc4626,
+            },
+            data: Default::default(),
+            state: Default::default(),
+        }
+    }
+
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn sync<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        &mut self,
+        provider: P,
+        block: BlockId
+    ) -> eyre::Result<()> {
+        self.data = Erc4626Pool::fetch_data(
+            &self.metadata,
+            provider.clone(),
+            block,
+        ).await?;
+
+        self.state = Erc4626Pool::fetch_state(
+            &self.metadata,
+            provider.clone(),
+            block,
+        ).await?;
+
+        Ok(())
+    }
+
+    /// Reads the asset and finds the share math reproducing the vault previews.
+    ///
+    /// Vaults matching none, e.g. charging deposit fees, can only be quoted with [`pricing::vault`].
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn fetch_data<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &PoolMetadata,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<PoolData> {
+        let vault = IERC4626::new(metadata.vault, provider.clone());
+
+        let asset: Address = vault.asset().block(block).call().await?._0;
+        let asset_decimals = IERC20::new(asset, provider.clone()).decimals().block(block).call().await?._0;
+        let vault_decimals = vault.decimals().block(block).call().await?._0;
+        let state = Erc4626Pool::fetch_state(metadata, provider.clone(), block).await?;
+
+        // Odd amounts of the size of the vault, which rounding differences show up on
+        let probe_assets = state.total_assets / U256::from(7) + U256::from(1);
+        let probe_shares = state.total_supply / U256::from(7) + U256::from(1);
+        let shares = vault.previewDeposit(probe_assets).block(block).call().await?._0;
+        let assets = vault.previewRedeem(probe_shares).block(block).call().await?._0;
+
+        let state: pricing::PoolState = (&state).into();
+        let candidates = [
+            ShareMath::Virtual { decimals_offset: vault_decimals.saturating_sub(asset_decimals) },
+            ShareMath::Proportional,
+            ShareMath::Rate,
+        ];
+        let share_math = candidates
+            .into_iter()
+            .find(|share_math| {
+                local::preview_deposit(probe_assets, *share_math, &state).ok() == Some(shares)
+                    && local::preview_redeem(probe_shares, *share_math, &state).ok() == Some(assets)
+            })
+            .ok_or(eyre!("UNSUPPORTED_SHARE_MATH {}", metadata.vault))?;
+
+        Ok(PoolData {
+            asset,
+            vault: metadata.vault,
+            share_math,
+        })
+    }
+
+    #[instrument(skip(provider), level = "debug", ret)]
+    pub async fn fetch_state<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &PoolMetadata,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<PoolState> {
+        let vault = IERC4626::new(metadata.vault, provider.clone());
+
+        let total_assets = vault.totalAssets().block(block).call().await?._0;
+        let total_supply = vault.totalSupply().block(block).call().await?._0;
+        let assets_per_share = vault.previewRedeem(RATE_SHARES).block(block).call().await?._0;
+        let max_deposit = vault.maxDeposit(metadata.account.unwrap_or_default()).block(block).call().await?._0;
+        let max_withdraw = match metadata.account {
+            Some(account) => Some(vault.maxWithdraw(account).block(block).call().await?._0),
+            None => None,
+        };
+
+        Ok(PoolState {
+            total_assets,
+            total_supply,
+            assets_per_share,
+            max_deposit,
+            max_withdraw,
+        })
+    }
+}
@@ -0,0 +1,68 @@
+use alloy::primitives::{Address, U256};
+use eyre::eyre;
+use tracing::instrument;
+use crate::erc4626pool::ShareMath;
+
+/// Shares quoted for [`ShareMath::Rate`], previewRedeem of 1e27 shares keeping the full precision of ray based rates.
+pub const RATE_SHARES: U256 = U256::from_limbs([0x9fd0803ce8000000, 0x33b2e3c, 0, 0]);
+
+/// Shares minted for `amount_in` assets, or assets redeemed for `amount_in` shares, within the vault limits.
+#[instrument(level = "debug", ret)]
+pub fn calc_amount_out(
+    amount_in: U256,
+    tok_in: Address,
+    data: super::PoolData,
+    state: super::PoolState,
+) -> eyre::Result<U256> {
+    let amount_out = if tok_in == data.asset {
+        if amount_in > state.max_deposit {
+            return Err(eyre!("MAX_DEPOSIT"));
+        }
+        preview_deposit(amount_in, data.share_math, &state)?
+    } else {
+        let assets = preview_redeem(amount_in, data.share_math, &state)?;
+        if assets > state.max_withdraw.unwrap_or(state.total_assets) {
+            return Err(eyre!("MAX_WITHDRAW"));
+        }
+        assets
+    };
+
+    if amount_out.is_zero() {
+        Err(eyre!("OUT_AMOUNT_IS_ZERO"))
+    } else {
+        Ok(amount_out)
+    }
+}
+
+/// ERC4626.previewDeposit, assets to shares rounded down.
+pub fn preview_deposit(assets: U256, share_math: ShareMath, state: &super::PoolState) -> eyre::Result<U256> {
+    match share_math {
+        ShareMath::Virtual { decimals_offset } => mul_div(
+            assets,
+            state.total_supply + U256::from(10).pow(U256::from(decimals_offset)),
+            state.total_assets + U256::from(1),
+        ),
+        ShareMath::Proportional if state.total_supply.is_zero() => Ok(assets),
+        ShareMath::Proportional => mul_div(assets, state.total_supply, state.total_assets),
+        ShareMath::Rate => mul_div(assets, RATE_SHARES, state.assets_per_share),
+    }
+}
+
+/// ERC4626.previewRedeem, shares to assets rounded down.
+pub fn preview_redeem(shares: U256, share_math: ShareMath, state: &super::PoolState) -> eyre::Result<U256> {
+    match share_math {
+        ShareMath::Virtual { decimals_offset } => mul_div(
+            shares,
+            state.total_assets + U256::from(1),
+            state.total_supply + U256::from(10).pow(U256::from(decimals_offset)),
+        ),
+        ShareMath::Proportional if state.total_supply.is_zero() => Ok(shares),
+        ShareMath::Proportional => mul_div(shares, state.total_assets, state.total_supply),
+        ShareMath::Rate => mul_div(shares, state.assets_per_share, RATE_SHARES),
+    }
+}
+
+fn mul_div(a: U256, b: U256, denominator: U256) -> eyre::Result<U256> {
+    let product = a.checked_mul(b).ok_or(eyre!("MUL_DIV_OVERFLOW"))?;
+    product.checked_div(denominator).ok_or(eyre!("ZERO_DENOMINATOR"))
+}
@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod tests;
+pub mod local;
+pub mod vault;
+
+use alloy::primitives::{Address, U256};
+use crate::erc4626pool::ShareMath;
+
+/// Ephemeral pool data.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PoolState {
+    pub total_assets: U256,
+    pub total_supply: U256,
+    pub assets_per_share: U256,
+    pub max_deposit: U256,
+    pub max_withdraw: Option<U256>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PoolData {
+    pub asset: Address,
+    pub vault: Address,
+    pub share_math: ShareMath,
+}
+
+impl From<&super::PoolData> for PoolData {
+    fn from(val: &super::PoolData) -> Self {
+        Self {
+            asset: val.asset,
+            vault: val.vault,
+            share_math: val.share_math,
+        }
+    }
+}
+
+impl From<&super::PoolState> for PoolState {
+    fn from(val: &super::PoolState) -> Self {
+        Self {
+            total_assets: val.total_assets,
+            total_supply: val.total_supply,
+            assets_per_share: val.assets_per_share,
+            max_deposit: val.max_deposit,
+            max_withdraw: val.max_withdraw,
+        }
+    }
+}
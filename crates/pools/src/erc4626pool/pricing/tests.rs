@@ -0,0 +1,99 @@
+use alloy::primitives::{Address, U256};
+use tracing::info;
+use config::Config;
+use crate::erc4626pool::pricing::{local, vault, PoolData, PoolState};
+use crate::erc4626pool::{Erc4626Pool, ShareMath};
+use crate::test_utils::{test_networks, TestNetwork};
+
+/// Parity with previewDeposit and previewRedeem for every configured vault.
+#[tokio::test]
+async fn test_calculate_amount_out() {
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .try_init();
+
+    let cfg = Config::default();
+    for TestNetwork { net, addresses, provider, block } in test_networks(&cfg, |addresses| !addresses.erc4626.vaults.is_empty()).await {
+        for (name, address) in addresses.erc4626.vaults.iter() {
+            let mut pool = Erc4626Pool::new(*address);
+            pool.sync(provider.clone(), block.into()).await.unwrap();
+
+            for (tok_in, amount_in) in [(pool.data.asset, pool.state.total_assets / U256::from(1000)), (pool.data.vault, pool.state.total_supply / U256::from(1000))] {
+                let amount_out_local = local::calc_amount_out(
+                    amount_in,
+                    tok_in,
+                    (&pool.data).into(),
+                    (&pool.state).into(),
+                ).unwrap();
+
+                let amount_out_vault = vault::calc_amount_out(
+                    pool.metadata.vault,
+                    amount_in,
+                    tok_in,
+                    provider.clone(),
+                    block.into(),
+                )
+                    .await
+                    .unwrap();
+                info!(?net, %name, share_math = ?pool.data.share_math, ?amount_out_local, ?amount_out_vault);
+
+                assert_eq!(amount_out_local, amount_out_vault);
+            }
+        }
+    }
+}
+
+fn pool_data(share_math: ShareMath) -> PoolData {
+    PoolData {
+        asset: Address::repeat_byte(0),
+        vault: Address::repeat_byte(1),
+        share_math,
+    }
+}
+
+/// 1M USDC worth 950k shares, plus some dust.
+fn pool_state(share_decimals: u8) -> PoolState {
+    PoolState {
+        total_assets: U256::from(1_000_000_000_123u64),
+        total_supply: U256::from(950_000u64) * U256::from(10).pow(U256::from(share_decimals)),
+        assets_per_share: U256::ZERO,
+        max_deposit: U256::MAX,
+        max_withdraw: None,
+    }
+}
+
+#[test]
+fn test_share_math() {
+    let amount = U256::from(10_000_000_000u64);
+
+    let data = pool_data(ShareMath::Proportional);
+    let state = pool_state(6);
+    assert_eq!(local::calc_amount_out(amount, data.asset, data, state).unwrap(), U256::from(9499999998u64));
+    assert_eq!(local::calc_amount_out(amount, data.vault, data, state).unwrap(), U256::from(10526315790u64));
+
+    // OpenZeppelin vault with 18 decimal shares over a 6 decimal asset
+    let data = pool_data(ShareMath::Virtual { decimals_offset: 12 });
+    let state = pool_state(18);
+    assert_eq!(local::calc_amount_out(amount, data.asset, data, state).unwrap(), U256::from(9499999998832000000144u128));
+
+    let data = pool_data(ShareMath::Rate);
+    let state = PoolState { assets_per_share: U256::from_str_radix("1100000000000000000000000123", 10).unwrap(), ..pool_state(6) };
+    assert_eq!(local::calc_amount_out(amount, data.asset, data, state).unwrap(), U256::from(9090909090u64));
+    assert_eq!(local::calc_amount_out(U256::from(10).pow(U256::from(18)), data.vault, data, state).unwrap(), U256::from(1100000000000000000u64));
+}
+
+#[test]
+fn test_limits() {
+    let data = pool_data(ShareMath::Proportional);
+    let state = PoolState { max_deposit: U256::from(1000), ..pool_state(6) };
+    assert!(local::calc_amount_out(U256::from(1000), data.asset, data, state).is_ok());
+    assert!(local::calc_amount_out(U256::from(1001), data.asset, data, state).is_err());
+
+    // Redemptions are capped by totalAssets without an account, by maxWithdraw with one
+    assert!(local::calc_amount_out(state.total_supply, data.vault, data, state).is_ok());
+    assert!(local::calc_amount_out(state.total_supply + U256::from(1_000_000), data.vault, data, state).is_err());
+    let state = PoolState { max_withdraw: Some(U256::from(500)), ..state };
+    assert!(local::calc_amount_out(U256::from(1000), data.vault, data, state).is_err());
+
+    assert!(local::calc_amount_out(U256::ZERO, data.asset, data, state).is_err());
+}
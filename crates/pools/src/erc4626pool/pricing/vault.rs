@@ -0,0 +1,28 @@
+use alloy::eips::BlockId;
+use alloy::network::Network;
+use alloy::primitives::{Address, U256};
+use alloy::providers::Provider;
+use alloy::transports::Transport;
+use tracing::instrument;
+use crate::erc4626pool::abi::IERC4626;
+
+/// Amount out quoted by the vault, previewDeposit from the asset and previewRedeem from the shares.
+///
+/// The previews ignore maxDeposit and maxWithdraw, as the standard requires.
+#[instrument(skip(provider), level = "debug", ret)]
+pub async fn calc_amount_out<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+    vault: Address,
+    amount_in: U256,
+    tok_in: Address,
+    provider: P,
+    block: BlockId,
+) -> eyre::Result<U256> {
+    let vault = IERC4626::new(vault, provider.clone());
+    let amount_out = if tok_in == *vault.address() {
+        vault.previewRedeem(amount_in).block(block).call().await?._0
+    } else {
+        vault.previewDeposit(amount_in).block(block).call().await?._0
+    };
+
+    Ok(amount_out)
+}
@@ -1,2 +1,16 @@
 pub mod uniswapv2pool;
 pub mod uniswapv3pool;
+pub mod algebrapool;
+pub mod uniswapv4pool;
+pub mod solidlypool;
+pub mod curvepool;
+pub mod curvecryptopool;
+pub mod balancerpool;
+pub mod wrappool;
+pub mod erc4626pool;
+pub mod liquiditybookpool;
+pub mod dodopool;
+pub mod detect;
+pub(crate) mod rpc;
+#[cfg(test)]
+mod test_utils;
@@ -0,0 +1,19 @@
+use alloy::sol;
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    #[sol(rpc)]
+    contract ILBPair {
+        event Swap(address indexed sender, address indexed to, uint24 id, bytes32 amountsIn, bytes32 amountsOut, uint24 volatilityAccumulator, bytes32 totalFees, bytes32 protocolFees);
+        function getTokenX() external view returns (address tokenX);
+        function getTokenY() external view returns (address tokenY);
+        function getBinStep() external view returns (uint16);
+        function getActiveId() external view returns (uint24 activeId);
+        function getBin(uint24 id) external view returns (uint128 binReserveX, uint128 binReserveY);
+        /// Next bin with liquidity below `id` when swapping for Y, above otherwise; 0 or type(uint24).max when there is none.
+        function getNextNonEmptyBin(bool swapForY, uint24 id) external view returns (uint24 nextId);
+        function getStaticFeeParameters() external view returns (uint16 baseFactor, uint16 filterPeriod, uint16 decayPeriod, uint16 reductionFactor, uint24 variableFeeControl, uint16 protocolShare, uint24 maxVolatilityAccumulator);
+        function getVariableFeeParameters() external view returns (uint24 volatilityAccumulator, uint24 volatilityReference, uint24 idReference, uint40 timeOfLastUpdate);
+        function getSwapOut(uint128 amountIn, bool swapForY) external view returns (uint128 amountInLeft, uint128 amountOut, uint128 fee);
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+use eyre::eyre;
+
+/// Largest uint24 bin id.
+pub const MAX_BIN_ID: u32 = (1 << 24) - 1;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Bin {
+    pub reserve_x: u128,
+    pub reserve_y: u128,
+}
+
+impl Bin {
+    /// Whether the bin has none of the token a swap would take out of it.
+    pub fn is_empty(&self, swap_for_y: bool) -> bool {
+        if swap_for_y { self.reserve_y == 0 } else { self.reserve_x == 0 }
+    }
+}
+
+/// Non-empty bins fetched around the active bin, the Liquidity Book counterpart of the V3 tick bitmap window.
+///
+/// Every non-empty bin in `lower..=upper` is known, the bounds reach 0 or [`MAX_BIN_ID`] once there are no more bins on that side.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bins {
+    bins: BTreeMap<u32, Bin>,
+    lower: u32,
+    upper: u32,
+}
+
+impl Bins {
+    pub fn new(bins: BTreeMap<u32, Bin>, lower: u32, upper: u32) -> Self {
+        Self { bins, lower, upper }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bins.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bins.is_empty()
+    }
+
+    pub fn lower(&self) -> u32 {
+        self.lower
+    }
+
+    pub fn upper(&self) -> u32 {
+        self.upper
+    }
+
+    /// Reserves of bin `id`, empty when it is not one of the non-empty bins.
+    pub fn get(&self, id: u32) -> Bin {
+        self.bins.get(&id).copied().unwrap_or_default()
+    }
+
+    /// Iterates in ascending id order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&u32, &Bin)> {
+        self.bins.iter()
+    }
+
+    /// Next non-empty bin below `id` when swapping for Y, above otherwise, as getNextNonEmptyBin.
+    ///
+    /// None once the pool has no more bins that way, an error when they lie outside the fetched window.
+    pub fn next_non_empty(&self, swap_for_y: bool, id: u32) -> eyre::Result<Option<u32>> {
+        let (next, exhausted) = if swap_for_y {
+            let next = if id > self.lower { self.bins.range(self.lower..id).next_back() } else { None };
+            (next, self.lower == 0)
+        } else {
+            let next = if id < self.upper { self.bins.range(id + 1..=self.upper).next() } else { None };
+            (next, self.upper == MAX_BIN_ID)
+        };
+
+        match next {
+            Some((id, _)) => Ok(Some(*id)),
+            None if exhausted => Ok(None),
+            None => Err(eyre!("BIN_WINDOW_EXCEEDED")),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use alloy::primitives::{U256, U512};
+use eyre::eyre;
+
+/// 1.0 in the 128.128 fixed point prices are expressed in.
+pub const SCALE_OFFSET: usize = 128;
+pub const SCALE: U256 = U256::from_limbs([0, 0, 1, 0]);
+pub const BASIS_POINT_MAX: u32 = 10_000;
+/// Fees are over 1e18 and cannot exceed 10%.
+pub const PRECISION: U256 = U256::from_limbs([1_000_000_000_000_000_000, 0, 0, 0]);
+pub const MAX_FEE: U256 = U256::from_limbs([100_000_000_000_000_000, 0, 0, 0]);
+/// Bin id of price 1, ids are uint24 centered on 2^23.
+pub const REAL_ID_SHIFT: i64 = 1 << 23;
+
+/// Price of bin `id` in 128.128, (1 + bin_step / 10000)^(id - 2^23), as PriceHelper.getPriceFromId.
+pub fn get_price_from_id(id: u32, bin_step: u16) -> eyre::Result<U256> {
+    let base = SCALE + (U256::from(bin_step) << SCALE_OFFSET) / U256::from(BASIS_POINT_MAX);
+    pow(base, id as i64 - REAL_ID_SHIFT)
+}
+
+/// x^y of a 128.128 `x`, by squaring over the 20 bits of |y|, as Uint128x128Math.pow.
+pub fn pow(x: U256, y: i64) -> eyre::Result<U256> {
+    if y == 0 {
+        return Ok(SCALE);
+    }
+    let mut invert = y < 0;
+    let abs_y = y.unsigned_abs();
+
+    let mut result = U256::ZERO;
+    if abs_y < 0x100000 {
+        result = SCALE;
+        let mut squared = x;
+        // Squaring a base above 1 overflows, its inverse is raised instead
+        if x > U256::from(u128::MAX) {
+            squared = U256::MAX / squared;
+            invert = !invert;
+        }
+        for bit in 0..20 {
+            if abs_y & (1 << bit) != 0 {
+                result = result.wrapping_mul(squared) >> 128;
+            }
+            if bit < 19 {
+                squared = squared.wrapping_mul(squared) >> 128;
+            }
+        }
+    }
+
+    if result.is_zero() {
+        return Err(eyre!("POW_UNDERFLOW"));
+    }
+    Ok(if invert { U256::MAX / result } else { result })
+}
+
+fn widen(x: U256) -> U512 {
+    let mut limbs = [0u64; 8];
+    limbs[..4].copy_from_slice(x.as_limbs());
+    U512::from_limbs(limbs)
+}
+
+fn narrow(x: U512) -> eyre::Result<U256> {
+    let limbs = x.as_limbs();
+    if limbs[4..].iter().any(|limb| *limb != 0) {
+        return Err(eyre!("MUL_DIV_OVERFLOW"));
+    }
+    Ok(U256::from_limbs(limbs[..4].try_into()?))
+}
+
+/// (x * y) >> offset with a 512 bit product, as Uint256x256Math.mulShiftRoundDown.
+pub fn mul_shift_round_down(x: U256, y: U256, offset: usize) -> eyre::Result<U256> {
+    narrow((widen(x) * widen(y)) >> offset)
+}
+
+pub fn mul_shift_round_up(x: U256, y: U256, offset: usize) -> eyre::Result<U256> {
+    let product = widen(x) * widen(y);
+    let result = narrow(product >> offset)?;
+    let remainder = product & ((U512::from(1) << offset) - U512::from(1));
+    Ok(if remainder.is_zero() { result } else { result + U256::from(1) })
+}
+
+/// (x << offset) / denominator with a 512 bit numerator, as Uint256x256Math.shiftDivRoundDown.
+pub fn shift_div_round_down(x: U256, offset: usize, denominator: U256) -> eyre::Result<U256> {
+    if denominator.is_zero() {
+        return Err(eyre!("ZERO_DENOMINATOR"));
+    }
+    narrow((widen(x) << offset) / widen(denominator))
+}
+
+pub fn shift_div_round_up(x: U256, offset: usize, denominator: U256) -> eyre::Result<U256> {
+    if denominator.is_zero() {
+        return Err(eyre!("ZERO_DENOMINATOR"));
+    }
+    let numerator = widen(x) << offset;
+    let result = narrow(numerator / widen(denominator))?;
+    Ok(if (numerator % widen(denominator)).is_zero() { result } else { result + U256::from(1) })
+}
+
+/// Fee taken out of an amount that includes it, rounded up, as FeeHelper.getFeeAmountFrom.
+pub fn get_fee_amount_from(amount_with_fees: U256, total_fee: U256) -> eyre::Result<U256> {
+    verify_fee(total_fee)?;
+    Ok((amount_with_fees * total_fee + PRECISION - U256::from(1)) / PRECISION)
+}
+
+/// Fee to add on top of an amount, rounded up, as FeeHelper.getFeeAmount.
+pub fn get_fee_amount(amount: U256, total_fee: U256) -> eyre::Result<U256> {
+    verify_fee(total_fee)?;
+    let denominator = PRECISION - total_fee;
+    Ok((amount * total_fee + denominator - U256::from(1)) / denominator)
+}
+
+fn verify_fee(total_fee: U256) -> eyre::Result<()> {
+    if total_fee > MAX_FEE {
+        return Err(eyre!("FEE_TOO_LARGE {total_fee}"));
+    }
+    Ok(())
+}
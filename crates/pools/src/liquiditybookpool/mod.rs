@@ -0,0 +1,315 @@
+mod abi;
+pub mod bins;
+pub mod math;
+pub mod params;
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+use alloy::eips::BlockId;
+use alloy::network::{BlockResponse, HeaderResponse, Network};
+use alloy::primitives::{Address, U256};
+use alloy::providers::Provider;
+use alloy::rpc::types::BlockTransactionsKind;
+use alloy::transports::Transport;
+use eyre::eyre;
+use tracing::instrument;
+use types::pool::{check_pair, Pool, PoolClass, PoolProtocol};
+use crate::liquiditybookpool::abi::ILBPair;
+use crate::liquiditybookpool::bins::{Bin, Bins, MAX_BIN_ID};
+use crate::liquiditybookpool::math::SCALE_OFFSET;
+use crate::liquiditybookpool::params::{FeeParameters, StaticFeeParameters, VariableFeeParameters};
+
+/// Non-empty bins fetched on each side of the active bin.
+pub const BIN_RANGE: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct LiquidityBookPoolMetadata {
+    pub pool: Address,
+    pub protocol: PoolProtocol,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LiquidityBookPoolData {
+    pub token_x: Address,
+    pub token_y: Address,
+    /// Price increment between bins in basis points.
+    pub bin_step: u16,
+    pub static_fee_parameters: StaticFeeParameters,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LiquidityBookPoolState {
+    pub active_id: u32,
+    pub variable_fee_parameters: VariableFeeParameters,
+    pub bins: Bins,
+    /// Timestamp of the synced block, which decays the volatility reference.
+    pub timestamp: u64,
+}
+
+/// Outcome of a swap through the bins, as getSwapOut.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SwapOut {
+    pub amount_in_left: U256,
+    pub amount_out: U256,
+    pub fee: U256,
+    /// Bin the swap ended in.
+    pub active_id: u32,
+}
+
+/// Liquidity Book V2.1 pair, constant sum bins with a fee growing with volatility.
+#[derive(Debug, Clone)]
+pub struct LiquidityBookPool {
+    pub metadata: LiquidityBookPoolMetadata,
+    pub data: LiquidityBookPoolData,
+    pub state: LiquidityBookPoolState,
+}
+
+impl Pool for LiquidityBookPool {
+    fn get_class(&self) -> PoolClass {
+        PoolClass::LiquidityBook
+    }
+
+    fn get_protocol(&self) -> PoolProtocol {
+        self.metadata.protocol
+    }
+
+    fn get_address(&self) -> Address {
+        self.metadata.pool
+    }
+
+    /// Base fee in hundredths of a bip, the variable fee comes on top.
+    fn get_fee(&self) -> u32 {
+        (self.fee_parameters().base_fee(self.data.bin_step) / U256::from(1_000_000_000_000u64)).saturating_to::<u32>()
+    }
+
+    fn get_tokens(&self) -> Vec<Address> {
+        vec![
+            self.data.token_x,
+            self.data.token_y
+        ]
+    }
+
+    fn calc_amount_out(&self, amount_in: U256, tok_in: Address, tok_out: Address) -> eyre::Result<U256> {
+        check_pair(self.data.token_x, self.data.token_y, tok_in, tok_out)?;
+        let swap = self.get_swap_out(amount_in, tok_in == self.data.token_x, self.state.timestamp)?;
+        // LBPair.swap reverts instead of leaving input behind
+        if !swap.amount_in_left.is_zero() {
+            return Err(eyre!("OUT_OF_LIQUIDITY"));
+        }
+        if swap.amount_out.is_zero() {
+            return Err(eyre!("INSUFFICIENT_AMOUNT_OUT"));
+        }
+        Ok(swap.amount_out)
+    }
+}
+
+impl LiquidityBookPool {
+    pub fn new(pool: Address) -> Self {
+        LiquidityBookPool {
+            metadata: LiquidityBookPoolMetadata {
+                pool,
+                protocol: PoolProtocol::LiquidityBook,
+            },
+            data: Default::default(),
+            state: Default::default(),
+        }
+    }
+
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn sync<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        &mut self,
+        provider: P,
+        block: BlockId
+    ) -> eyre::Result<()> {
+        self.data = LiquidityBookPool::fetch_data(
+            &self.metadata,
+            provider.clone(),
+            block,
+        ).await?;
+
+        self.state = LiquidityBookPool::fetch_state(
+            &self.metadata,
+            provider.clone(),
+            block,
+        ).await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn fetch_data<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &LiquidityBookPoolMetadata,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<LiquidityBookPoolData> {
+        let pair = ILBPair::new(metadata.pool, provider.clone());
+
+        let token_x: Address = pair.getTokenX().block(block).call().await?.tokenX;
+        let token_y: Address = pair.getTokenY().block(block).call().await?.tokenY;
+        let bin_step: u16 = pair.getBinStep().block(block).call().await?._0;
+        let fee_parameters = pair.getStaticFeeParameters().block(block).call().await?;
+
+        Ok(LiquidityBookPoolData {
+            token_x,
+            token_y,
+            bin_step,
+            static_fee_parameters: StaticFeeParameters {
+                base_factor: fee_parameters.baseFactor,
+                filter_period: fee_parameters.filterPeriod,
+                decay_period: fee_parameters.decayPeriod,
+                reduction_factor: fee_parameters.reductionFactor,
+                variable_fee_control: fee_parameters.variableFeeControl.to::<u32>(),
+                protocol_share: fee_parameters.protocolShare,
+                max_volatility_accumulator: fee_parameters.maxVolatilityAccumulator.to::<u32>(),
+            },
+        })
+    }
+
+    /// Reads the active bin and up to [`BIN_RANGE`] non-empty bins on each side of it.
+    #[instrument(skip(provider), level = "debug", ret)]
+    pub async fn fetch_state<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &LiquidityBookPoolMetadata,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<LiquidityBookPoolState> {
+        let pair = ILBPair::new(metadata.pool, provider.clone());
+
+        let active_id: u32 = pair.getActiveId().block(block).call().await?.activeId.to::<u32>();
+        let fee_parameters = pair.getVariableFeeParameters().block(block).call().await?;
+        let timestamp = provider
+            .get_block(block, BlockTransactionsKind::Hashes)
+            .await?
+            .ok_or(eyre!("BLOCK_NOT_FOUND"))?
+            .header()
+            .timestamp();
+
+        let mut bins = BTreeMap::new();
+        let active_bin = pair.getBin(active_id.try_into()?).block(block).call().await?;
+        bins.insert(active_id, Bin { reserve_x: active_bin.binReserveX, reserve_y: active_bin.binReserveY });
+
+        // Below the active bin when swapping for Y, above it otherwise
+        let mut bounds = [active_id, active_id];
+        for (swap_for_y, bound) in [true, false].into_iter().zip(bounds.iter_mut()) {
+            let mut id = active_id;
+            for _ in 0..BIN_RANGE {
+                let next_id: u32 = pair.getNextNonEmptyBin(swap_for_y, id.try_into()?).block(block).call().await?.nextId.to::<u32>();
+                if next_id == 0 || next_id == MAX_BIN_ID {
+                    id = if swap_for_y { 0 } else { MAX_BIN_ID };
+                    break;
+                }
+                let bin = pair.getBin(next_id.try_into()?).block(block).call().await?;
+                bins.insert(next_id, Bin { reserve_x: bin.binReserveX, reserve_y: bin.binReserveY });
+                id = next_id;
+            }
+            *bound = id;
+        }
+
+        Ok(LiquidityBookPoolState {
+            active_id,
+            variable_fee_parameters: VariableFeeParameters {
+                volatility_accumulator: fee_parameters.volatilityAccumulator.to::<u32>(),
+                volatility_reference: fee_parameters.volatilityReference.to::<u32>(),
+                id_reference: fee_parameters.idReference.to::<u32>(),
+                time_of_last_update: fee_parameters.timeOfLastUpdate.to::<u64>(),
+            },
+            bins: Bins::new(bins, bounds[0], bounds[1]),
+            timestamp,
+        })
+    }
+
+    pub fn fee_parameters(&self) -> FeeParameters {
+        FeeParameters {
+            fixed: self.data.static_fee_parameters,
+            variable: self.state.variable_fee_parameters,
+            active_id: self.state.active_id,
+        }
+    }
+
+    /// Walks the bins from the active one like LBPair.getSwapOut, at `timestamp` for the volatility decay.
+    ///
+    /// Input the bins cannot absorb is returned as `amount_in_left`.
+    pub fn get_swap_out(&self, amount_in: U256, swap_for_y: bool, timestamp: u64) -> eyre::Result<SwapOut> {
+        if amount_in > U256::from(u128::MAX) {
+            return Err(eyre!("AMOUNT_IN_OVERFLOW"));
+        }
+        let bin_step = self.data.bin_step;
+        let mut parameters = self.fee_parameters();
+        parameters.update_references(timestamp)?;
+
+        let mut swap = SwapOut {
+            amount_in_left: amount_in,
+            active_id: self.state.active_id,
+            ..Default::default()
+        };
+        loop {
+            let bin = self.state.bins.get(swap.active_id);
+            if !bin.is_empty(swap_for_y) {
+                parameters.update_volatility_accumulator(swap.active_id);
+                let (amount_in_with_fees, amount_out, fee) = LiquidityBookPool::get_amounts(
+                    bin,
+                    &parameters,
+                    bin_step,
+                    swap_for_y,
+                    swap.active_id,
+                    swap.amount_in_left,
+                )?;
+                if !amount_in_with_fees.is_zero() {
+                    swap.amount_in_left -= amount_in_with_fees;
+                    swap.amount_out += amount_out;
+                    swap.fee += fee;
+                }
+            }
+
+            if swap.amount_in_left.is_zero() {
+                break;
+            }
+            match self.state.bins.next_non_empty(swap_for_y, swap.active_id)? {
+                Some(next_id) => swap.active_id = next_id,
+                None => break,
+            }
+        }
+
+        Ok(swap)
+    }
+
+    /// Amount in with fees, amount out and fee of a swap within bin `id`, as BinHelper.getAmounts.
+    pub fn get_amounts(
+        bin: Bin,
+        parameters: &FeeParameters,
+        bin_step: u16,
+        swap_for_y: bool,
+        id: u32,
+        amount_in_left: U256,
+    ) -> eyre::Result<(U256, U256, U256)> {
+        let price = math::get_price_from_id(id, bin_step)?;
+        let bin_reserve_out = U256::from(if swap_for_y { bin.reserve_y } else { bin.reserve_x });
+
+        // Input that would empty the bin
+        let mut max_amount_in = if swap_for_y {
+            math::shift_div_round_up(bin_reserve_out, SCALE_OFFSET, price)?
+        } else {
+            math::mul_shift_round_up(bin_reserve_out, price, SCALE_OFFSET)?
+        };
+        if max_amount_in > U256::from(u128::MAX) {
+            return Err(eyre!("SAFE_128_OVERFLOW"));
+        }
+        let total_fee = parameters.total_fee(bin_step);
+        let max_fee = math::get_fee_amount(max_amount_in, total_fee)?;
+        max_amount_in += max_fee;
+
+        if amount_in_left >= max_amount_in {
+            return Ok((max_amount_in, bin_reserve_out, max_fee));
+        }
+
+        let fee = math::get_fee_amount_from(amount_in_left, total_fee)?;
+        let amount_in = amount_in_left - fee;
+        let amount_out = if swap_for_y {
+            math::mul_shift_round_down(amount_in, price, SCALE_OFFSET)?
+        } else {
+            math::shift_div_round_down(amount_in, SCALE_OFFSET, price)?
+        };
+
+        Ok((amount_in_left, amount_out.min(bin_reserve_out), fee))
+    }
+}
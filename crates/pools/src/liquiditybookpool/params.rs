@@ -0,0 +1,77 @@
+use alloy::primitives::U256;
+use eyre::eyre;
+use crate::liquiditybookpool::math::BASIS_POINT_MAX;
+
+/// Fee configuration set by the factory, as getStaticFeeParameters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StaticFeeParameters {
+    pub base_factor: u16,
+    pub filter_period: u16,
+    pub decay_period: u16,
+    pub reduction_factor: u16,
+    pub variable_fee_control: u32,
+    pub protocol_share: u16,
+    pub max_volatility_accumulator: u32,
+}
+
+/// Volatility tracking updated by every swap, as getVariableFeeParameters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VariableFeeParameters {
+    pub volatility_accumulator: u32,
+    pub volatility_reference: u32,
+    pub id_reference: u32,
+    pub time_of_last_update: u64,
+}
+
+/// Both parameter sets and the active id, what PairParameterHelper packs in one word.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeParameters {
+    pub fixed: StaticFeeParameters,
+    pub variable: VariableFeeParameters,
+    pub active_id: u32,
+}
+
+impl FeeParameters {
+    /// Decays the volatility reference once the filter period is over, as updateReferences at the start of a swap.
+    pub fn update_references(&mut self, timestamp: u64) -> eyre::Result<()> {
+        let dt = timestamp
+            .checked_sub(self.variable.time_of_last_update)
+            .ok_or(eyre!("TIMESTAMP_BEFORE_LAST_UPDATE"))?;
+
+        if dt >= self.fixed.filter_period as u64 {
+            self.variable.id_reference = self.active_id;
+            self.variable.volatility_reference = if dt < self.fixed.decay_period as u64 {
+                (self.variable.volatility_accumulator as u64 * self.fixed.reduction_factor as u64 / BASIS_POINT_MAX as u64) as u32
+            } else {
+                0
+            };
+        }
+        self.variable.time_of_last_update = timestamp;
+        Ok(())
+    }
+
+    /// Volatility reference plus the bins crossed since the reference id, as updateVolatilityAccumulator.
+    pub fn update_volatility_accumulator(&mut self, active_id: u32) {
+        let delta_id = active_id.abs_diff(self.variable.id_reference) as u64;
+        let volatility_accumulator = self.variable.volatility_reference as u64 + delta_id * BASIS_POINT_MAX as u64;
+        self.variable.volatility_accumulator = volatility_accumulator.min(self.fixed.max_volatility_accumulator as u64) as u32;
+    }
+
+    /// base_factor * bin_step * 1e10, over 1e18.
+    pub fn base_fee(&self, bin_step: u16) -> U256 {
+        U256::from(self.fixed.base_factor) * U256::from(bin_step) * U256::from(10_000_000_000u64)
+    }
+
+    /// (volatility_accumulator * bin_step)^2 * variable_fee_control / 100 rounded up, over 1e18.
+    pub fn variable_fee(&self, bin_step: u16) -> U256 {
+        if self.fixed.variable_fee_control == 0 {
+            return U256::ZERO;
+        }
+        let prod = U256::from(self.variable.volatility_accumulator) * U256::from(bin_step);
+        (prod * prod * U256::from(self.fixed.variable_fee_control) + U256::from(99)) / U256::from(100)
+    }
+
+    pub fn total_fee(&self, bin_step: u16) -> U256 {
+        self.base_fee(bin_step) + self.variable_fee(bin_step)
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+use alloy::primitives::{Address, U256};
+use tracing::info;
+use config::Config;
+use types::pool::Pool;
+use crate::liquiditybookpool::abi::ILBPair;
+use crate::liquiditybookpool::bins::{Bin, Bins, MAX_BIN_ID};
+use crate::liquiditybookpool::math::{get_price_from_id, REAL_ID_SHIFT, SCALE};
+use crate::liquiditybookpool::params::{StaticFeeParameters, VariableFeeParameters};
+use crate::liquiditybookpool::LiquidityBookPool;
+use crate::test_utils::{test_networks, units, TestNetwork};
+
+/// Parity with getSwapOut for every configured pair, both directions.
+#[tokio::test]
+async fn test_get_swap_out() {
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .try_init();
+
+    let cfg = Config::default();
+    let mut checked = 0;
+    for TestNetwork { net, addresses, provider, block } in test_networks(&cfg, |addresses| !addresses.liquidity_book.pools.is_empty()).await {
+        for (name, address) in addresses.liquidity_book.pools.iter() {
+            let mut pool = LiquidityBookPool::new(*address);
+            pool.sync(provider.clone(), block.into()).await.unwrap();
+            let pair = ILBPair::new(*address, provider.clone());
+
+            for swap_for_y in [true, false] {
+                // As much as the active bin holds of the token in, enough to cross into the next bins
+                let active_bin = pool.state.bins.get(pool.state.active_id);
+                let amount_in = U256::from(if swap_for_y { active_bin.reserve_x.max(1) } else { active_bin.reserve_y.max(1) });
+                let swap = pool.get_swap_out(amount_in, swap_for_y, pool.state.timestamp).unwrap();
+
+                let quote = pair.getSwapOut(amount_in.to::<u128>(), swap_for_y).block(block.into()).call().await.unwrap();
+                info!(?net, %name, swap_for_y, ?swap, ?quote.amountOut);
+
+                assert_eq!(swap.amount_in_left, U256::from(quote.amountInLeft));
+                assert_eq!(swap.amount_out, U256::from(quote.amountOut));
+                assert_eq!(swap.fee, U256::from(quote.fee));
+                checked += 1;
+            }
+        }
+    }
+    assert!(checked > 0, "no Liquidity Book pair configured");
+}
+
+/// Reference values from a line by line port of PriceHelper and Uint128x128Math.
+#[test]
+fn test_price_from_id() {
+    let shift = REAL_ID_SHIFT as u32;
+    assert_eq!(get_price_from_id(shift, 25).unwrap(), SCALE);
+    assert_eq!(get_price_from_id(shift + 1, 25).unwrap(), U256::from_str_radix("341133072838240809622033043950347631984", 10).unwrap());
+    assert_eq!(get_price_from_id(shift - 100, 20).unwrap(), U256::from_str_radix("278655289831604920798715975769955788813", 10).unwrap());
+    assert_eq!(get_price_from_id(shift + 5000, 10).unwrap(), U256::from_str_radix("50376366695112022875509444522348170436572", 10).unwrap());
+    assert!(get_price_from_id(MAX_BIN_ID, 100).is_err());
+}
+
+/// Y below the active bin, X above, both sides fully known.
+fn pool(lower: u32, upper: u32) -> LiquidityBookPool {
+    let shift = REAL_ID_SHIFT as u32;
+    let mut pool = LiquidityBookPool::new(Address::ZERO);
+    pool.data.token_x = Address::repeat_byte(0);
+    pool.data.token_y = Address::repeat_byte(1);
+    pool.data.bin_step = 25;
+    pool.data.static_fee_parameters = StaticFeeParameters {
+        base_factor: 5000,
+        filter_period: 30,
+        decay_period: 600,
+        reduction_factor: 5000,
+        variable_fee_control: 40000,
+        protocol_share: 1000,
+        max_volatility_accumulator: 350000,
+    };
+    pool.state.active_id = shift;
+    pool.state.variable_fee_parameters = VariableFeeParameters {
+        volatility_accumulator: 20000,
+        volatility_reference: 10000,
+        id_reference: shift - 2,
+        time_of_last_update: 1000,
+    };
+    let bins: BTreeMap<u32, Bin> = [
+        (shift - 2, Bin { reserve_x: 0, reserve_y: 3_000_000_000 }),
+        (shift - 1, Bin { reserve_x: 0, reserve_y: 2_000_000_000 }),
+        (shift, Bin { reserve_x: 500_000_000, reserve_y: 1_000_000_000 }),
+        (shift + 1, Bin { reserve_x: 1_500_000_000, reserve_y: 0 }),
+        (shift + 3, Bin { reserve_x: 2_500_000_000, reserve_y: 0 }),
+    ].into_iter().collect();
+    pool.state.bins = Bins::new(bins, lower, upper);
+    pool.state.timestamp = 1010;
+    pool
+}
+
+#[test]
+fn test_fees() {
+    let pool = pool(0, MAX_BIN_ID);
+    // 0.125% base fee and 0.01% variable fee of a volatility accumulator of 20000
+    assert_eq!(pool.fee_parameters().total_fee(25), U256::from(1_350_000_000_000_000u64));
+    assert_eq!(pool.get_fee(), 1250);
+}
+
+/// Reference values from a line by line port of LBPair.getSwapOut.
+#[test]
+fn test_get_swap_out_bins() {
+    let shift = REAL_ID_SHIFT as u32;
+    let pool = pool(0, MAX_BIN_ID);
+
+    // Within the filter period, after it and after the decay period
+    for (timestamp, out_for_y, fee_for_y, out_for_x, fee_for_x) in [
+        (1010, 2492767895u64, 3500185u64, 2983496434u64, 5358886u64),
+        (1100, 2492967675, 3299905, 2984536970, 4310527),
+        (2000, 2493104768, 3162470, 2984834036, 4011227),
+    ] {
+        let swap = pool.get_swap_out(units(2500, 6), true, timestamp).unwrap();
+        assert_eq!(swap.amount_in_left, U256::ZERO);
+        assert_eq!(swap.amount_out, U256::from(out_for_y));
+        assert_eq!(swap.fee, U256::from(fee_for_y));
+        assert_eq!(swap.active_id, shift - 1);
+
+        let swap = pool.get_swap_out(units(3000, 6), false, timestamp).unwrap();
+        assert_eq!(swap.amount_out, U256::from(out_for_x));
+        assert_eq!(swap.fee, U256::from(fee_for_x));
+        assert_eq!(swap.active_id, shift + 3);
+    }
+
+    // Within the active bin
+    let swap = pool.get_swap_out(units(100, 6), true, 1010).unwrap();
+    assert_eq!(swap.amount_out, U256::from(99852500u64));
+    assert_eq!(swap.fee, U256::from(147500u64));
+    assert_eq!(pool.calc_amount_out(units(100, 6), pool.data.token_x, pool.data.token_y).unwrap(), U256::from(99852500u64));
+
+    // More than all the Y in the pool
+    let swap = pool.get_swap_out(units(10_000_000, 6), true, 1010).unwrap();
+    assert_eq!(swap.amount_in_left, U256::from(9993971944602u64));
+    assert_eq!(swap.amount_out, units(6000, 6));
+    assert_eq!(swap.fee, U256::from(8036646u64));
+    assert!(pool.calc_amount_out(units(10_000_000, 6), pool.data.token_x, pool.data.token_y).is_err());
+}
+
+#[test]
+fn test_bin_window() {
+    let shift = REAL_ID_SHIFT as u32;
+    // Bins below shift - 1 were not fetched
+    let pool = pool(shift - 1, MAX_BIN_ID);
+    assert!(pool.get_swap_out(units(1000, 6), true, 1010).is_ok());
+    assert!(pool.get_swap_out(units(4000, 6), true, 1010).is_err());
+    assert!(pool.get_swap_out(units(3000, 6), false, 1010).is_ok());
+
+    assert_eq!(pool.state.bins.next_non_empty(false, shift + 1).unwrap(), Some(shift + 3));
+    assert_eq!(pool.state.bins.next_non_empty(false, shift + 3).unwrap(), None);
+    assert!(pool.state.bins.next_non_empty(true, shift - 1).is_err());
+}
@@ -0,0 +1,69 @@
+use alloy::contract::Error;
+use alloy::eips::BlockId;
+use alloy::network::Network;
+use alloy::primitives::{Address, Bytes};
+use alloy::providers::Provider;
+use alloy::rpc::types::{Filter, Log, TransactionRequest};
+use alloy::sol_types::SolCall;
+use alloy::transports::{RpcError, Transport};
+use tracing::debug;
+
+/// Block range of a single eth_getLogs request, most providers cap it.
+pub(crate) const LOGS_BLOCK_RANGE: u64 = 10_000;
+
+/// Calls per JSON-RPC batch, public endpoints reject oversized ones.
+const CALL_BATCH_SIZE: usize = 100;
+
+/// Whether a contract call failed on chain, by reverting or returning no decodable data, rather than on the way to the node.
+pub(crate) fn is_revert(error: &Error) -> bool {
+    match error {
+        Error::TransportError(RpcError::ErrorResp(payload)) => {
+            payload.code == 3 || payload.message.contains("revert")
+        }
+        Error::ZeroData(..) | Error::AbiError(_) => true,
+        _ => false,
+    }
+}
+
+/// Logs matching `filter` in [from_block, to_block], requested [`LOGS_BLOCK_RANGE`] blocks at a time.
+pub(crate) async fn get_logs_chunked<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+    provider: &P,
+    filter: &Filter,
+    from_block: u64,
+    to_block: u64,
+) -> eyre::Result<Vec<Log>> {
+    let mut logs = Vec::new();
+    let mut start = from_block;
+    while start <= to_block {
+        let end = (start + LOGS_BLOCK_RANGE - 1).min(to_block);
+        let chunk = provider.get_logs(&filter.clone().from_block(start).to_block(end)).await?;
+        debug!(start, end, logs = chunk.len(), "logs");
+        logs.extend(chunk);
+        start = end + 1;
+    }
+    Ok(logs)
+}
+
+/// Calls `to` with each of `calls` through `eth_call` JSON-RPC batches, in order.
+pub(crate) async fn call_batch<C: SolCall, T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+    provider: &P,
+    to: Address,
+    calls: &[C],
+    block: BlockId,
+) -> eyre::Result<Vec<C::Return>> {
+    let mut returns = Vec::with_capacity(calls.len());
+    for chunk in calls.chunks(CALL_BATCH_SIZE) {
+        let client = provider.client();
+        let mut batch = client.new_batch();
+        let mut waiters = Vec::with_capacity(chunk.len());
+        for call in chunk {
+            let tx = TransactionRequest::default().to(to).input(Bytes::from(call.abi_encode()).into());
+            waiters.push(batch.add_call::<_, Bytes>("eth_call", &(tx, block))?);
+        }
+        batch.send().await?;
+        for waiter in waiters {
+            returns.push(C::abi_decode_returns(&waiter.await?, true)?);
+        }
+    }
+    Ok(returns)
+}
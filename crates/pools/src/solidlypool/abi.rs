@@ -0,0 +1,22 @@
+use alloy::sol;
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    #[sol(rpc)]
+    contract ISolidlyPool {
+        event Sync(uint256 reserve0, uint256 reserve1);
+        function metadata() external view returns (uint256 dec0, uint256 dec1, uint256 r0, uint256 r1, bool st, address t0, address t1);
+        function getReserves() external view returns (uint256 _reserve0, uint256 _reserve1, uint256 _blockTimestampLast);
+        function getAmountOut(uint256 amountIn, address tokenIn) external view returns (uint256);
+        function swap(uint256 amount0Out, uint256 amount1Out, address to, bytes calldata data);
+        function factory() external view returns (address);
+    }
+}
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    #[sol(rpc)]
+    contract ISolidlyPoolFactory {
+        function getFee(address pool, bool _stable) external view returns (uint256);
+    }
+}
@@ -0,0 +1,169 @@
+mod abi;
+pub mod pricing;
+
+use alloy::eips::BlockId;
+use alloy::{
+    network::Network,
+    primitives::{Address, U256},
+    providers::Provider,
+    transports::Transport,
+};
+use tracing::instrument;
+use types::pool::{check_pair, Pool, PoolClass, PoolProtocol};
+use crate::solidlypool::abi::{ISolidlyPool, ISolidlyPoolFactory};
+
+#[derive(Debug, Clone)]
+pub struct PoolMetadata {
+    pub pool: Address,
+    pub protocol: PoolProtocol,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PoolData {
+    pub factory: Address,
+    pub tok0: Address,
+    pub tok1: Address,
+    /// 10^decimals of each token, as returned by metadata().
+    pub decimals0: U256,
+    pub decimals1: U256,
+    pub stable: bool,
+    /// Swap fee in basis points, set per pool or per curve by the factory.
+    pub fee: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PoolState {
+    pub reserve0: U256,
+    pub reserve1: U256,
+}
+
+/// Solidly style pair, Velodrome V2 and Aerodrome pools, either volatile or stable.
+///
+/// The swap interface is the Uniswap V2 pair one, only the curve and the fee differ.
+#[derive(Debug, Clone)]
+pub struct SolidlyPool {
+    pub metadata: PoolMetadata,
+    pub data: PoolData,
+    pub state: PoolState,
+}
+
+impl Pool for SolidlyPool {
+    fn get_class(&self) -> PoolClass {
+        PoolClass::UniswapV2
+    }
+
+    fn get_protocol(&self) -> PoolProtocol {
+        self.metadata.protocol
+    }
+
+    fn get_address(&self) -> Address {
+        self.metadata.pool
+    }
+
+    /// Fee in hundredths of a bip like the other pools, the factory sets it in basis points.
+    fn get_fee(&self) -> u32 {
+        self.data.fee * 100
+    }
+
+    fn get_tokens(&self) -> Vec<Address> {
+        vec![
+            self.data.tok0,
+            self.data.tok1
+        ]
+    }
+
+    fn calc_amount_out(&self, amount_in: U256, tok_in: Address, tok_out: Address) -> eyre::Result<U256> {
+        check_pair(self.data.tok0, self.data.tok1, tok_in, tok_out)?;
+        pricing::local::calc_amount_out(
+            amount_in,
+            tok_in,
+            (&self.data).into(),
+            (&self.state).into()
+        )
+    }
+}
+
+impl SolidlyPool {
+    pub fn new(pool: Address) -> Self {
+        SolidlyPool {
+            metadata: PoolMetadata {
+                pool,
+                protocol: PoolProtocol::Solidly,
+            },
+            data: Default::default(),
+            state: Default::default(),
+        }
+    }
+
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn sync<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        &mut self,
+        provider: P,
+        block: BlockId
+    ) -> eyre::Result<()> {
+        self.data = SolidlyPool::fetch_data(
+            &self.metadata,
+            provider.clone(),
+            block,
+        ).await?;
+
+        self.state = SolidlyPool::fetch_state(
+            &self.metadata,
+            provider.clone(),
+            block,
+        ).await?;
+
+        Ok(())
+    }
+
+    /// Reads the pool metadata and its fee, which the factory may change at any time.
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn fetch_data<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &PoolMetadata,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<PoolData> {
+        let pool = ISolidlyPool::new(metadata.pool, provider.clone());
+
+        let pool_metadata = pool.metadata().block(block).call().await?;
+        let factory: Address = pool.factory().block(block).call().await?._0;
+        let fee = SolidlyPool::fetch_fee(metadata.pool, factory, pool_metadata.st, provider.clone(), block).await?;
+
+        Ok(PoolData {
+            factory,
+            tok0: pool_metadata.t0,
+            tok1: pool_metadata.t1,
+            decimals0: pool_metadata.dec0,
+            decimals1: pool_metadata.dec1,
+            stable: pool_metadata.st,
+            fee,
+        })
+    }
+
+    #[instrument(skip(provider), level = "debug", ret)]
+    pub async fn fetch_fee<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        pool: Address,
+        factory: Address,
+        stable: bool,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<u32> {
+        let factory = ISolidlyPoolFactory::new(factory, provider.clone());
+        let fee: u32 = factory.getFee(pool, stable).block(block).call().await?._0.try_into()?;
+        Ok(fee)
+    }
+
+    #[instrument(skip(provider), level = "debug", ret)]
+    pub async fn fetch_state<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &PoolMetadata,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<PoolState> {
+        let pool = ISolidlyPool::new(metadata.pool, provider.clone());
+        let reserves = pool.getReserves().block(block).call().await?;
+        Ok(PoolState {
+            reserve0: reserves._reserve0,
+            reserve1: reserves._reserve1,
+        })
+    }
+}
@@ -0,0 +1,111 @@
+use alloy::primitives::{Address, U256};
+use eyre::eyre;
+use tracing::instrument;
+
+const E18: U256 = U256::from_limbs([1_000_000_000_000_000_000, 0, 0, 0]);
+/// Fees are in basis points.
+pub const FEE_DENOMINATOR: U256 = U256::from_limbs([10_000, 0, 0, 0]);
+/// Newton iterations of Pool._get_y before it reverts.
+const GET_Y_ITERATIONS: usize = 255;
+
+/// Pool.getAmountOut of Velodrome V2 style pools, volatile `x * y = k` or stable `x^3 * y + x * y^3 = k`.
+#[instrument(level = "debug", ret)]
+pub fn calc_amount_out(
+    amount_in: U256,
+    tok_in: Address,
+    data: super::PoolData,
+    state: super::PoolState,
+) -> eyre::Result<U256> {
+    let fee_amount = amount_in.checked_mul(data.fee).ok_or(eyre!("AMOUNT_IN_WITH_FEE_OVERFLOW"))? / FEE_DENOMINATOR;
+    let amount_in = amount_in.checked_sub(fee_amount).ok_or(eyre!("FEE_EXCEEDS_AMOUNT_IN"))?;
+    let zero_for_one = tok_in == data.tok0;
+    let reserve_out = if zero_for_one { state.reserve1 } else { state.reserve0 };
+
+    let out_amount = if data.stable {
+        stable_amount_out(amount_in, zero_for_one, &data, &state)?
+    } else {
+        let reserve_in = if zero_for_one { state.reserve0 } else { state.reserve1 };
+        let numerator = amount_in.checked_mul(reserve_out).ok_or(eyre!("NUMERATOR_OVERFLOW"))?;
+        let denominator = reserve_in.checked_add(amount_in).ok_or(eyre!("DENOMINATOR_OVERFLOW"))?;
+        numerator.checked_div(denominator).ok_or(eyre!("CANNOT_CALCULATE_ZERO_RESERVE"))?
+    };
+
+    // Pool.swap requires the amount out to be strictly lower than the reserve
+    if out_amount >= reserve_out {
+        Err(eyre!("RESERVE_EXCEEDED"))
+    } else if out_amount.is_zero() {
+        Err(eyre!("OUT_AMOUNT_IS_ZERO"))
+    } else {
+        Ok(out_amount)
+    }
+}
+
+/// Solves the stable invariant on reserves normalized to 18 decimals, then scales the output back.
+fn stable_amount_out(amount_in: U256, zero_for_one: bool, data: &super::PoolData, state: &super::PoolState) -> eyre::Result<U256> {
+    if data.decimals0.is_zero() || data.decimals1.is_zero() {
+        return Err(eyre!("ZERO_DECIMALS"));
+    }
+    let xy = k(state.reserve0, state.reserve1, data)?;
+    let reserve0 = state.reserve0 * E18 / data.decimals0;
+    let reserve1 = state.reserve1 * E18 / data.decimals1;
+    let (reserve_in, reserve_out, decimals_in, decimals_out) = if zero_for_one {
+        (reserve0, reserve1, data.decimals0, data.decimals1)
+    } else {
+        (reserve1, reserve0, data.decimals1, data.decimals0)
+    };
+    let amount_in = amount_in.checked_mul(E18).ok_or(eyre!("AMOUNT_IN_OVERFLOW"))? / decimals_in;
+
+    let y = get_y(amount_in + reserve_in, xy, reserve_out, data)?;
+    let amount_out = reserve_out.checked_sub(y).ok_or(eyre!("Y_EXCEEDS_RESERVE"))?;
+    Ok(amount_out * decimals_out / E18)
+}
+
+/// Pool._k for stable pools, the invariant on normalized reserves.
+fn k(x: U256, y: U256, data: &super::PoolData) -> eyre::Result<U256> {
+    let x = x.checked_mul(E18).ok_or(eyre!("K_OVERFLOW"))? / data.decimals0;
+    let y = y.checked_mul(E18).ok_or(eyre!("K_OVERFLOW"))? / data.decimals1;
+    Ok(f(x, y))
+}
+
+fn f(x0: U256, y: U256) -> U256 {
+    let a = x0 * y / E18;
+    let b = x0 * x0 / E18 + y * y / E18;
+    a * b / E18
+}
+
+/// Derivative of [`f`] in y.
+fn d(x0: U256, y: U256) -> U256 {
+    U256::from(3) * x0 * (y * y / E18) / E18 + x0 * x0 / E18 * x0 / E18
+}
+
+/// Pool._get_y, Newton's method for the y such that f(x0, y) >= xy, starting from the current y.
+fn get_y(x0: U256, xy: U256, mut y: U256, data: &super::PoolData) -> eyre::Result<U256> {
+    for _ in 0..GET_Y_ITERATIONS {
+        let k_y = f(x0, y);
+        let d_y = d(x0, y);
+        if d_y.is_zero() {
+            return Err(eyre!("!y"));
+        }
+        if k_y < xy {
+            let mut dy = (xy - k_y) * E18 / d_y;
+            if dy.is_zero() {
+                // The pool normalizes x0 and y + 1 a second time here, kept for parity
+                if k(x0, y + U256::from(1), data)? > xy {
+                    return Ok(y + U256::from(1));
+                }
+                dy = U256::from(1);
+            }
+            y += dy;
+        } else {
+            let mut dy = (k_y - xy) * E18 / d_y;
+            if dy.is_zero() {
+                if k_y == xy || f(x0, y - U256::from(1)) < xy {
+                    return Ok(y);
+                }
+                dy = U256::from(1);
+            }
+            y = y.checked_sub(dy).ok_or(eyre!("!y"))?;
+        }
+    }
+    Err(eyre!("!y"))
+}
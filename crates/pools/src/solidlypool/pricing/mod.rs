@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod tests;
+pub mod local;
+pub mod pair;
+
+use alloy::primitives::{U256, Address};
+
+/// Ephemeral pool data.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PoolState {
+    pub reserve0: U256,
+    pub reserve1: U256,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PoolData {
+    pub tok0: Address,
+    pub tok1: Address,
+    pub decimals0: U256,
+    pub decimals1: U256,
+    pub stable: bool,
+    pub fee: U256,
+}
+
+impl From<&super::PoolData> for PoolData {
+    fn from(val: &super::PoolData) -> Self {
+        Self {
+            tok0: val.tok0,
+            tok1: val.tok1,
+            decimals0: val.decimals0,
+            decimals1: val.decimals1,
+            stable: val.stable,
+            fee: U256::from(val.fee),
+        }
+    }
+}
+
+impl From<&super::PoolState> for PoolState {
+    fn from(val: &super::PoolState) -> Self {
+        Self {
+            reserve0: val.reserve0,
+            reserve1: val.reserve1,
+        }
+    }
+}
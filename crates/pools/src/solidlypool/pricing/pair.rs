@@ -0,0 +1,27 @@
+use alloy::eips::BlockId;
+use alloy::network::Network;
+use alloy::primitives::{Address, U256};
+use alloy::providers::Provider;
+use alloy::transports::Transport;
+use tracing::instrument;
+use crate::solidlypool::abi::ISolidlyPool;
+
+/// Amount out quoted by the pool itself, which returns 0 rather than reverting on a failed quote.
+#[instrument(skip(provider), level = "debug", ret)]
+pub async fn calc_amount_out<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+    pool: Address,
+    amount_in: U256,
+    tok_in: Address,
+    provider: P,
+    block: BlockId,
+) -> eyre::Result<U256> {
+    let pool = ISolidlyPool::new(pool, provider.clone());
+    let amount_out = pool
+        .getAmountOut(amount_in, tok_in)
+        .block(block)
+        .call()
+        .await?
+        ._0;
+
+    Ok(amount_out)
+}
@@ -0,0 +1,102 @@
+use alloy::primitives::{Address, U256};
+use tracing::info;
+use config::Config;
+use crate::solidlypool::pricing::{local, pair, PoolData, PoolState};
+use crate::solidlypool::SolidlyPool;
+use crate::test_utils::{test_networks, TestNetwork};
+
+/// Parity with getAmountOut for every configured pool, both directions.
+#[tokio::test]
+async fn test_calculate_amount_out() {
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .try_init();
+
+    let cfg = Config::default();
+    let mut checked = 0;
+    for TestNetwork { net, addresses, provider, block } in test_networks(&cfg, |addresses| !addresses.solidly.pools.is_empty()).await {
+        for (name, pool) in addresses.solidly.pools.iter() {
+            let mut pool = SolidlyPool::new(*pool);
+            pool.sync(provider.clone(), block.into()).await.unwrap();
+
+            for (tok_in, decimals_in) in [(pool.data.tok0, pool.data.decimals0), (pool.data.tok1, pool.data.decimals1)] {
+                let amount_in = decimals_in * U256::from(1000);
+                let amount_out_local = local::calc_amount_out(
+                    amount_in,
+                    tok_in,
+                    (&pool.data).into(),
+                    (&pool.state).into(),
+                ).unwrap();
+
+                let amount_out_pair = pair::calc_amount_out(
+                    pool.metadata.pool,
+                    amount_in,
+                    tok_in,
+                    provider.clone(),
+                    block.into(),
+                )
+                    .await
+                    .unwrap();
+                info!(?net, %name, stable = pool.data.stable, ?amount_out_local, ?amount_out_pair);
+
+                assert_eq!(amount_out_local, amount_out_pair);
+                checked += 1;
+            }
+        }
+    }
+    assert!(checked > 0, "no Solidly pool configured");
+}
+
+fn pool_data(stable: bool, decimals0: u8, decimals1: u8, fee: u32) -> PoolData {
+    PoolData {
+        tok0: Address::repeat_byte(0),
+        tok1: Address::repeat_byte(1),
+        decimals0: U256::from(10).pow(U256::from(decimals0)),
+        decimals1: U256::from(10).pow(U256::from(decimals1)),
+        stable,
+        fee: U256::from(fee),
+    }
+}
+
+/// Reference amounts from a line by line port of Velodrome V2 Pool.getAmountOut.
+#[test]
+fn test_stable_amount_out() {
+    let data = pool_data(true, 6, 18, 5);
+    let state = PoolState {
+        reserve0: U256::from(5_000_000u64) * U256::from(10).pow(U256::from(6)),
+        reserve1: U256::from(4_800_000u64) * U256::from(10).pow(U256::from(18)),
+    };
+
+    let amount_out = local::calc_amount_out(U256::from(10_000u64) * data.decimals0, data.tok0, data, state).unwrap();
+    assert_eq!(amount_out, U256::from(9994802876115632270050u128));
+
+    let amount_out = local::calc_amount_out(U256::from(10_000u64) * data.decimals1, data.tok1, data, state).unwrap();
+    assert_eq!(amount_out, U256::from(9995146094u64));
+
+    let amount_out = local::calc_amount_out(U256::from(2_000_000u64) * data.decimals0, data.tok0, data, state).unwrap();
+    assert_eq!(amount_out, U256::from_str_radix("1923188583353476885941567", 10).unwrap());
+}
+
+#[test]
+fn test_volatile_amount_out() {
+    let data = pool_data(false, 18, 6, 30);
+    let state = PoolState {
+        reserve0: U256::from(1000u64) * U256::from(10).pow(U256::from(18)),
+        reserve1: U256::from(3_000_000u64) * U256::from(10).pow(U256::from(6)),
+    };
+
+    let amount_out = local::calc_amount_out(data.decimals0, data.tok0, data, state).unwrap();
+    assert_eq!(amount_out, U256::from(2988020943u64));
+
+    assert!(local::calc_amount_out(U256::ZERO, data.tok0, data, state).is_err());
+}
+
+#[test]
+fn test_fee_units() {
+    use types::pool::Pool;
+
+    // Aerodrome volatile pools charge 30 basis points
+    let mut pool = SolidlyPool::new(Address::ZERO);
+    pool.data.fee = 30;
+    assert_eq!(pool.get_fee(), 3000);
+}
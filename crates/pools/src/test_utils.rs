@@ -0,0 +1,41 @@
+use std::sync::Arc;
+use alloy::primitives::U256;
+use alloy::providers::{Provider, ProviderBuilder, ReqwestProvider};
+use config::{Config, Network, NetworkAddresses};
+
+/// A configured network connected at its latest block.
+pub(crate) struct TestNetwork {
+    pub net: Network,
+    pub addresses: NetworkAddresses,
+    pub provider: Arc<ReqwestProvider>,
+    pub block: u64,
+}
+
+/// Connects only to the configured networks `has_pools` selects, so an unrelated endpoint being down fails no test.
+pub(crate) async fn test_networks(cfg: &Config, has_pools: impl Fn(&NetworkAddresses) -> bool) -> Vec<TestNetwork> {
+    let mut networks = Vec::new();
+    for (net, provider) in cfg.providers.iter() {
+        let addresses = cfg.addresses.get(net).unwrap().clone();
+        if !has_pools(&addresses) {
+            continue;
+        }
+        let provider = Arc::new(ProviderBuilder::new().on_http(provider.api.parse().unwrap()));
+
+        let block = provider
+            .get_block_number()
+            .await
+            .unwrap();
+
+        networks.push(TestNetwork { net: net.clone(), addresses, provider, block });
+    }
+    networks
+}
+
+/// `amount` whole tokens of `decimals` decimals.
+pub(crate) fn units(amount: u64, decimals: u8) -> U256 {
+    U256::from(amount) * U256::from(10).pow(U256::from(decimals))
+}
+
+pub(crate) fn ether(amount: u64) -> U256 {
+    units(amount, 18)
+}
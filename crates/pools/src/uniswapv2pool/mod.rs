@@ -10,7 +10,7 @@ use alloy::{
 };
 use lazy_static::lazy_static;
 use tracing::{instrument};
-use types::pool::{Pool, PoolClass, PoolProtocol};
+use types::pool::{check_pair, Pool, PoolClass, PoolProtocol};
 use crate::uniswapv2pool::abi::IUniswapV2Pair;
 
 #[derive(Debug, Clone)]
@@ -66,12 +66,13 @@ impl Pool for UniswapV2Pool {
         ]
     }
 
-    fn calc_amount_out(&self, amount_in: U256, tok_in: Address) -> eyre::Result<U256> {
+    fn calc_amount_out(&self, amount_in: U256, tok_in: Address, tok_out: Address) -> eyre::Result<U256> {
+        check_pair(self.data.tok0, self.data.tok1, tok_in, tok_out)?;
         pricing::local::calc_amount_out(
             amount_in,
             tok_in,
-            self.data.clone().into(),
-            self.state.clone().into()
+            (&self.data).into(),
+            (&self.state).into()
         )
     }
 }
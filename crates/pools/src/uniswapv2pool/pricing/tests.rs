@@ -46,8 +46,8 @@ async fn test_calculate_amount_out() {
     let amount_out_local = local::calc_amount_out(
         amount_in,
         tok_in,
-        pool.data.clone().into(),
-        pool.state.clone().into(),
+        (&pool.data).into(),
+        (&pool.state).into(),
     ).unwrap();
     info!(?amount_out_local);
 
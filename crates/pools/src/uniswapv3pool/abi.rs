@@ -21,9 +21,51 @@ sol! {
                 bool unlocked
         );
         function fee() external view returns (uint24);
+        function feeGrowthGlobal0X128() external view returns (uint256);
+        function feeGrowthGlobal1X128() external view returns (uint256);
         function tickSpacing() external view returns (int24);
         function ticks(int24 tick) external view returns (uint128, int128, uint256, uint256, int56, uint160, uint32, bool);
         function tickBitmap(int16 wordPosition) external view returns (uint256);
+        function observations(uint256 index) external view returns (uint32 blockTimestamp, int56 tickCumulative, uint160 secondsPerLiquidityCumulativeX128, bool initialized);
+        function observe(uint32[] calldata secondsAgos) external view returns (int56[] memory tickCumulatives, uint160[] memory secondsPerLiquidityCumulativeX128s);
         function swap(address recipient, bool zeroForOne, int256 amountSpecified, uint160 sqrtPriceLimitX96, bytes calldata data) external returns (int256, int256);
     }
 }
+
+sol! {
+    /// PancakeSwap V3 widens feeProtocol to uint32, which moves `unlocked` and shifts the storage layout by a slot.
+    #[derive(Debug, PartialEq, Eq)]
+    #[sol(rpc)]
+    contract IPancakeV3Pool {
+        function slot0() external view returns (
+                uint160 sqrtPriceX96,
+                int24 tick,
+                uint16 observationIndex,
+                uint16 observationCardinality,
+                uint16 observationCardinalityNext,
+                uint32 feeProtocol,
+                bool unlocked
+        );
+    }
+}
+
+sol! {
+    /// Payload passed to the pool swap and decoded by the executor in uniswapV3SwapCallback to pay the pool.
+    #[derive(Debug, PartialEq, Eq)]
+    struct SwapCallbackData {
+        address tokenIn;
+        address tokenOut;
+        uint24 fee;
+        address payer;
+    }
+}
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    #[sol(rpc)]
+    contract IUniswapV3Factory {
+        event PoolCreated(address indexed token0, address indexed token1, uint24 indexed fee, int24 tickSpacing, address pool);
+        function getPool(address tokenA, address tokenB, uint24 fee) external view returns (address pool);
+        function feeAmountTickSpacing(uint24 fee) external view returns (int24);
+    }
+}
@@ -0,0 +1,95 @@
+use alloy::primitives::aliases::U24;
+use alloy::primitives::{Address, Bytes, I256, U160, U256};
+use alloy::sol_types::{SolCall, SolValue};
+use eyre::eyre;
+use uniswap_v3_math::tick_math::{MAX_SQRT_RATIO, MIN_SQRT_RATIO};
+use crate::uniswapv3pool::abi::IUniswapV3Pool::swapCall;
+pub use crate::uniswapv3pool::abi::SwapCallbackData;
+use crate::uniswapv3pool::UniswapV3Pool;
+
+/// Encodes `IUniswapV3Pool.swap`.
+///
+/// A positive `amount_specified` is an exact input, a negative one an exact output.
+/// Without a limit the swap may move the price up to the pool bounds, like the router does.
+pub fn encode_swap(
+    recipient: Address,
+    zero_for_one: bool,
+    amount_specified: I256,
+    sqrt_price_limit_x96: Option<U160>,
+    callback_data: &SwapCallbackData,
+) -> eyre::Result<Bytes> {
+    if amount_specified.is_zero() {
+        return Err(eyre!("AS"));
+    }
+
+    let sqrt_price_limit_x96 = match sqrt_price_limit_x96 {
+        Some(limit) => limit,
+        None if zero_for_one => (MIN_SQRT_RATIO + U256::from(1)).to(),
+        None => (MAX_SQRT_RATIO - U256::from(1)).to(),
+    };
+
+    let call = swapCall {
+        recipient,
+        zeroForOne: zero_for_one,
+        amountSpecified: amount_specified,
+        sqrtPriceLimitX96: sqrt_price_limit_x96,
+        data: callback_data.abi_encode().into(),
+    };
+    Ok(call.abi_encode().into())
+}
+
+pub fn decode_callback_data(data: &[u8]) -> eyre::Result<SwapCallbackData> {
+    Ok(SwapCallbackData::abi_decode(data, true)?)
+}
+
+/// Decodes the `(int256 amount0, int256 amount1)` pool deltas returned by swap, positive when paid to the pool.
+pub fn decode_swap_return(data: &[u8]) -> eyre::Result<(I256, I256)> {
+    let ret = swapCall::abi_decode_returns(data, true)?;
+    Ok((ret._0, ret._1))
+}
+
+impl UniswapV3Pool {
+    /// Calldata of an exact input swap of `amount_in` tok_in sent to the pool, paid by `payer` in the callback.
+    pub fn encode_swap_exact_in(
+        &self,
+        recipient: Address,
+        tok_in: Address,
+        amount_in: U256,
+        sqrt_price_limit_x96: Option<U160>,
+        payer: Address,
+    ) -> eyre::Result<Bytes> {
+        if tok_in != self.data.tok0 && tok_in != self.data.tok1 {
+            return Err(eyre!("TOKEN_NOT_IN_POOL {tok_in}"));
+        }
+        let amount_specified = I256::try_from(amount_in)?;
+        let callback_data = SwapCallbackData {
+            tokenIn: tok_in,
+            tokenOut: self.get_other_token(tok_in),
+            fee: U24::from(self.data.fee),
+            payer,
+        };
+        encode_swap(recipient, tok_in == self.data.tok0, amount_specified, sqrt_price_limit_x96, &callback_data)
+    }
+
+    /// Calldata of an exact output swap receiving `amount_out` of the other token, paid by `payer` in the callback.
+    pub fn encode_swap_exact_out(
+        &self,
+        recipient: Address,
+        tok_in: Address,
+        amount_out: U256,
+        sqrt_price_limit_x96: Option<U160>,
+        payer: Address,
+    ) -> eyre::Result<Bytes> {
+        if tok_in != self.data.tok0 && tok_in != self.data.tok1 {
+            return Err(eyre!("TOKEN_NOT_IN_POOL {tok_in}"));
+        }
+        let amount_specified = -I256::try_from(amount_out)?;
+        let callback_data = SwapCallbackData {
+            tokenIn: tok_in,
+            tokenOut: self.get_other_token(tok_in),
+            fee: U24::from(self.data.fee),
+            payer,
+        };
+        encode_swap(recipient, tok_in == self.data.tok0, amount_specified, sqrt_price_limit_x96, &callback_data)
+    }
+}
@@ -0,0 +1,112 @@
+use std::fmt::Write;
+use alloy::primitives::U256;
+use eyre::eyre;
+use serde::{Deserialize, Serialize};
+use uniswap_v3_math::sqrt_price_math::{_get_amount_0_delta, _get_amount_1_delta};
+use uniswap_v3_math::tick_math::get_sqrt_ratio_at_tick;
+use crate::uniswapv3pool::UniswapV3PoolState;
+
+/// Active liquidity between two adjacent initialized ticks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiquidityRange {
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    /// Raw token1/token0 prices at the range bounds, not adjusted for decimals.
+    pub price_lower: f64,
+    pub price_upper: f64,
+    pub liquidity: u128,
+    /// Tokens held by the range at the current price.
+    pub amount0: U256,
+    pub amount1: U256,
+    pub contains_current_tick: bool,
+}
+
+/// Liquidity profile over the initialized ticks of the state, ordered by tick.
+///
+/// Only covers the fetched tick bitmap window, ranges outside the outermost known ticks are omitted.
+pub fn liquidity_distribution(state: &UniswapV3PoolState) -> eyre::Result<Vec<LiquidityRange>> {
+    let ticks: Vec<(i32, i128)> = state.ticks.iter().map(|(tick, info)| (*tick, info.liquidity_net)).collect();
+
+    let current_tick = state.slot0.tick;
+    let idx = ticks.partition_point(|(tick, _)| *tick <= current_tick);
+    let mut ranges = Vec::new();
+
+    // Walk down from the current tick, un-crossing ticks
+    let mut liquidity = state.liquidity as i128;
+    if idx > 0 && idx < ticks.len() {
+        ranges.push(liquidity_range(state, ticks[idx - 1].0, ticks[idx].0, liquidity)?);
+    }
+    for i in (1..idx).rev() {
+        liquidity -= ticks[i].1;
+        ranges.push(liquidity_range(state, ticks[i - 1].0, ticks[i].0, liquidity)?);
+    }
+
+    // Walk up from the current tick, crossing ticks
+    let mut liquidity = state.liquidity as i128;
+    for i in idx..ticks.len().saturating_sub(1) {
+        liquidity += ticks[i].1;
+        ranges.push(liquidity_range(state, ticks[i].0, ticks[i + 1].0, liquidity)?);
+    }
+
+    ranges.sort_unstable_by_key(|range| range.tick_lower);
+    Ok(ranges)
+}
+
+fn liquidity_range(state: &UniswapV3PoolState, tick_lower: i32, tick_upper: i32, liquidity: i128) -> eyre::Result<LiquidityRange> {
+    if liquidity < 0 {
+        return Err(eyre!("LIQUIDITY_UNDERFLOW at [{tick_lower}, {tick_upper})"));
+    }
+    let liquidity = liquidity as u128;
+    let sqrt_price_lower = get_sqrt_ratio_at_tick(tick_lower)?;
+    let sqrt_price_upper = get_sqrt_ratio_at_tick(tick_upper)?;
+    let sqrt_price = state.slot0.sqrt_price_x96;
+
+    let (amount0, amount1) = if sqrt_price <= sqrt_price_lower {
+        (_get_amount_0_delta(sqrt_price_lower, sqrt_price_upper, liquidity, false)?, U256::ZERO)
+    } else if sqrt_price >= sqrt_price_upper {
+        (U256::ZERO, _get_amount_1_delta(sqrt_price_lower, sqrt_price_upper, liquidity, false)?)
+    } else {
+        (
+            _get_amount_0_delta(sqrt_price, sqrt_price_upper, liquidity, false)?,
+            _get_amount_1_delta(sqrt_price_lower, sqrt_price, liquidity, false)?,
+        )
+    };
+
+    Ok(LiquidityRange {
+        tick_lower,
+        tick_upper,
+        price_lower: tick_to_price(tick_lower),
+        price_upper: tick_to_price(tick_upper),
+        liquidity,
+        amount0,
+        amount1,
+        contains_current_tick: tick_lower <= state.slot0.tick && state.slot0.tick < tick_upper,
+    })
+}
+
+pub fn tick_to_price(tick: i32) -> f64 {
+    1.0001f64.powi(tick)
+}
+
+pub fn to_json(ranges: &[LiquidityRange]) -> eyre::Result<String> {
+    Ok(simd_json::to_string(ranges)?)
+}
+
+pub fn to_csv(ranges: &[LiquidityRange]) -> String {
+    let mut csv = String::from("tick_lower,tick_upper,price_lower,price_upper,liquidity,amount0,amount1,contains_current_tick\n");
+    for range in ranges {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{}",
+            range.tick_lower,
+            range.tick_upper,
+            range.price_lower,
+            range.price_upper,
+            range.liquidity,
+            range.amount0,
+            range.amount1,
+            range.contains_current_tick,
+        );
+    }
+    csv
+}
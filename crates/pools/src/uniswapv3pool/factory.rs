@@ -0,0 +1,101 @@
+use alloy::eips::BlockId;
+use alloy::network::Network;
+use alloy::primitives::aliases::U24;
+use alloy::primitives::Address;
+use alloy::providers::Provider;
+use alloy::rpc::types::Filter;
+use alloy::sol_types::SolEvent;
+use alloy::transports::Transport;
+use eyre::eyre;
+use tracing::instrument;
+use crate::rpc::get_logs_chunked;
+use crate::uniswapv3pool::abi::IUniswapV3Factory;
+use crate::uniswapv3pool::{UniswapV3Pool, UniswapV3PoolData};
+
+fn factory_address(core: &config::UniswapV3Core) -> eyre::Result<Address> {
+    core.factory.ok_or(eyre!("UNISWAP_V3_FACTORY_NOT_CONFIGURED"))
+}
+
+fn discovered_pool(
+    pool: Address,
+    factory: Address,
+    tok0: Address,
+    tok1: Address,
+    fee: u32,
+    tick_spacing: u32,
+    periphery: &config::UniswapV3Periphery,
+) -> UniswapV3Pool {
+    let mut v3_pool = UniswapV3Pool::new(pool, periphery.clone());
+    v3_pool.data = UniswapV3PoolData {
+        factory,
+        tok0,
+        tok1,
+        fee,
+        tick_spacing,
+    };
+    v3_pool
+}
+
+/// Pools created by the factory in [from_block, to_block], from its PoolCreated events.
+///
+/// The returned pools have their data filled in and only need their state synced.
+#[instrument(skip(provider), level = "debug")]
+pub async fn discover_pools<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+    core: &config::UniswapV3Core,
+    periphery: &config::UniswapV3Periphery,
+    from_block: u64,
+    to_block: u64,
+    provider: P,
+) -> eyre::Result<Vec<UniswapV3Pool>> {
+    let factory = factory_address(core)?;
+    let mut pools = Vec::new();
+
+    let filter = Filter::new()
+        .address(factory)
+        .event_signature(IUniswapV3Factory::PoolCreated::SIGNATURE_HASH);
+    let logs = get_logs_chunked(&provider, &filter, from_block.max(core.factory_start_block), to_block).await?;
+    for log in logs {
+        let created = log.log_decode::<IUniswapV3Factory::PoolCreated>()?.inner.data;
+        let tick_spacing: i32 = created.tickSpacing.try_into()?;
+        pools.push(discovered_pool(
+            created.pool,
+            factory,
+            created.token0,
+            created.token1,
+            created.fee.to::<u32>(),
+            tick_spacing.try_into()?,
+            periphery,
+        ));
+    }
+
+    Ok(pools)
+}
+
+/// Pools of the token pair for every fee tier enabled in the config, through the factory getPool.
+#[instrument(skip(provider), level = "debug")]
+pub async fn discover_pair_pools<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+    core: &config::UniswapV3Core,
+    periphery: &config::UniswapV3Periphery,
+    tok_a: Address,
+    tok_b: Address,
+    provider: P,
+    block: BlockId,
+) -> eyre::Result<Vec<UniswapV3Pool>> {
+    let factory = factory_address(core)?;
+    let v3_factory = IUniswapV3Factory::new(factory, provider.clone());
+    let (tok0, tok1) = if tok_a < tok_b { (tok_a, tok_b) } else { (tok_b, tok_a) };
+
+    let mut pools = Vec::new();
+    for fee in core.fee_tiers.iter().copied() {
+        let pool = v3_factory.getPool(tok0, tok1, U24::from(fee)).block(block).call().await?.pool;
+        if pool == Address::ZERO {
+            continue;
+        }
+
+        let tick_spacing: i32 = v3_factory.feeAmountTickSpacing(U24::from(fee)).block(block).call().await?._0.try_into()?;
+        let tick_spacing = if tick_spacing > 0 { tick_spacing as u32 } else { UniswapV3Pool::tick_spacing(fee)? };
+        pools.push(discovered_pool(pool, factory, tok0, tok1, fee, tick_spacing, periphery));
+    }
+
+    Ok(pools)
+}
@@ -0,0 +1,95 @@
+use alloy::primitives::{Address, U256};
+use uniswap_v3_math::full_math::mul_div;
+use crate::uniswapv3pool::position::Q128;
+use crate::uniswapv3pool::pricing::local::{self, SwapResult};
+use crate::uniswapv3pool::{UniswapV3PoolData, UniswapV3PoolState};
+
+/// Fee growth per unit of liquidity inside [tick_lower, tick_upper), as Tick.getFeeGrowthInside.
+///
+/// Ticks that are not initialized are treated as having zero fee growth outside.
+pub fn fee_growth_inside(state: &UniswapV3PoolState, tick_lower: i32, tick_upper: i32) -> (U256, U256) {
+    let current_tick = state.slot0.tick;
+    let global = (state.fee_growth_global_0_x128, state.fee_growth_global_1_x128);
+    let outside = |tick: i32| {
+        state.ticks.get(tick).map_or((U256::ZERO, U256::ZERO), |info| (info.fee_growth_outside_0_x128, info.fee_growth_outside_1_x128))
+    };
+
+    let lower = outside(tick_lower);
+    let below = if current_tick >= tick_lower {
+        lower
+    } else {
+        (global.0.wrapping_sub(lower.0), global.1.wrapping_sub(lower.1))
+    };
+
+    let upper = outside(tick_upper);
+    let above = if current_tick < tick_upper {
+        upper
+    } else {
+        (global.0.wrapping_sub(upper.0), global.1.wrapping_sub(upper.1))
+    };
+
+    (
+        global.0.wrapping_sub(below.0).wrapping_sub(above.0),
+        global.1.wrapping_sub(below.1).wrapping_sub(above.1),
+    )
+}
+
+/// Fees owed to `liquidity` between two fee growth inside readings, as Position.update.
+pub fn fees_owed(liquidity: u128, fee_growth_inside_before: (U256, U256), fee_growth_inside_after: (U256, U256)) -> eyre::Result<(U256, U256)> {
+    Ok((
+        mul_div(fee_growth_inside_after.0.wrapping_sub(fee_growth_inside_before.0), U256::from(liquidity), Q128)?,
+        mul_div(fee_growth_inside_after.1.wrapping_sub(fee_growth_inside_before.1), U256::from(liquidity), Q128)?,
+    ))
+}
+
+/// Swaps against the state and writes back the price, tick, liquidity and fee growth, crossing ticks like the pool.
+pub fn apply_swap(
+    data: &UniswapV3PoolData,
+    state: &mut UniswapV3PoolState,
+    amount_in: U256,
+    tok_in: Address,
+    sqrt_price_limit_x96: Option<U256>,
+) -> eyre::Result<SwapResult> {
+    let zero_for_one = tok_in == data.tok0;
+    let protocol_fee = state.slot0.protocol_fee(zero_for_one);
+    let mut fee_growth_global_x128 = if zero_for_one { state.fee_growth_global_0_x128 } else { state.fee_growth_global_1_x128 };
+
+    // Ticks crossed with the input token fee growth global at that moment
+    let mut crossed: Vec<(i32, U256)> = Vec::new();
+    let mut error = None;
+    let swap = local::swap(amount_in, tok_in, sqrt_price_limit_x96, data.into(), (&*state).into(), |step, sqrt_price_x96, liquidity| {
+        if liquidity > 0 {
+            let fee_amount = step.fee_amount - protocol_fee.amount(step.fee_amount);
+            match mul_div(fee_amount, Q128, U256::from(liquidity)) {
+                Ok(growth) => fee_growth_global_x128 = fee_growth_global_x128.wrapping_add(growth),
+                Err(e) => error = Some(e),
+            }
+        }
+        if step.initialized && sqrt_price_x96 == step.sqrt_price_next_x96 {
+            crossed.push((step.tick_next, fee_growth_global_x128));
+        }
+    })?;
+    if let Some(e) = error {
+        return Err(e.into());
+    }
+
+    let (fee_growth_global_0_x128, fee_growth_global_1_x128) = (state.fee_growth_global_0_x128, state.fee_growth_global_1_x128);
+    for (tick, growth) in crossed {
+        if let Some(info) = state.ticks.get_mut(tick) {
+            let (global_0, global_1) = if zero_for_one { (growth, fee_growth_global_1_x128) } else { (fee_growth_global_0_x128, growth) };
+            info.fee_growth_outside_0_x128 = global_0.wrapping_sub(info.fee_growth_outside_0_x128);
+            info.fee_growth_outside_1_x128 = global_1.wrapping_sub(info.fee_growth_outside_1_x128);
+        }
+    }
+
+    if zero_for_one {
+        state.fee_growth_global_0_x128 = fee_growth_global_x128;
+    } else {
+        state.fee_growth_global_1_x128 = fee_growth_global_x128;
+    }
+    state.slot0.sqrt_price_x96 = swap.sqrt_price_x96;
+    state.slot0.tick = swap.tick;
+    state.liquidity = swap.liquidity;
+
+    Ok(swap)
+}
@@ -1,19 +1,41 @@
 use std::collections::HashMap;
+use std::ops::RangeInclusive;
 use alloy::eips::BlockId;
 use alloy::network::Network;
 use alloy::primitives::{Address, U256};
 use alloy::providers::Provider;
 use alloy::transports::Transport;
-use tracing::instrument;
-use types::pool::{Pool, PoolClass, PoolProtocol};
-use crate::uniswapv3pool::abi::IUniswapV3Pool;
+use eyre::eyre;
+use tracing::{instrument, warn};
+use uniswap_v3_math::tick_math::{MAX_TICK, MIN_TICK};
+use types::pool::{check_pair, Pool, PoolClass, PoolProtocol};
+use crate::rpc::is_revert;
+use crate::uniswapv3pool::abi::{IPancakeV3Pool, IUniswapV3Pool};
+use crate::uniswapv3pool::distribution::LiquidityRange;
+use crate::uniswapv3pool::oracle::Twap;
 use crate::uniswapv3pool::pricing::abi::ITickLens;
-use crate::uniswapv3pool::pricing::local::TickInfo;
+use crate::uniswapv3pool::pricing::local::{SwapResult, TickInfo};
 use crate::uniswapv3pool::slot0::Slot0;
+use crate::uniswapv3pool::ticks::Ticks;
 
-mod slot0;
+#[cfg(test)]
+mod tests;
+pub(crate) mod slot0;
 mod abi;
-mod pricing;
+pub(crate) mod pricing;
+pub(crate) mod storage;
+pub mod ticks;
+pub mod oracle;
+pub mod distribution;
+pub mod position;
+pub mod route;
+pub mod calldata;
+pub mod snapshot;
+pub mod fees;
+pub mod factory;
+
+/// Tick bitmap words fetched around the current tick.
+pub(crate) const TICK_BITMAP_WORD_RANGE: RangeInclusive<i16> = -4..=3; // TODO: define the range better
 
 #[derive(Debug, Clone)]
 pub struct UniswapV3PoolMetadata {
@@ -35,8 +57,10 @@ pub struct UniswapV3PoolData {
 pub struct UniswapV3PoolState {
     pub slot0: Slot0,
     pub liquidity: u128,
+    pub fee_growth_global_0_x128: U256,
+    pub fee_growth_global_1_x128: U256,
     pub tick_bitmap: HashMap<i16, U256>,
-    pub ticks: HashMap<i32, TickInfo>,
+    pub ticks: Ticks,
 }
 
 #[derive(Debug, Clone)]
@@ -70,13 +94,9 @@ impl Pool for UniswapV3Pool {
         ]
     }
 
-    fn calc_amount_out(&self, amount_in: U256, tok_in: Address) -> eyre::Result<U256> {
-        pricing::local::calc_amount_out(
-            amount_in,
-            tok_in,
-            self.data.clone().into(),
-            self.state.clone().into()
-        )
+    fn calc_amount_out(&self, amount_in: U256, tok_in: Address, tok_out: Address) -> eyre::Result<U256> {
+        check_pair(self.data.tok0, self.data.tok1, tok_in, tok_out)?;
+        Ok(self.calc_swap(amount_in, tok_in, None)?.amount_out)
     }
 }
 
@@ -84,11 +104,16 @@ impl Pool for UniswapV3Pool {
 #[allow(dead_code)]
 impl UniswapV3Pool {
     pub fn new(pool: Address, periphery: config::UniswapV3Periphery) -> Self {
+        UniswapV3Pool::new_with_protocol(pool, periphery, PoolProtocol::UniswapV3Like)
+    }
+
+    /// For forks whose slot0 or storage layout differs, e.g. [`PoolProtocol::PancakeSwapV3`].
+    pub fn new_with_protocol(pool: Address, periphery: config::UniswapV3Periphery, protocol: PoolProtocol) -> Self {
         UniswapV3Pool {
             metadata: UniswapV3PoolMetadata {
                 pool,
                 periphery,
-                protocol: PoolProtocol::UniswapV3Like,
+                protocol,
             },
             data: Default::default(),
             state: Default::default(),
@@ -117,6 +142,23 @@ impl UniswapV3Pool {
         Ok(())
     }
 
+    /// Syncs only the state, for pools whose data is already known, e.g. from factory discovery.
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn sync_state<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        &mut self,
+        provider: P,
+        block: BlockId
+    ) -> eyre::Result<()> {
+        self.state = UniswapV3Pool::fetch_state(
+            &self.metadata,
+            &self.data,
+            provider.clone(),
+            block.into(),
+        ).await?;
+
+        Ok(())
+    }
+
     // TODO: make batch request
     #[instrument(skip_all, level = "debug", ret)]
     pub async fn fetch_data<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
@@ -131,15 +173,32 @@ impl UniswapV3Pool {
         let fee: u32 = v3_pool.fee().block(block).call().await?._0.try_into()?;
         let factory: Address = v3_pool.factory().block(block).call().await?._0;
 
+        // Forks may not expose tickSpacing(), fall back to the standard fee tiers when it reverts
+        let tick_spacing = match v3_pool.tickSpacing().block(block).call().await {
+            Ok(ret) => {
+                let tick_spacing: i32 = ret._0.try_into()?;
+                if tick_spacing <= 0 {
+                    return Err(eyre!("INVALID_TICK_SPACING {tick_spacing}"));
+                }
+                tick_spacing as u32
+            }
+            Err(e) if is_revert(&e) => {
+                warn!(pool = ?metadata.pool, ?e, "tickSpacing() reverted, using fee tier table");
+                UniswapV3Pool::tick_spacing(fee)?
+            }
+            Err(e) => return Err(e.into()),
+        };
+
         Ok(UniswapV3PoolData {
             factory,
             tok0,
             tok1,
             fee,
-            tick_spacing: UniswapV3Pool::tick_spacing(fee),
+            tick_spacing,
         })
     }
 
+    /// Fetches the pool state through TickLens when configured, from raw storage slots otherwise.
     #[allow(dead_code)]
     #[instrument(skip(provider), level = "debug", ret)]
     pub async fn fetch_state<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
@@ -147,24 +206,40 @@ impl UniswapV3Pool {
         data: &UniswapV3PoolData,
         provider: P,
         block: BlockId,
+    ) -> eyre::Result<UniswapV3PoolState> {
+        match metadata.periphery.tick_lens {
+            Some(tick_lens) => UniswapV3Pool::fetch_state_tick_lens(metadata, data, tick_lens, provider, block).await,
+            None => UniswapV3Pool::fetch_state_storage(metadata, data, provider, block).await,
+        }
+    }
+
+    #[instrument(skip(provider), level = "debug", ret)]
+    pub async fn fetch_state_tick_lens<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &UniswapV3PoolMetadata,
+        data: &UniswapV3PoolData,
+        tick_lens: Address,
+        provider: P,
+        block: BlockId,
     ) -> eyre::Result<UniswapV3PoolState> {
         let v3_pool = IUniswapV3Pool::IUniswapV3PoolInstance::new(metadata.pool, provider.clone());
 
         let liquidity: u128 = v3_pool.liquidity().block(block).call().await?._0;
-        let slot0: Slot0 = v3_pool.slot0().block(block).call().await?.into();
+        let slot0 = UniswapV3Pool::fetch_slot0(metadata, provider.clone(), block).await?;
+        let fee_growth_global_0_x128: U256 = v3_pool.feeGrowthGlobal0X128().block(block).call().await?._0;
+        let fee_growth_global_1_x128: U256 = v3_pool.feeGrowthGlobal1X128().block(block).call().await?._0;
 
         let mut tick_bitmap: HashMap<i16, U256> = Default::default();
-        let mut ticks: HashMap<i32, TickInfo> = Default::default();
+        let mut ticks = Ticks::default();
 
         let tick_lens = ITickLens::new(
-            metadata.periphery.tick_lens.clone(),
+            tick_lens,
             provider.clone(),
         );
         let tick_bitmap_index = UniswapV3Pool::get_tick_bitmap_index(
             slot0.tick,
             data.tick_spacing,
-        );
-        for i in -4..=3 { // TODO: define the range better
+        )?;
+        for i in TICK_BITMAP_WORD_RANGE {
             let next_index = tick_bitmap_index + i;
             let populated_ticks = tick_lens.getPopulatedTicksInWord(
                 metadata.pool,
@@ -180,6 +255,7 @@ impl UniswapV3Pool {
                     TickInfo {
                         liquidity_gross: populated_tick.liquidityGross,
                         liquidity_net: populated_tick.liquidityNet,
+                        ..Default::default()
                     },
                 );
             }
@@ -191,14 +267,240 @@ impl UniswapV3Pool {
             );
         }
 
+        // TickLens does not return the fee growth outside, read the feeGrowthOutside0X128 and feeGrowthOutside1X128 slots of each Tick.Info in batches
+        let layout = storage::layout(metadata.protocol);
+        let fee_growth_slots: Vec<U256> = ticks
+            .iter()
+            .flat_map(|(tick, _)| {
+                let slot = storage::tick_slot(&layout, *tick);
+                [slot + U256::from(1), slot + U256::from(2)]
+            })
+            .collect();
+        let fee_growth_values = storage::get_storage_batch(
+            provider.clone(),
+            metadata.pool,
+            &fee_growth_slots,
+            block,
+        ).await?;
+        let tick_keys: Vec<i32> = ticks.iter().map(|(tick, _)| *tick).collect();
+        for (tick, values) in tick_keys.into_iter().zip(fee_growth_values.chunks(2)) {
+            let info = ticks.get_mut_or_default(tick);
+            info.fee_growth_outside_0_x128 = values[0];
+            info.fee_growth_outside_1_x128 = values[1];
+        }
+
         Ok(UniswapV3PoolState {
             slot0,
             liquidity,
+            fee_growth_global_0_x128,
+            fee_growth_global_1_x128,
             tick_bitmap,
             ticks,
         })
     }
 
+    /// Calls slot0() with the ABI of the fork, PancakeSwap V3 returns a uint32 feeProtocol.
+    #[instrument(skip(provider), level = "debug", ret)]
+    pub async fn fetch_slot0<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &UniswapV3PoolMetadata,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<Slot0> {
+        let slot0 = match metadata.protocol {
+            PoolProtocol::PancakeSwapV3 => {
+                let pool = IPancakeV3Pool::IPancakeV3PoolInstance::new(metadata.pool, provider.clone());
+                pool.slot0().block(block).call().await?.into()
+            }
+            _ => {
+                let pool = IUniswapV3Pool::IUniswapV3PoolInstance::new(metadata.pool, provider.clone());
+                pool.slot0().block(block).call().await?.into()
+            }
+        };
+        Ok(slot0)
+    }
+
+    /// Reads slot0, liquidity, tickBitmap and ticks directly from storage, for chains without TickLens.
+    #[instrument(skip(provider), level = "debug", ret)]
+    pub async fn fetch_state_storage<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &UniswapV3PoolMetadata,
+        data: &UniswapV3PoolData,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<UniswapV3PoolState> {
+        UniswapV3Pool::fetch_state_storage_words(metadata, data, false, provider, block).await
+    }
+
+    /// Same as [`UniswapV3Pool::fetch_state_storage`] over the whole MIN_TICK..MAX_TICK bitmap, used to record snapshots.
+    #[instrument(skip(provider), level = "debug", ret)]
+    pub async fn fetch_state_storage_full<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &UniswapV3PoolMetadata,
+        data: &UniswapV3PoolData,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<UniswapV3PoolState> {
+        UniswapV3Pool::fetch_state_storage_words(metadata, data, true, provider, block).await
+    }
+
+    async fn fetch_state_storage_words<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &UniswapV3PoolMetadata,
+        data: &UniswapV3PoolData,
+        full_bitmap: bool,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<UniswapV3PoolState> {
+        let layout = storage::layout(metadata.protocol);
+        let mut slots: Vec<U256> = (0..layout.slot0_words).map(|i| layout.slot0 + U256::from(i)).collect();
+        slots.extend([layout.liquidity, layout.fee_growth_global_0, layout.fee_growth_global_1]);
+        let values = storage::get_storage_batch(
+            provider.clone(),
+            metadata.pool,
+            &slots,
+            block,
+        ).await?;
+        let (slot0_values, values) = values.split_at(layout.slot0_words);
+        let slot0 = storage::storage_to_slot0(&layout, slot0_values);
+        let liquidity = storage::storage_to_liquidity(values[0]);
+        let (fee_growth_global_0_x128, fee_growth_global_1_x128) = (values[1], values[2]);
+
+        let tick_bitmap_index = UniswapV3Pool::get_tick_bitmap_index(
+            slot0.tick,
+            data.tick_spacing,
+        )?;
+        let word_positions: Vec<i16> = if full_bitmap {
+            let min_word = UniswapV3Pool::get_tick_bitmap_index(MIN_TICK, data.tick_spacing)?;
+            let max_word = UniswapV3Pool::get_tick_bitmap_index(MAX_TICK, data.tick_spacing)?;
+            (min_word..=max_word).collect()
+        } else {
+            TICK_BITMAP_WORD_RANGE.map(|i| tick_bitmap_index + i).collect()
+        };
+        let words = storage::get_storage_batch(
+            provider.clone(),
+            metadata.pool,
+            &word_positions.iter().map(|w| storage::tick_bitmap_slot(&layout, *w)).collect::<Vec<_>>(),
+            block,
+        ).await?;
+        let tick_bitmap: HashMap<i16, U256> = word_positions.into_iter().zip(words).collect();
+
+        let mut initialized_ticks: Vec<i32> = Vec::new();
+        for (word_pos, word) in tick_bitmap.iter() {
+            for bit_pos in 0..256usize {
+                if word.bit(bit_pos) {
+                    let compressed = ((*word_pos as i32) << 8) + bit_pos as i32;
+                    initialized_ticks.push(compressed * data.tick_spacing as i32);
+                }
+            }
+        }
+        // liquidity, feeGrowthOutside0X128 and feeGrowthOutside1X128 slots of each Tick.Info
+        let tick_slots: Vec<U256> = initialized_ticks
+            .iter()
+            .flat_map(|t| {
+                let slot = storage::tick_slot(&layout, *t);
+                [slot, slot + U256::from(1), slot + U256::from(2)]
+            })
+            .collect();
+        let tick_values = storage::get_storage_batch(
+            provider.clone(),
+            metadata.pool,
+            &tick_slots,
+            block,
+        ).await?;
+        let ticks: Ticks = initialized_ticks
+            .into_iter()
+            .zip(tick_values.chunks(3))
+            .map(|(tick, values)| (tick, storage::storage_to_tick_info(values[0], values[1], values[2])))
+            .collect();
+
+        Ok(UniswapV3PoolState {
+            slot0,
+            liquidity,
+            fee_growth_global_0_x128,
+            fee_growth_global_1_x128,
+            tick_bitmap,
+            ticks,
+        })
+    }
+
+    /// Time-weighted average tick, price and harmonic mean liquidity over the last `seconds_ago` seconds.
+    #[instrument(skip(provider), level = "debug", ret)]
+    pub async fn fetch_twap<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &UniswapV3PoolMetadata,
+        seconds_ago: u32,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<Twap> {
+        let v3_pool = IUniswapV3Pool::IUniswapV3PoolInstance::new(metadata.pool, provider.clone());
+
+        let observed = v3_pool.observe(vec![seconds_ago, 0]).block(block).call().await?;
+        if observed.tickCumulatives.len() != 2 || observed.secondsPerLiquidityCumulativeX128s.len() != 2 {
+            return Err(eyre!("INVALID_OBSERVE_RESPONSE"));
+        }
+
+        oracle::consult(
+            seconds_ago,
+            [observed.tickCumulatives[0].try_into()?, observed.tickCumulatives[1].try_into()?],
+            [observed.secondsPerLiquidityCumulativeX128s[0], observed.secondsPerLiquidityCumulativeX128s[1]],
+        )
+    }
+
+    /// Distance in ticks between the spot price and the given TWAP.
+    pub fn twap_tick_deviation(&self, twap: &Twap) -> i32 {
+        (self.state.slot0.tick - twap.arithmetic_mean_tick).abs()
+    }
+
+    /// Active liquidity and token amounts per initialized tick range.
+    pub fn liquidity_distribution(&self) -> eyre::Result<Vec<LiquidityRange>> {
+        distribution::liquidity_distribution(&self.state)
+    }
+
+    /// Clone of the pool with `liquidity` minted in [tick_lower, tick_upper), with the token amounts the mint costs.
+    pub fn with_position(&self, tick_lower: i32, tick_upper: i32, liquidity: u128) -> eyre::Result<(UniswapV3Pool, U256, U256)> {
+        let mut pool = self.clone();
+        let (amount0, amount1) = position::mint(&mut pool.state, self.data.tick_spacing, tick_lower, tick_upper, liquidity)?;
+        Ok((pool, amount0, amount1))
+    }
+
+    /// Fees in tok_in earned by a position already minted with [`UniswapV3Pool::with_position`] from the given swap.
+    pub fn calc_position_fees(
+        &self,
+        amount_in: U256,
+        tok_in: Address,
+        tick_lower: i32,
+        tick_upper: i32,
+        liquidity: u128,
+    ) -> eyre::Result<U256> {
+        let (_swap, fees) = position::swap_fees(
+            amount_in,
+            tok_in,
+            (&self.data).into(),
+            (&self.state).into(),
+            tick_lower,
+            tick_upper,
+            liquidity,
+        )?;
+        Ok(fees)
+    }
+
+    /// Swaps against the pool state in place, updating fee growth and crossed ticks.
+    pub fn apply_swap(&mut self, amount_in: U256, tok_in: Address, sqrt_price_limit_x96: Option<U256>) -> eyre::Result<SwapResult> {
+        fees::apply_swap(&self.data, &mut self.state, amount_in, tok_in, sqrt_price_limit_x96)
+    }
+
+    /// Fee growth per unit of liquidity inside [tick_lower, tick_upper) for token0 and token1.
+    pub fn fee_growth_inside(&self, tick_lower: i32, tick_upper: i32) -> (U256, U256) {
+        fees::fee_growth_inside(&self.state, tick_lower, tick_upper)
+    }
+
+    /// Simulates an exact input swap stopping at `sqrt_price_limit_x96`, if given.
+    pub fn calc_swap(&self, amount_in: U256, tok_in: Address, sqrt_price_limit_x96: Option<U256>) -> eyre::Result<SwapResult> {
+        pricing::local::calc_amount_out(
+            amount_in,
+            tok_in,
+            sqrt_price_limit_x96,
+            (&self.data).into(),
+            (&self.state).into()
+        )
+    }
+
     fn get_tokens(&self) -> Vec<Address> {
         vec![
             self.data.tok0,
@@ -210,22 +512,51 @@ impl UniswapV3Pool {
         if tok == self.data.tok0 { self.data.tok1 } else { self.data.tok0 }
     }
 
-    pub fn tick_spacing(fee: u32) -> u32 {
-        Self::get_price_step(fee)
+    /// Tick spacing of the well known fee tiers, used when the pool does not report it.
+    pub fn tick_spacing(fee: u32) -> eyre::Result<u32> {
+        Self::get_price_step(fee).ok_or(eyre!("UNKNOWN_FEE_TIER {fee}"))
     }
 
-    pub fn get_price_step(fee: u32) -> u32 {
+    pub fn get_price_step(fee: u32) -> Option<u32> {
         match fee {
-            10000 => 200,
-            3000 => 60,
-            500 => 10,
-            100 => 1,
-            _ => 0,
+            10000 => Some(200),
+            3000 => Some(60),
+            2500 => Some(50),
+            500 => Some(10),
+            100 => Some(1),
+            _ => None,
+        }
+    }
+
+    pub fn get_tick_bitmap_index(tick: i32, spacing: u32) -> eyre::Result<i16> {
+        if spacing == 0 {
+            return Err(eyre!("ZERO_TICK_SPACING"));
         }
+        // Round towards negative infinity like TickBitmap.nextInitializedTickWithinOneWord
+        let mut compressed = tick / (spacing as i32);
+        if tick < 0 && tick % (spacing as i32) != 0 {
+            compressed -= 1;
+        }
+        let (word_pos, _bit_pos) = uniswap_v3_math::tick_bitmap::position(compressed);
+        Ok(word_pos)
     }
+}
 
-    pub fn get_tick_bitmap_index(tick: i32, spacing: u32) -> i16 {
-        let (word_pos, _bit_pos) = uniswap_v3_math::tick_bitmap::position(tick / (spacing as i32));
-        word_pos
+#[cfg(test)]
+impl UniswapV3Pool {
+    /// Offline pool without periphery, priced at tick 0 with the fee tier's tick spacing and no liquidity.
+    pub(crate) fn synthetic(tok0: Address, tok1: Address, fee: u32) -> Self {
+        let mut pool = UniswapV3Pool::new(
+            Address::ZERO,
+            config::UniswapV3Periphery { quoter: Address::ZERO, quoter_v2: Address::ZERO, tick_lens: None },
+        );
+        pool.data.tok0 = tok0;
+        pool.data.tok1 = tok1;
+        pool.data.fee = fee;
+        pool.data.tick_spacing = UniswapV3Pool::get_price_step(fee).unwrap();
+        pool.state.slot0.sqrt_price_x96 = uniswap_v3_math::tick_math::get_sqrt_ratio_at_tick(0).unwrap();
+        pool.state.tick_bitmap.insert(-1, U256::ZERO);
+        pool.state.tick_bitmap.insert(0, U256::ZERO);
+        pool
     }
 }
@@ -0,0 +1,48 @@
+use alloy::primitives::{U160, U256};
+use eyre::eyre;
+
+/// Time-weighted averages over `seconds_ago` seconds, as computed by OracleLibrary.consult.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Twap {
+    pub seconds_ago: u32,
+    pub arithmetic_mean_tick: i32,
+    /// Square root price at the arithmetic mean tick.
+    pub sqrt_price_x96: U256,
+    pub harmonic_mean_liquidity: u128,
+}
+
+/// Computes the averages from the cumulatives returned by `observe([seconds_ago, 0])`.
+pub fn consult(
+    seconds_ago: u32,
+    tick_cumulatives: [i64; 2],
+    seconds_per_liquidity_cumulatives_x128: [U160; 2],
+) -> eyre::Result<Twap> {
+    if seconds_ago == 0 {
+        return Err(eyre!("BP"));
+    }
+
+    let tick_cumulatives_delta = tick_cumulatives[1] - tick_cumulatives[0];
+    let mut arithmetic_mean_tick = tick_cumulatives_delta / seconds_ago as i64;
+    // Always round to negative infinity
+    if tick_cumulatives_delta < 0 && tick_cumulatives_delta % seconds_ago as i64 != 0 {
+        arithmetic_mean_tick -= 1;
+    }
+    let arithmetic_mean_tick: i32 = arithmetic_mean_tick.try_into()?;
+
+    // uint160 subtraction wraps on chain
+    let seconds_per_liquidity_delta = U256::from(
+        seconds_per_liquidity_cumulatives_x128[1].wrapping_sub(seconds_per_liquidity_cumulatives_x128[0]),
+    );
+    if seconds_per_liquidity_delta.is_zero() {
+        return Err(eyre!("ZERO_SECONDS_PER_LIQUIDITY"));
+    }
+    let seconds_ago_x160 = U256::from(seconds_ago) * U256::from(U160::MAX);
+    let harmonic_mean_liquidity = seconds_ago_x160 / (seconds_per_liquidity_delta << 32);
+
+    Ok(Twap {
+        seconds_ago,
+        arithmetic_mean_tick,
+        sqrt_price_x96: uniswap_v3_math::tick_math::get_sqrt_ratio_at_tick(arithmetic_mean_tick)?,
+        harmonic_mean_liquidity: harmonic_mean_liquidity.saturating_to::<u128>(),
+    })
+}
@@ -0,0 +1,123 @@
+use alloy::primitives::{Address, U256};
+use eyre::eyre;
+use uniswap_v3_math::full_math::mul_div;
+use uniswap_v3_math::sqrt_price_math::{_get_amount_0_delta, _get_amount_1_delta};
+use uniswap_v3_math::tick_math::{get_sqrt_ratio_at_tick, MAX_TICK, MIN_TICK};
+use crate::uniswapv3pool::pricing::local::{self, PoolData, PoolState, SwapResult};
+use crate::uniswapv3pool::UniswapV3PoolState;
+
+pub const Q128: U256 = U256::from_limbs([0, 0, 1, 0]);
+
+/// Token amounts Pool.mint pulls for `liquidity` in [tick_lower, tick_upper) at the given price, rounded up.
+pub fn amounts_for_liquidity(sqrt_price_x96: U256, tick_lower: i32, tick_upper: i32, liquidity: u128) -> eyre::Result<(U256, U256)> {
+    let sqrt_price_lower = get_sqrt_ratio_at_tick(tick_lower)?;
+    let sqrt_price_upper = get_sqrt_ratio_at_tick(tick_upper)?;
+
+    if sqrt_price_x96 <= sqrt_price_lower {
+        Ok((_get_amount_0_delta(sqrt_price_lower, sqrt_price_upper, liquidity, true)?, U256::ZERO))
+    } else if sqrt_price_x96 < sqrt_price_upper {
+        Ok((
+            _get_amount_0_delta(sqrt_price_x96, sqrt_price_upper, liquidity, true)?,
+            _get_amount_1_delta(sqrt_price_lower, sqrt_price_x96, liquidity, true)?,
+        ))
+    } else {
+        Ok((U256::ZERO, _get_amount_1_delta(sqrt_price_lower, sqrt_price_upper, liquidity, true)?))
+    }
+}
+
+/// Adds `liquidity` in [tick_lower, tick_upper) to the state and returns the token amounts owed for it.
+pub fn mint(
+    state: &mut UniswapV3PoolState,
+    tick_spacing: u32,
+    tick_lower: i32,
+    tick_upper: i32,
+    liquidity: u128,
+) -> eyre::Result<(U256, U256)> {
+    if tick_lower >= tick_upper {
+        return Err(eyre!("TLU"));
+    }
+    if tick_lower < MIN_TICK {
+        return Err(eyre!("TLM"));
+    }
+    if tick_upper > MAX_TICK {
+        return Err(eyre!("TUM"));
+    }
+    if tick_spacing == 0 || tick_lower % tick_spacing as i32 != 0 || tick_upper % tick_spacing as i32 != 0 {
+        return Err(eyre!("TICK_NOT_SPACED"));
+    }
+    if liquidity == 0 {
+        return Err(eyre!("ZERO_LIQUIDITY"));
+    }
+
+    let amounts = amounts_for_liquidity(state.slot0.sqrt_price_x96, tick_lower, tick_upper, liquidity)?;
+
+    update_tick(state, tick_spacing, tick_lower, liquidity as i128, liquidity)?;
+    update_tick(state, tick_spacing, tick_upper, -(liquidity as i128), liquidity)?;
+
+    if tick_lower <= state.slot0.tick && state.slot0.tick < tick_upper {
+        state.liquidity = state.liquidity.checked_add(liquidity).ok_or(eyre!("LA"))?;
+    }
+
+    Ok(amounts)
+}
+
+fn update_tick(state: &mut UniswapV3PoolState, tick_spacing: u32, tick: i32, liquidity_net: i128, liquidity_gross: u128) -> eyre::Result<()> {
+    let (current_tick, fee_growth_global_0_x128, fee_growth_global_1_x128) =
+        (state.slot0.tick, state.fee_growth_global_0_x128, state.fee_growth_global_1_x128);
+    let info = state.ticks.get_mut_or_default(tick);
+    let flipped = info.liquidity_gross == 0;
+    // By convention all growth before a tick was initialized happened below it
+    if flipped && tick <= current_tick {
+        info.fee_growth_outside_0_x128 = fee_growth_global_0_x128;
+        info.fee_growth_outside_1_x128 = fee_growth_global_1_x128;
+    }
+    info.liquidity_gross = info.liquidity_gross.checked_add(liquidity_gross).ok_or(eyre!("LO"))?;
+    info.liquidity_net = info.liquidity_net.checked_add(liquidity_net).ok_or(eyre!("LO"))?;
+
+    if flipped {
+        let (word_pos, bit_pos) = uniswap_v3_math::tick_bitmap::position(tick / tick_spacing as i32);
+        // A word missing from the fetched window would hide the other ticks in it
+        let word = state.tick_bitmap.get_mut(&word_pos).ok_or(eyre!("TICK_BITMAP_WORD_NOT_LOADED {word_pos}"))?;
+        *word ^= U256::from(1) << bit_pos;
+    }
+    Ok(())
+}
+
+/// Swaps against the pool and returns the fees earned by `liquidity` in [tick_lower, tick_upper), in token_in.
+///
+/// The position must already be part of the state, see [`mint`].
+pub fn swap_fees(
+    amount_in: U256,
+    tok_in: Address,
+    pool_data: PoolData,
+    pool_state: PoolState<'_>,
+    tick_lower: i32,
+    tick_upper: i32,
+    liquidity: u128,
+) -> eyre::Result<(SwapResult, U256)> {
+    let sqrt_price_lower = get_sqrt_ratio_at_tick(tick_lower)?;
+    let sqrt_price_upper = get_sqrt_ratio_at_tick(tick_upper)?;
+    let zero_for_one = tok_in == pool_data.tok0;
+    let protocol_fee = pool_state.slot0.protocol_fee(zero_for_one);
+
+    // Steps never cross an initialized tick, so each one is either fully inside or fully outside the range
+    let mut fee_growth_inside_x128 = U256::ZERO;
+    let mut error = None;
+    let swap = local::swap(amount_in, tok_in, None, pool_data, pool_state, |step, sqrt_price_x96, step_liquidity| {
+        let (start, end) = (step.sqrt_price_start_x_96.min(sqrt_price_x96), step.sqrt_price_start_x_96.max(sqrt_price_x96));
+        if step_liquidity == 0 || start < sqrt_price_lower || end > sqrt_price_upper {
+            return;
+        }
+        let fee_amount = step.fee_amount - protocol_fee.amount(step.fee_amount);
+        match mul_div(fee_amount, Q128, U256::from(step_liquidity)) {
+            Ok(growth) => fee_growth_inside_x128 += growth,
+            Err(e) => error = Some(e),
+        }
+    })?;
+    if let Some(e) = error {
+        return Err(e.into());
+    }
+
+    let fees = mul_div(fee_growth_inside_x128, U256::from(liquidity), Q128)?;
+    Ok((swap, fees))
+}
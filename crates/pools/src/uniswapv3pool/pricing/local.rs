@@ -5,7 +5,8 @@ use serde::{Deserialize, Serialize};
 use tracing::instrument;
 use uniswap_v3_math::tick_math::{MAX_SQRT_RATIO, MAX_TICK, MIN_SQRT_RATIO, MIN_TICK};
 use crate::uniswapv3pool::slot0::Slot0;
-use crate::uniswapv3pool::{UniswapV3PoolState};
+use crate::uniswapv3pool::ticks::Ticks;
+use crate::uniswapv3pool::UniswapV3PoolState;
 
 pub const U256_1: U256 = U256::from_limbs([1, 0, 0, 0]);
 
@@ -40,58 +41,106 @@ pub struct Tick {
     pub initialized: bool,
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TickInfo {
     pub liquidity_gross: u128,
     pub liquidity_net: i128,
+    /// Fee growth per unit of liquidity on the other side of the tick, relative to the current tick.
+    pub fee_growth_outside_0_x128: U256,
+    pub fee_growth_outside_1_x128: U256,
 }
 
 /// Constant pool data.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct PoolData {
     pub tok0: Address,
     pub fee: u32,
     pub tick_spacing: u32,
 }
 
-/// Ephemeral pool data.
-#[derive(Debug)]
-pub struct PoolState {
-    pub slot0: Slot0,
+/// Ephemeral pool data, borrowed from the pool so quoting does not copy the tick maps.
+#[derive(Debug, Clone, Copy)]
+pub struct PoolState<'a> {
+    pub slot0: &'a Slot0,
     pub liquidity: u128,
-    pub tick_bitmap: HashMap<i16, U256>,
-    pub ticks: HashMap<i32, TickInfo>,
+    pub tick_bitmap: &'a HashMap<i16, U256>,
+    pub ticks: &'a Ticks,
 }
 
-impl From<UniswapV3PoolState> for PoolState {
-    fn from(val: UniswapV3PoolState) -> Self {
+impl<'a> From<&'a UniswapV3PoolState> for PoolState<'a> {
+    fn from(val: &'a UniswapV3PoolState) -> Self {
         Self {
-            slot0: val.slot0,
+            slot0: &val.slot0,
             liquidity: val.liquidity,
-            tick_bitmap: val.tick_bitmap,
-            ticks: val.ticks,
+            tick_bitmap: &val.tick_bitmap,
+            ticks: &val.ticks,
         }
     }
 }
 
+/// Result of a simulated exact input swap.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SwapResult {
+    /// Amount of token_in consumed, fee included. Lower than the requested amount when the price limit is hit.
+    pub amount_in: U256,
+    pub amount_out: U256,
+    /// Pool price, tick and active liquidity after the swap.
+    pub sqrt_price_x96: U256,
+    pub tick: i32,
+    pub liquidity: u128,
+}
+
 #[instrument(level = "debug", ret)]
 pub fn calc_amount_out(
     amount_in: U256,
     tok_in: Address,
+    sqrt_price_limit_x96: Option<U256>,
     pool_data: PoolData,
-    pool_state: PoolState,
-) -> eyre::Result<U256> {
+    pool_state: PoolState<'_>,
+) -> eyre::Result<SwapResult> {
+    swap(amount_in, tok_in, sqrt_price_limit_x96, pool_data, pool_state, |_, _, _| {})
+}
+
+/// Same as [`calc_amount_out`], calling `on_step` with each step, the price after it and the liquidity it used.
+#[instrument(level = "debug", skip(on_step), ret)]
+pub fn swap<F: FnMut(&StepComputations, U256, u128)>(
+    amount_in: U256,
+    tok_in: Address,
+    sqrt_price_limit_x96: Option<U256>,
+    pool_data: PoolData,
+    pool_state: PoolState<'_>,
+    mut on_step: F,
+) -> eyre::Result<SwapResult> {
     if amount_in.is_zero() {
-        return Ok(U256::ZERO);
+        return Ok(SwapResult {
+            sqrt_price_x96: pool_state.slot0.sqrt_price_x96,
+            tick: pool_state.slot0.tick,
+            liquidity: pool_state.liquidity,
+            ..Default::default()
+        });
+    }
+
+    if pool_data.tick_spacing == 0 {
+        return Err(eyre!("ZERO_TICK_SPACING"));
     }
 
     let zero_for_one = tok_in == pool_data.tok0;
 
-    // Set sqrt_price_limit_x_96 to the max or min sqrt price in the pool depending on zero_for_one
-    let sqrt_price_limit_x_96 = if zero_for_one {
-        MIN_SQRT_RATIO + U256_1
-    } else {
-        MAX_SQRT_RATIO - U256_1
+    // Use the given limit or the max or min sqrt price in the pool depending on zero_for_one
+    let sqrt_price_limit_x_96 = match sqrt_price_limit_x96 {
+        Some(limit) => {
+            let valid = if zero_for_one {
+                limit < pool_state.slot0.sqrt_price_x96 && limit > MIN_SQRT_RATIO
+            } else {
+                limit > pool_state.slot0.sqrt_price_x96 && limit < MAX_SQRT_RATIO
+            };
+            if !valid {
+                return Err(eyre!("SPL"));
+            }
+            limit
+        }
+        None if zero_for_one => MIN_SQRT_RATIO + U256_1,
+        None => MAX_SQRT_RATIO - U256_1,
     };
 
     // Initialize a mutable state struct to hold the dynamic simulated state of the pool
@@ -118,7 +167,7 @@ pub fn calc_amount_out(
             uniswap_v3_math::tick_bitmap::next_initialized_tick_within_one_word(
                 &pool_state.tick_bitmap,
                 current_state.tick,
-                pool_data.tick_spacing.try_into().unwrap(),
+                pool_data.tick_spacing.try_into()?,
                 zero_for_one,
             )?;
 
@@ -156,6 +205,7 @@ pub fn calc_amount_out(
             current_state.amount_specified_remaining,
             pool_data.fee,
         )?;
+        on_step(&step, current_state.sqrt_price_x_96, current_state.liquidity);
 
         // Decrement the amount remaining to be swapped and amount received from the step
         current_state.amount_specified_remaining = current_state
@@ -170,7 +220,7 @@ pub fn calc_amount_out(
         // If the price moved all the way to the next price, recompute the liquidity change for the next iteration
         if current_state.sqrt_price_x_96 == step.sqrt_price_next_x96 {
             if step.initialized {
-                let mut liquidity_net = if let Some(info) = pool_state.ticks.get(&step.tick_next) {
+                let mut liquidity_net = if let Some(info) = pool_state.ticks.get(step.tick_next) {
                     info.liquidity_net
                 } else {
                     0
@@ -206,12 +256,19 @@ pub fn calc_amount_out(
         }
     }
 
-    if current_state.amount_specified_remaining.is_zero() {
-        let amount_out = (-current_state.amount_calculated).into_raw();
-        tracing::trace!("amount_out : {amount_out}");
-        Ok(amount_out)
-    } else {
-        Err(eyre!("NOT_ENOUGH_LIQUIDITY"))
+    // Without an explicit limit a partial fill means the pool ran out of liquidity
+    if !current_state.amount_specified_remaining.is_zero() && sqrt_price_limit_x96.is_none() {
+        return Err(eyre!("NOT_ENOUGH_LIQUIDITY"));
     }
+
+    let amount_out = (-current_state.amount_calculated).into_raw();
+    tracing::trace!("amount_out : {amount_out}");
+    Ok(SwapResult {
+        amount_in: amount_in - current_state.amount_specified_remaining.into_raw(),
+        amount_out,
+        sqrt_price_x96: current_state.sqrt_price_x_96,
+        tick: current_state.tick,
+        liquidity: current_state.liquidity,
+    })
 }
 
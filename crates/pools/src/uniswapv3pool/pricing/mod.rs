@@ -5,11 +5,12 @@ use crate::uniswapv3pool::UniswapV3PoolData;
 mod tests;
 pub mod abi;
 pub mod local;
+pub mod path;
 pub mod quoter;
 pub mod quoter2;
 
-impl From<UniswapV3PoolData> for PoolData {
-    fn from(val: UniswapV3PoolData) -> Self {
+impl From<&UniswapV3PoolData> for PoolData {
+    fn from(val: &UniswapV3PoolData) -> Self {
         Self {
             tok0: val.tok0,
             fee: val.fee,
@@ -0,0 +1,37 @@
+use alloy::primitives::{Address, Bytes};
+use eyre::eyre;
+
+/// Encodes the packed `token | fee | token | fee | token` path used by the V3 router and QuoterV2.
+pub fn encode_path(tokens: &[Address], fees: &[u32]) -> eyre::Result<Bytes> {
+    if tokens.len() < 2 || tokens.len() != fees.len() + 1 {
+        return Err(eyre!("INVALID_PATH_LENGTH"));
+    }
+
+    let mut path = Vec::with_capacity(20 * tokens.len() + 3 * fees.len());
+    for (tok, fee) in tokens.iter().zip(fees.iter()) {
+        if *fee >= 1 << 24 {
+            return Err(eyre!("INVALID_FEE {fee}"));
+        }
+        path.extend_from_slice(tok.as_slice());
+        path.extend_from_slice(&fee.to_be_bytes()[1..]);
+    }
+    path.extend_from_slice(tokens[tokens.len() - 1].as_slice());
+
+    Ok(path.into())
+}
+
+/// Splits a packed path back into its tokens and fees.
+pub fn decode_path(path: &[u8]) -> eyre::Result<(Vec<Address>, Vec<u32>)> {
+    if path.len() < 43 || (path.len() - 20) % 23 != 0 {
+        return Err(eyre!("INVALID_PATH_LENGTH"));
+    }
+
+    let mut tokens = vec![Address::from_slice(&path[..20])];
+    let mut fees = Vec::new();
+    for hop in path[20..].chunks(23) {
+        fees.push(u32::from_be_bytes([0, hop[0], hop[1], hop[2]]));
+        tokens.push(Address::from_slice(&hop[3..]));
+    }
+
+    Ok((tokens, fees))
+}
@@ -14,6 +14,7 @@ pub async fn calc_amount_out<T: Transport + Clone, N: Network, P: Provider<T, N>
     tok_in: Address,
     tok_out: Address,
     fee: u32,
+    sqrt_price_limit_x96: Option<U160>,
     provider: P,
     block: BlockId,
 ) -> eyre::Result<U256> {
@@ -24,7 +25,7 @@ pub async fn calc_amount_out<T: Transport + Clone, N: Network, P: Provider<T, N>
             tok_out,
             U24::from(fee),
             amount_in,
-            U160::ZERO,
+            sqrt_price_limit_x96.unwrap_or(U160::ZERO),
         )
         .block(block)
         .call()
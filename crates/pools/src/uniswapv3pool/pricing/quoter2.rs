@@ -1,12 +1,21 @@
 use alloy::eips::BlockId;
 use alloy::network::Network;
-use alloy::primitives::{Address, U160, U256};
+use alloy::primitives::{Address, Bytes, U160, U256};
 use alloy::primitives::aliases::U24;
 use alloy::providers::Provider;
 use alloy::transports::Transport;
 use tracing::instrument;
 use crate::uniswapv3pool::pricing::abi::IQuoterV2;
 
+/// QuoterV2 exact input output, with the pool price the swap stopped at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quote {
+    pub amount_out: U256,
+    /// Equals the price limit when it cut the swap short.
+    pub sqrt_price_x96_after: U256,
+    pub initialized_ticks_crossed: u32,
+}
+
 #[instrument(skip(provider), level = "debug", ret)]
 pub async fn calc_amount_out<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
     quoter_addr: Address,
@@ -14,25 +23,94 @@ pub async fn calc_amount_out<T: Transport + Clone, N: Network, P: Provider<T, N>
     tok_in: Address,
     tok_out: Address,
     fee: u32,
+    sqrt_price_limit_x96: Option<U160>,
     provider: P,
     block: BlockId,
 ) -> eyre::Result<U256> {
+    let quote = quote_exact_input_single(
+        quoter_addr,
+        amount_in,
+        tok_in,
+        tok_out,
+        fee,
+        sqrt_price_limit_x96,
+        provider,
+        block,
+    ).await?;
+
+    Ok(quote.amount_out)
+}
+
+/// Same as [`calc_amount_out`], keeping the price after the swap to tell whether the limit was hit.
+#[instrument(skip(provider), level = "debug", ret)]
+pub async fn quote_exact_input_single<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+    quoter_addr: Address,
+    amount_in: U256,
+    tok_in: Address,
+    tok_out: Address,
+    fee: u32,
+    sqrt_price_limit_x96: Option<U160>,
+    provider: P,
+    block: BlockId,
+) -> eyre::Result<Quote> {
     let quoter = IQuoterV2::new(quoter_addr, provider.clone());
-    let amount_out = quoter
+    let ret = quoter
         .quoteExactInputSingle(
             IQuoterV2::QuoteExactInputSingleParams {
                 tokenIn: tok_in,
                 tokenOut: tok_out,
                 amountIn: amount_in,
                 fee: U24::from(fee),
-                sqrtPriceLimitX96: U160::ZERO,
+                sqrtPriceLimitX96: sqrt_price_limit_x96.unwrap_or(U160::ZERO),
             }
         )
         .block(block)
         .call()
+        .await?;
+
+    Ok(Quote {
+        amount_out: ret.amountOut,
+        sqrt_price_x96_after: U256::from(ret.sqrtPriceX96After),
+        initialized_ticks_crossed: ret.initializedTicksCrossed,
+    })
+}
+
+/// Quotes an exact input swap along a path built with [`super::path::encode_path`].
+#[instrument(skip(provider), level = "debug", ret)]
+pub async fn calc_amount_out_path<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+    quoter_addr: Address,
+    amount_in: U256,
+    path: Bytes,
+    provider: P,
+    block: BlockId,
+) -> eyre::Result<U256> {
+    let quoter = IQuoterV2::new(quoter_addr, provider.clone());
+    let amount_out = quoter
+        .quoteExactInput(path, amount_in)
+        .block(block)
+        .call()
         .await?
         .amountOut;
 
     Ok(amount_out)
 }
 
+/// Quotes an exact output swap along a path encoded from token_out to token_in.
+#[instrument(skip(provider), level = "debug", ret)]
+pub async fn calc_amount_in_path<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+    quoter_addr: Address,
+    amount_out: U256,
+    path: Bytes,
+    provider: P,
+    block: BlockId,
+) -> eyre::Result<U256> {
+    let quoter = IQuoterV2::new(quoter_addr, provider.clone());
+    let amount_in = quoter
+        .quoteExactOutput(path, amount_out)
+        .block(block)
+        .call()
+        .await?
+        .amountIn;
+
+    Ok(amount_in)
+}
@@ -46,9 +46,10 @@ async fn test_calculate_amount_out() {
     let amount_out_local = local::calc_amount_out(
         amount_in,
         tok_in,
-        pool.data.clone().into(),
-        pool.state.clone().into(),
-    ).unwrap();
+        None,
+        (&pool.data).into(),
+        (&pool.state).into(),
+    ).unwrap().amount_out;
     info!(?amount_out_local);
 
     let amount_out_quoter = quoter::calc_amount_out(
@@ -57,6 +58,7 @@ async fn test_calculate_amount_out() {
         tok_in,
         tok_out,
         pool.data.fee,
+        None,
         provider.clone(),
         block.into(),
     )
@@ -70,6 +72,7 @@ async fn test_calculate_amount_out() {
         tok_in,
         tok_out,
         pool.data.fee,
+        None,
         provider.clone(),
         block.into(),
     )
@@ -80,3 +83,184 @@ async fn test_calculate_amount_out() {
     assert_eq!(amount_out_quoter, amount_out_quoter2);
     assert_eq!(amount_out_quoter, amount_out_local); // FAILS
 }
+
+#[tokio::test]
+async fn test_calculate_amount_out_price_limit() {
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .try_init();
+
+    let cfg = Config::default();
+    let net = config::Network::Ethereum;
+    let provider = cfg.providers.get(&net).unwrap().clone();
+    let addresses = cfg.addresses.get(&net).unwrap().clone();
+    let provider = Arc::new(ProviderBuilder::new().on_http(provider.api.parse().unwrap()));
+
+    let block = provider
+        .get_block_number()
+        .await
+        .unwrap();
+
+    let mut pool = UniswapV3Pool::new(
+        addresses.uniswap_v3.pools.get("USDC_WETH").unwrap().clone(),
+        addresses.uniswap_v3.periphery.clone());
+    pool.sync(provider.clone(), block.into()).await.unwrap();
+
+    // 10M USDC moves the price well past a 0.1% limit
+    let amount_in = U256::from(10_000_000u64) * U256::from(10).pow(U256::from(6));
+    let tok_in = pool.data.tok0;
+    let tok_out = pool.get_other_token(tok_in);
+    let sqrt_price_limit = pool.state.slot0.sqrt_price_x96 * U256::from(9995) / U256::from(10000);
+
+    let swap_local = local::calc_amount_out(
+        amount_in,
+        tok_in,
+        Some(sqrt_price_limit),
+        (&pool.data).into(),
+        (&pool.state).into(),
+    ).unwrap();
+    info!(?swap_local);
+    assert!(swap_local.amount_in < amount_in);
+    assert_eq!(swap_local.sqrt_price_x96, sqrt_price_limit);
+
+    let quote = quoter2::quote_exact_input_single(
+        pool.metadata.periphery.quoter_v2.clone(),
+        amount_in,
+        tok_in,
+        tok_out,
+        pool.data.fee,
+        Some(sqrt_price_limit.to()),
+        provider.clone(),
+        block.into(),
+    )
+        .await
+        .unwrap();
+    info!(?quote);
+
+    assert_eq!(quote.amount_out, swap_local.amount_out);
+    assert_eq!(quote.sqrt_price_x96_after, swap_local.sqrt_price_x96);
+}
+
+#[test]
+fn test_encode_path() {
+    use alloy::primitives::{address, hex};
+
+    let usdc = address!("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+    let weth = address!("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+    let dai = address!("6b175474e89094c44da98b954eedeac495271d0f");
+
+    let encoded = path::encode_path(&[usdc, weth, dai], &[500, 3000]).unwrap();
+    assert_eq!(
+        encoded.to_vec(),
+        hex!("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb480001f4c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2000bb86b175474e89094c44da98b954eedeac495271d0f").to_vec(),
+    );
+    assert_eq!(path::decode_path(&encoded).unwrap(), (vec![usdc, weth, dai], vec![500, 3000]));
+    assert!(path::encode_path(&[usdc, weth], &[500, 3000]).is_err());
+}
+
+/// Replays the snapshots recorded with `cargo run --example uniswapv3pool_snapshot` offline.
+#[test]
+fn test_conformance_snapshots() {
+    use std::fs;
+    use uniswap_v3_math::tick_math::{MAX_SQRT_RATIO, MIN_SQRT_RATIO};
+    use crate::uniswapv3pool::snapshot::UniswapV3PoolSnapshot;
+
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/uniswapv3");
+    let mut checked = 0;
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().map_or(true, |ext| ext != "json") {
+            continue;
+        }
+        let snapshot = UniswapV3PoolSnapshot::load(&path).unwrap();
+        let pool = snapshot.to_pool();
+
+        for quote in snapshot.quotes.iter() {
+            // QuoterV2 swaps up to the pool bounds and returns the partial fill, so does the local swap with the same limit
+            let limit = if quote.tok_in == pool.data.tok0 { MIN_SQRT_RATIO + U256::from(1) } else { MAX_SQRT_RATIO - U256::from(1) };
+            let local = pool.calc_swap(quote.amount_in, quote.tok_in, Some(limit));
+            match quote.amount_out {
+                Some(amount_out) => assert_eq!(
+                    local.unwrap().amount_out,
+                    amount_out,
+                    "{} block {} tok_in {} amount_in {}",
+                    snapshot.name, snapshot.block, quote.tok_in, quote.amount_in,
+                ),
+                None => assert!(
+                    local.map_or(true, |swap| swap.amount_out.is_zero()),
+                    "{} block {} tok_in {} amount_in {} reverted on chain",
+                    snapshot.name, snapshot.block, quote.tok_in, quote.amount_in,
+                ),
+            }
+            checked += 1;
+        }
+    }
+    info!(checked, "conformance quotes");
+    assert!(checked > 0, "no snapshot in {dir}, record them with `cargo run --example uniswapv3pool_snapshot`");
+}
+
+#[test]
+fn test_zero_liquidity() {
+    let pool = synthetic_pool(0, &[]);
+    let tok_in = pool.data.tok0;
+    assert!(pool.calc_swap(U256::from(1000), tok_in, None).is_err());
+
+    let limit = uniswap_v3_math::tick_math::get_sqrt_ratio_at_tick(-600).unwrap();
+    let swap = pool.calc_swap(U256::from(1000), tok_in, Some(limit)).unwrap();
+    assert_eq!(swap.amount_in, U256::ZERO);
+    assert_eq!(swap.amount_out, U256::ZERO);
+    assert_eq!(swap.sqrt_price_x96, limit);
+}
+
+#[test]
+fn test_min_max_tick() {
+    use uniswap_v3_math::tick_math::MAX_TICK;
+
+    // Full range position on the outermost spaced ticks
+    let max_tick = MAX_TICK / 60 * 60;
+    let liquidity = 10u128.pow(18);
+    let pool = synthetic_pool(liquidity, &[(-max_tick, liquidity as i128), (max_tick, -(liquidity as i128))]);
+
+    // Draining either side runs out of liquidity at the bounds
+    let huge = U256::from(10).pow(U256::from(40));
+    assert!(pool.calc_swap(huge, pool.data.tok0, None).is_err());
+    assert!(pool.calc_swap(huge, pool.data.tok1, None).is_err());
+    assert!(pool.calc_swap(U256::from(10).pow(U256::from(15)), pool.data.tok0, None).is_ok());
+}
+
+#[test]
+fn test_exact_tick_landing() {
+    use uniswap_v3_math::full_math::mul_div_rounding_up;
+    use uniswap_v3_math::sqrt_price_math::{_get_amount_0_delta, _get_amount_1_delta};
+    use uniswap_v3_math::tick_math::get_sqrt_ratio_at_tick;
+
+    let liquidity = 10u128.pow(18);
+    // [-60, 60) with L and [-120, -60) with 2L, the swap stops right on the initialized tick -60
+    let pool = synthetic_pool(liquidity, &[(-120, 2 * liquidity as i128), (-60, -(liquidity as i128)), (60, -(liquidity as i128))]);
+    let sqrt_price = pool.state.slot0.sqrt_price_x96;
+    let sqrt_price_target = get_sqrt_ratio_at_tick(-60).unwrap();
+
+    // Exactly the input needed to reach tick -60, fee included
+    let amount = _get_amount_0_delta(sqrt_price_target, sqrt_price, liquidity, true).unwrap();
+    let fee = mul_div_rounding_up(amount, U256::from(pool.data.fee), U256::from(1_000_000 - pool.data.fee)).unwrap();
+    let swap = pool.calc_swap(amount + fee, pool.data.tok0, Some(sqrt_price_target)).unwrap();
+
+    assert_eq!(swap.sqrt_price_x96, sqrt_price_target);
+    assert_eq!(swap.amount_in, amount + fee);
+    assert_eq!(swap.amount_out, _get_amount_1_delta(sqrt_price_target, sqrt_price, liquidity, false).unwrap());
+}
+
+/// 0.3% pool at tick 0 with the given liquidity net per tick.
+fn synthetic_pool(liquidity: u128, ticks: &[(i32, i128)]) -> UniswapV3Pool {
+    use alloy::primitives::Address;
+    use crate::uniswapv3pool::pricing::local::TickInfo;
+
+    let mut pool = UniswapV3Pool::synthetic(Address::repeat_byte(0), Address::repeat_byte(1), 3000);
+    pool.state.liquidity = liquidity;
+    for (tick, liquidity_net) in ticks {
+        let (word_pos, bit_pos) = uniswap_v3_math::tick_bitmap::position(tick / 60);
+        *pool.state.tick_bitmap.entry(word_pos).or_default() |= U256::from(1) << bit_pos;
+        pool.state.ticks.insert(*tick, TickInfo { liquidity_gross: liquidity_net.unsigned_abs(), liquidity_net: *liquidity_net, ..Default::default() });
+    }
+    pool
+}
@@ -0,0 +1,91 @@
+use alloy::eips::BlockId;
+use alloy::network::Network;
+use alloy::primitives::{Address, Bytes, U256};
+use alloy::providers::Provider;
+use alloy::transports::Transport;
+use eyre::eyre;
+use tracing::instrument;
+use crate::uniswapv3pool::pricing::{path, quoter2};
+use crate::uniswapv3pool::UniswapV3Pool;
+
+/// Local and QuoterV2 results for the same multi-hop route.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteQuote {
+    pub path: Bytes,
+    pub amount_in: U256,
+    pub amount_out_local: U256,
+    pub amount_out_quoter: U256,
+}
+
+impl RouteQuote {
+    pub fn matches(&self) -> bool {
+        self.amount_out_local == self.amount_out_quoter
+    }
+}
+
+/// Tokens visited by the route starting from tok_in.
+pub fn route_tokens(pools: &[&UniswapV3Pool], tok_in: Address) -> eyre::Result<Vec<Address>> {
+    if pools.is_empty() {
+        return Err(eyre!("EMPTY_ROUTE"));
+    }
+
+    let mut tokens = Vec::with_capacity(pools.len() + 1);
+    let mut tok = tok_in;
+    tokens.push(tok);
+    for pool in pools {
+        if tok != pool.data.tok0 && tok != pool.data.tok1 {
+            return Err(eyre!("TOKEN_NOT_IN_POOL {tok} {}", pool.metadata.pool));
+        }
+        tok = pool.get_other_token(tok);
+        tokens.push(tok);
+    }
+    Ok(tokens)
+}
+
+/// Packed QuoterV2 path for the route.
+pub fn route_path(pools: &[&UniswapV3Pool], tok_in: Address) -> eyre::Result<Bytes> {
+    let tokens = route_tokens(pools, tok_in)?;
+    let fees: Vec<u32> = pools.iter().map(|pool| pool.data.fee).collect();
+    path::encode_path(&tokens, &fees)
+}
+
+/// Chains the local exact input swaps along the route.
+pub fn calc_amount_out(pools: &[&UniswapV3Pool], amount_in: U256, tok_in: Address) -> eyre::Result<U256> {
+    let tokens = route_tokens(pools, tok_in)?;
+    let mut amount = amount_in;
+    for (pool, tok) in pools.iter().zip(tokens.iter()) {
+        amount = pool.calc_swap(amount, *tok, None)?.amount_out;
+    }
+    Ok(amount)
+}
+
+/// Quotes the route with QuoterV2 at `block` and locally on the pools' current state.
+///
+/// The two only compare when the pools were synced at `block` too, nothing here syncs them.
+/// QuoterV2 resolves pools from its factory by tokens and fee, so the route must only use canonical pools of that factory.
+#[instrument(skip(pools, provider), level = "debug", ret)]
+pub async fn quote_route<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+    quoter_v2: Address,
+    pools: &[&UniswapV3Pool],
+    amount_in: U256,
+    tok_in: Address,
+    provider: P,
+    block: BlockId,
+) -> eyre::Result<RouteQuote> {
+    let path = route_path(pools, tok_in)?;
+    let amount_out_local = calc_amount_out(pools, amount_in, tok_in)?;
+    let amount_out_quoter = quoter2::calc_amount_out_path(
+        quoter_v2,
+        amount_in,
+        path.clone(),
+        provider,
+        block,
+    ).await?;
+
+    Ok(RouteQuote {
+        path,
+        amount_in,
+        amount_out_local,
+        amount_out_quoter,
+    })
+}
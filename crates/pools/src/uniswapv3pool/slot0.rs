@@ -1,11 +1,15 @@
 use alloy::primitives::U256;
+use crate::uniswapv3pool::abi::IPancakeV3Pool;
 use crate::uniswapv3pool::abi::IUniswapV3Pool::slot0Return;
 
 #[allow(dead_code)]
 #[derive(Clone, Debug, Default)]
 pub struct Slot0 {
     pub tick: i32,
-    pub fee_protocol: u8,
+    /// Raw feeProtocol, its layout depends on the fork.
+    pub fee_protocol: u32,
+    /// Protocol share of the swap fee for token0 and token1 inputs, normalized across forks.
+    pub protocol_fee: [ProtocolFee; 2],
     pub sqrt_price_x96: U256,
     pub unlocked: bool,
     pub observation_index: u16,
@@ -13,12 +17,65 @@ pub struct Slot0 {
     pub observation_cardinality_next: u16,
 }
 
+/// Protocol cut of a swap step fee, `fee_amount * numerator / denominator` rounded down.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProtocolFee {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl ProtocolFee {
+    pub fn amount(&self, fee_amount: U256) -> U256 {
+        if self.numerator == 0 || self.denominator == 0 {
+            return U256::ZERO;
+        }
+        fee_amount * U256::from(self.numerator) / U256::from(self.denominator)
+    }
+}
+
+impl Slot0 {
+    pub fn protocol_fee(&self, zero_for_one: bool) -> ProtocolFee {
+        self.protocol_fee[if zero_for_one { 0 } else { 1 }]
+    }
+
+    /// Uniswap V3 packs `1 / n` per token in the two nibbles of a uint8.
+    pub fn uniswap_protocol_fee(fee_protocol: u8) -> [ProtocolFee; 2] {
+        [
+            ProtocolFee { numerator: 1, denominator: (fee_protocol % 16) as u32 },
+            ProtocolFee { numerator: 1, denominator: (fee_protocol >> 4) as u32 },
+        ]
+    }
+
+    /// PancakeSwap V3 packs a share over 10000 per token in the two halves of a uint32.
+    pub fn pancakeswap_protocol_fee(fee_protocol: u32) -> [ProtocolFee; 2] {
+        [
+            ProtocolFee { numerator: fee_protocol % 65536, denominator: 10000 },
+            ProtocolFee { numerator: fee_protocol >> 16, denominator: 10000 },
+        ]
+    }
+}
 
 impl From<slot0Return> for Slot0 {
     fn from(value: slot0Return) -> Self {
+        Self {
+            tick: value.tick.try_into().unwrap(),
+            fee_protocol: value.feeProtocol as u32,
+            protocol_fee: Slot0::uniswap_protocol_fee(value.feeProtocol),
+            observation_cardinality: value.observationCardinality,
+            observation_cardinality_next: value.observationCardinalityNext,
+            sqrt_price_x96: value.sqrtPriceX96.to(),
+            unlocked: value.unlocked,
+            observation_index: value.observationIndex,
+        }
+    }
+}
+
+impl From<IPancakeV3Pool::slot0Return> for Slot0 {
+    fn from(value: IPancakeV3Pool::slot0Return) -> Self {
         Self {
             tick: value.tick.try_into().unwrap(),
             fee_protocol: value.feeProtocol,
+            protocol_fee: Slot0::pancakeswap_protocol_fee(value.feeProtocol),
             observation_cardinality: value.observationCardinality,
             observation_cardinality_next: value.observationCardinalityNext,
             sqrt_price_x96: value.sqrtPriceX96.to(),
@@ -26,4 +83,4 @@ impl From<slot0Return> for Slot0 {
             observation_index: value.observationIndex,
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,166 @@
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use alloy::eips::BlockId;
+use alloy::network::Network;
+use alloy::primitives::{Address, U256};
+use alloy::providers::Provider;
+use alloy::transports::Transport;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use tracing::{instrument, warn};
+use crate::uniswapv3pool::pricing::local::TickInfo;
+use crate::uniswapv3pool::pricing::quoter2;
+use crate::uniswapv3pool::slot0::Slot0;
+use crate::uniswapv3pool::{UniswapV3Pool, UniswapV3PoolData, UniswapV3PoolState};
+
+/// A pool state recorded at a block, with the QuoterV2 outputs at the same block.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniswapV3PoolSnapshot {
+    pub name: String,
+    pub block: u64,
+    pub pool: Address,
+    pub tok0: Address,
+    pub tok1: Address,
+    pub fee: u32,
+    pub tick_spacing: u32,
+    pub sqrt_price_x96: U256,
+    pub tick: i32,
+    #[serde(with = "as_string")]
+    pub liquidity: u128,
+    pub tick_bitmap: Vec<(i16, U256)>,
+    pub ticks: Vec<SnapshotTick>,
+    pub quotes: Vec<RecordedQuote>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotTick {
+    pub tick: i32,
+    #[serde(with = "as_string")]
+    pub liquidity_gross: u128,
+    #[serde(with = "as_string")]
+    pub liquidity_net: i128,
+}
+
+/// QuoterV2 exact input output, `None` when the quote reverted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedQuote {
+    pub tok_in: Address,
+    pub amount_in: U256,
+    pub amount_out: Option<U256>,
+}
+
+impl UniswapV3PoolSnapshot {
+    pub fn from_pool(name: String, block: u64, pool: &UniswapV3Pool) -> Self {
+        let mut tick_bitmap: Vec<(i16, U256)> = pool.state.tick_bitmap.iter().map(|(w, v)| (*w, *v)).collect();
+        tick_bitmap.sort_unstable_by_key(|(w, _)| *w);
+
+        Self {
+            name,
+            block,
+            pool: pool.metadata.pool,
+            tok0: pool.data.tok0,
+            tok1: pool.data.tok1,
+            fee: pool.data.fee,
+            tick_spacing: pool.data.tick_spacing,
+            sqrt_price_x96: pool.state.slot0.sqrt_price_x96,
+            tick: pool.state.slot0.tick,
+            liquidity: pool.state.liquidity,
+            tick_bitmap,
+            ticks: pool.state.ticks.iter().map(|(tick, info)| SnapshotTick {
+                tick: *tick,
+                liquidity_gross: info.liquidity_gross,
+                liquidity_net: info.liquidity_net,
+            }).collect(),
+            quotes: Vec::new(),
+        }
+    }
+
+    /// Rebuilds an offline pool, without periphery.
+    pub fn to_pool(&self) -> UniswapV3Pool {
+        let mut pool = UniswapV3Pool::new(
+            self.pool,
+            config::UniswapV3Periphery { quoter: Address::ZERO, quoter_v2: Address::ZERO, tick_lens: None },
+        );
+        pool.data = UniswapV3PoolData {
+            factory: Address::ZERO,
+            tok0: self.tok0,
+            tok1: self.tok1,
+            fee: self.fee,
+            tick_spacing: self.tick_spacing,
+        };
+        pool.state = UniswapV3PoolState {
+            slot0: Slot0 { sqrt_price_x96: self.sqrt_price_x96, tick: self.tick, unlocked: true, ..Default::default() },
+            liquidity: self.liquidity,
+            tick_bitmap: self.tick_bitmap.iter().copied().collect(),
+            ticks: self.ticks.iter().map(|t| (t.tick, TickInfo {
+                liquidity_gross: t.liquidity_gross,
+                liquidity_net: t.liquidity_net,
+                ..Default::default()
+            })).collect(),
+        };
+        pool
+    }
+
+    /// Syncs the full tick range of the pool from storage and quotes `amounts` in both directions with QuoterV2.
+    #[instrument(skip(provider), level = "debug")]
+    pub async fn record<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        name: String,
+        pool: Address,
+        periphery: config::UniswapV3Periphery,
+        amounts: &[U256],
+        provider: P,
+        block: u64,
+    ) -> eyre::Result<Self> {
+        let mut v3_pool = UniswapV3Pool::new(pool, periphery);
+        v3_pool.data = UniswapV3Pool::fetch_data(&v3_pool.metadata, provider.clone(), block.into()).await?;
+        v3_pool.state = UniswapV3Pool::fetch_state_storage_full(&v3_pool.metadata, &v3_pool.data, provider.clone(), block.into()).await?;
+
+        let mut snapshot = Self::from_pool(name, block, &v3_pool);
+        for tok_in in [v3_pool.data.tok0, v3_pool.data.tok1] {
+            for amount_in in amounts {
+                let amount_out = quoter2::calc_amount_out(
+                    v3_pool.metadata.periphery.quoter_v2,
+                    *amount_in,
+                    tok_in,
+                    v3_pool.get_other_token(tok_in),
+                    v3_pool.data.fee,
+                    None,
+                    provider.clone(),
+                    BlockId::from(block),
+                ).await;
+                if let Err(e) = &amount_out {
+                    warn!(?tok_in, ?amount_in, ?e, "quote reverted");
+                }
+                snapshot.quotes.push(RecordedQuote { tok_in, amount_in: *amount_in, amount_out: amount_out.ok() });
+            }
+        }
+        Ok(snapshot)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        let mut contents = fs::read(path)?;
+        Ok(simd_json::from_slice(&mut contents)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> eyre::Result<()> {
+        fs::write(path, simd_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// 128 bit integers as decimal strings, JSON numbers lose precision past 2^53.
+mod as_string {
+    use super::*;
+
+    pub fn serialize<T: Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T: FromStr, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error>
+    where
+        T::Err: Display,
+    {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
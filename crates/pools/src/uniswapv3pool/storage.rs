@@ -0,0 +1,142 @@
+use alloy::eips::BlockId;
+use alloy::network::Network;
+use alloy::primitives::{keccak256, Address, I256, U256};
+use alloy::providers::Provider;
+use alloy::transports::Transport;
+use lazy_static::lazy_static;
+use types::pool::PoolProtocol;
+use crate::uniswapv3pool::pricing::local::TickInfo;
+use crate::uniswapv3pool::slot0::Slot0;
+
+/// Storage slots of the pool contract, forks shift them around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorageLayout {
+    pub slot0: U256,
+    /// Words used by slot0, PancakeSwap V3 spills feeProtocol and unlocked into a second one.
+    pub slot0_words: usize,
+    pub fee_growth_global_0: U256,
+    pub fee_growth_global_1: U256,
+    pub liquidity: U256,
+    pub ticks: U256,
+    pub tick_bitmap: U256,
+}
+
+/// Storage layout of UniswapV3Pool.sol
+pub const UNISWAP_V3_LAYOUT: StorageLayout = StorageLayout {
+    slot0: U256::from_limbs([0, 0, 0, 0]),
+    slot0_words: 1,
+    fee_growth_global_0: U256::from_limbs([1, 0, 0, 0]),
+    fee_growth_global_1: U256::from_limbs([2, 0, 0, 0]),
+    liquidity: U256::from_limbs([4, 0, 0, 0]),
+    ticks: U256::from_limbs([5, 0, 0, 0]),
+    tick_bitmap: U256::from_limbs([6, 0, 0, 0]),
+};
+
+/// Storage layout of PancakeV3Pool.sol
+pub const PANCAKESWAP_V3_LAYOUT: StorageLayout = StorageLayout {
+    slot0: U256::from_limbs([0, 0, 0, 0]),
+    slot0_words: 2,
+    fee_growth_global_0: U256::from_limbs([2, 0, 0, 0]),
+    fee_growth_global_1: U256::from_limbs([3, 0, 0, 0]),
+    liquidity: U256::from_limbs([5, 0, 0, 0]),
+    ticks: U256::from_limbs([6, 0, 0, 0]),
+    tick_bitmap: U256::from_limbs([7, 0, 0, 0]),
+};
+
+pub fn layout(protocol: PoolProtocol) -> StorageLayout {
+    match protocol {
+        PoolProtocol::PancakeSwapV3 => PANCAKESWAP_V3_LAYOUT,
+        _ => UNISWAP_V3_LAYOUT,
+    }
+}
+
+/// Public endpoints reject oversized JSON-RPC batches.
+const STORAGE_BATCH_SIZE: usize = 100;
+
+lazy_static! {
+    static ref U128_MASK: U256 = (U256::from(1) << 128) - U256::from(1);
+    static ref U160_MASK: U256 = (U256::from(1) << 160) - U256::from(1);
+}
+
+/// Slot of `mapping(int24 => Tick.Info) ticks` for the given tick.
+pub fn tick_slot(layout: &StorageLayout, tick: i32) -> U256 {
+    mapping_slot(tick, layout.ticks)
+}
+
+/// Slot of `mapping(int16 => uint256) tickBitmap` for the given word.
+pub fn tick_bitmap_slot(layout: &StorageLayout, word_pos: i16) -> U256 {
+    mapping_slot(word_pos.into(), layout.tick_bitmap)
+}
+
+/// keccak256(abi.encode(key, slot)) with the signed key sign-extended to 32 bytes.
+pub(crate) fn mapping_slot(key: i32, slot: U256) -> U256 {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(&I256::try_from(key as i64).unwrap().to_be_bytes::<32>());
+    buf[32..].copy_from_slice(&slot.to_be_bytes::<32>());
+    U256::from_be_bytes(keccak256(buf).0)
+}
+
+/// Decodes the `layout.slot0_words` words read from `layout.slot0`.
+pub fn storage_to_slot0(layout: &StorageLayout, values: &[U256]) -> Slot0 {
+    let value = values[0];
+    let tick_raw = ((value >> 160) & U256::from(0xffffff)).to::<u32>();
+    let mut slot0 = Slot0 {
+        sqrt_price_x96: value & *U160_MASK,
+        // sign extend int24
+        tick: ((tick_raw << 8) as i32) >> 8,
+        observation_index: ((value >> 184) & U256::from(0xffff)).to::<u16>(),
+        observation_cardinality: ((value >> 200) & U256::from(0xffff)).to::<u16>(),
+        observation_cardinality_next: ((value >> 216) & U256::from(0xffff)).to::<u16>(),
+        ..Default::default()
+    };
+
+    if layout.slot0_words == 2 {
+        let fee_protocol = (values[1] & U256::from(0xffffffffu32)).to::<u32>();
+        slot0.fee_protocol = fee_protocol;
+        slot0.protocol_fee = Slot0::pancakeswap_protocol_fee(fee_protocol);
+        slot0.unlocked = ((values[1] >> 32) & U256::from(0xff)) != U256::ZERO;
+    } else {
+        let fee_protocol = ((value >> 232) & U256::from(0xff)).to::<u8>();
+        slot0.fee_protocol = fee_protocol as u32;
+        slot0.protocol_fee = Slot0::uniswap_protocol_fee(fee_protocol);
+        slot0.unlocked = ((value >> 240) & U256::from(0xff)) != U256::ZERO;
+    }
+    slot0
+}
+
+pub fn storage_to_liquidity(value: U256) -> u128 {
+    (value & *U128_MASK).to::<u128>()
+}
+
+/// Tick.Info starts with `uint128 liquidityGross` and `int128 liquidityNet` packed, then the two fee growth outside words.
+pub fn storage_to_tick_info(liquidity: U256, fee_growth_outside_0_x128: U256, fee_growth_outside_1_x128: U256) -> TickInfo {
+    TickInfo {
+        liquidity_gross: (liquidity & *U128_MASK).to::<u128>(),
+        liquidity_net: (liquidity >> 128).to::<u128>() as i128,
+        fee_growth_outside_0_x128,
+        fee_growth_outside_1_x128,
+    }
+}
+
+/// Reads the given slots with `eth_getStorageAt` JSON-RPC batches.
+pub async fn get_storage_batch<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+    provider: P,
+    address: Address,
+    slots: &[U256],
+    block: BlockId,
+) -> eyre::Result<Vec<U256>> {
+    let mut values = Vec::with_capacity(slots.len());
+    for chunk in slots.chunks(STORAGE_BATCH_SIZE) {
+        let client = provider.client();
+        let mut batch = client.new_batch();
+        let mut waiters = Vec::with_capacity(chunk.len());
+        for slot in chunk {
+            waiters.push(batch.add_call::<_, U256>("eth_getStorageAt", &(address, *slot, block))?);
+        }
+        batch.send().await?;
+        for waiter in waiters {
+            values.push(waiter.await?);
+        }
+    }
+    Ok(values)
+}
@@ -0,0 +1,329 @@
+use std::sync::Arc;
+use alloy::providers::{Provider, ProviderBuilder};
+use config::Config;
+use crate::uniswapv3pool::abi::IUniswapV3Pool;
+use crate::uniswapv3pool::UniswapV3Pool;
+
+#[tokio::test]
+async fn test_fetch_state_storage() {
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .try_init();
+
+    let cfg = Config::default();
+    let net = config::Network::Ethereum;
+    let provider = cfg.providers.get(&net).unwrap().clone();
+    let addresses = cfg.addresses.get(&net).unwrap().clone();
+    let provider = Arc::new(ProviderBuilder::new().on_http(provider.api.parse().unwrap()));
+
+    let block = provider
+        .get_block_number()
+        .await
+        .unwrap();
+
+    let pool = UniswapV3Pool::new(
+        addresses.uniswap_v3.pools.get("USDC_WETH").unwrap().clone(),
+        addresses.uniswap_v3.periphery.clone());
+
+    let data = UniswapV3Pool::fetch_data(
+        &pool.metadata,
+        provider.clone(),
+        block.into(),
+    ).await.unwrap();
+
+    let state_tick_lens = UniswapV3Pool::fetch_state_tick_lens(
+        &pool.metadata,
+        &data,
+        pool.metadata.periphery.tick_lens.unwrap(),
+        provider.clone(),
+        block.into(),
+    ).await.unwrap();
+
+    let state_storage = UniswapV3Pool::fetch_state_storage(
+        &pool.metadata,
+        &data,
+        provider.clone(),
+        block.into(),
+    ).await.unwrap();
+
+    assert_eq!(state_tick_lens.slot0.sqrt_price_x96, state_storage.slot0.sqrt_price_x96);
+    assert_eq!(state_tick_lens.slot0.tick, state_storage.slot0.tick);
+    assert_eq!(state_tick_lens.slot0.observation_index, state_storage.slot0.observation_index);
+    assert_eq!(state_tick_lens.slot0.fee_protocol, state_storage.slot0.fee_protocol);
+    assert_eq!(state_tick_lens.liquidity, state_storage.liquidity);
+    assert_eq!(state_tick_lens.tick_bitmap, state_storage.tick_bitmap);
+    assert_eq!(state_tick_lens.ticks.len(), state_storage.ticks.len());
+    for (tick, info) in state_tick_lens.ticks.iter() {
+        let info_storage = state_storage.ticks.get(*tick).unwrap();
+        assert_eq!(info.liquidity_gross, info_storage.liquidity_gross);
+        assert_eq!(info.liquidity_net, info_storage.liquidity_net);
+        assert_eq!(info.fee_growth_outside_0_x128, info_storage.fee_growth_outside_0_x128);
+        assert_eq!(info.fee_growth_outside_1_x128, info_storage.fee_growth_outside_1_x128);
+    }
+    assert_eq!(state_tick_lens.fee_growth_global_0_x128, state_storage.fee_growth_global_0_x128);
+    assert_eq!(state_tick_lens.fee_growth_global_1_x128, state_storage.fee_growth_global_1_x128);
+
+    // Both read the fee growth outside from storage, check a sample against the ticks() getter
+    let v3_pool = IUniswapV3Pool::new(pool.metadata.pool, provider.clone());
+    let step = (state_tick_lens.ticks.len() / 8).max(1);
+    for (tick, info) in state_tick_lens.ticks.iter().step_by(step) {
+        let ret = v3_pool.ticks((*tick).try_into().unwrap()).block(block.into()).call().await.unwrap();
+        assert_eq!(info.liquidity_gross, ret._0);
+        assert_eq!(info.liquidity_net, ret._1);
+        assert_eq!(info.fee_growth_outside_0_x128, ret._2);
+        assert_eq!(info.fee_growth_outside_1_x128, ret._3);
+    }
+}
+
+#[test]
+fn test_consult() {
+    use alloy::primitives::U160;
+    use crate::uniswapv3pool::oracle;
+
+    // tick -10.5 on average rounds down to -11
+    let twap = oracle::consult(
+        2,
+        [0, -21],
+        [U160::ZERO, U160::from(1) << 128],
+    ).unwrap();
+    assert_eq!(twap.arithmetic_mean_tick, -11);
+    assert_eq!(twap.sqrt_price_x96, uniswap_v3_math::tick_math::get_sqrt_ratio_at_tick(-11).unwrap());
+    // L = 2 over 2 seconds, floored to 1 by the type(uint160).max scaling
+    assert_eq!(twap.harmonic_mean_liquidity, 1);
+
+    assert!(oracle::consult(0, [0, 0], [U160::ZERO, U160::ZERO]).is_err());
+}
+
+#[test]
+fn test_liquidity_distribution() {
+    use alloy::primitives::U256;
+    use crate::uniswapv3pool::distribution;
+    use crate::uniswapv3pool::pricing::local::TickInfo;
+    use crate::uniswapv3pool::slot0::Slot0;
+    use crate::uniswapv3pool::UniswapV3PoolState;
+
+    // Positions [-120, 120) with 1000 and [-60, 60) with 500, price at tick 0
+    let mut state = UniswapV3PoolState {
+        slot0: Slot0 {
+            sqrt_price_x96: uniswap_v3_math::tick_math::get_sqrt_ratio_at_tick(0).unwrap(),
+            ..Default::default()
+        },
+        liquidity: 1500,
+        ..Default::default()
+    };
+    for (tick, liquidity_net) in [(-120, 1000), (-60, 500), (60, -500), (120, -1000)] {
+        state.ticks.insert(tick, TickInfo { liquidity_gross: liquidity_net.unsigned_abs(), liquidity_net, ..Default::default() });
+    }
+
+    let ranges = distribution::liquidity_distribution(&state).unwrap();
+    let bounds: Vec<(i32, i32, u128)> = ranges.iter().map(|r| (r.tick_lower, r.tick_upper, r.liquidity)).collect();
+    assert_eq!(bounds, vec![(-120, -60, 1000), (-60, 60, 1500), (60, 120, 1000)]);
+    assert!(ranges[1].contains_current_tick);
+    assert_eq!(ranges[0].amount0, U256::ZERO);
+    assert_eq!(ranges[2].amount1, U256::ZERO);
+
+    let csv = distribution::to_csv(&ranges);
+    assert_eq!(csv.lines().count(), 4);
+    assert!(distribution::to_json(&ranges).unwrap().starts_with('['));
+}
+
+#[test]
+fn test_position_fees() {
+    use alloy::primitives::{Address, U256};
+
+    let pool = UniswapV3Pool::synthetic(Address::repeat_byte(0), Address::repeat_byte(1), 3000);
+
+    let liquidity = 10u128.pow(18);
+    let (pool, amount0, amount1) = pool.with_position(-600, 600, liquidity).unwrap();
+    assert_eq!(pool.state.liquidity, liquidity);
+    assert!(amount0 > U256::ZERO && amount1 > U256::ZERO);
+    assert!(pool.with_position(-610, 600, liquidity).is_err());
+
+    // The position holds all the liquidity so it earns the whole 0.3% fee
+    let amount_in = U256::from(10u64.pow(15));
+    let fees = pool.calc_position_fees(amount_in, pool.data.tok0, -600, 600, liquidity).unwrap();
+    let expected = amount_in * U256::from(3) / U256::from(1000);
+    assert!(fees + U256::from(10) >= expected && fees <= expected + U256::from(10));
+}
+
+#[test]
+fn test_ticks_sorted() {
+    use crate::uniswapv3pool::pricing::local::TickInfo;
+    use crate::uniswapv3pool::ticks::Ticks;
+
+    let info = |liquidity_net: i128| TickInfo { liquidity_gross: liquidity_net.unsigned_abs(), liquidity_net, ..Default::default() };
+    let mut ticks: Ticks = [(60, info(-1)), (-60, info(1))].into_iter().collect();
+    ticks.insert(0, info(2));
+    ticks.get_mut_or_default(120).liquidity_net = -2;
+
+    let order: Vec<i32> = ticks.iter().map(|(tick, _)| *tick).collect();
+    assert_eq!(order, vec![-60, 0, 60, 120]);
+    assert_eq!(ticks.get(0), Some(&info(2)));
+    assert_eq!(ticks.insert(0, info(3)), Some(info(2)));
+    assert!(ticks.get(30).is_none());
+
+    let repeated: Ticks = [(0, info(1)), (60, info(-1)), (0, info(2)), (0, info(3))].into_iter().collect();
+    assert_eq!(repeated.len(), 2);
+    assert_eq!(repeated.get(0), Some(&info(3)));
+}
+
+#[test]
+fn test_swap_calldata() {
+    use alloy::primitives::{Address, I256, U256};
+    use alloy::sol_types::SolCall;
+    use crate::uniswapv3pool::abi::IUniswapV3Pool::swapCall;
+    use crate::uniswapv3pool::calldata;
+
+    let pool = UniswapV3Pool::synthetic(Address::repeat_byte(0), Address::repeat_byte(1), 500);
+
+    let recipient = Address::repeat_byte(2);
+    let payer = Address::repeat_byte(3);
+    let encoded = pool.encode_swap_exact_in(recipient, pool.data.tok1, U256::from(1000), None, payer).unwrap();
+    let call = swapCall::abi_decode(&encoded, true).unwrap();
+    assert_eq!(call.recipient, recipient);
+    assert!(!call.zeroForOne);
+    assert_eq!(call.amountSpecified, I256::try_from(1000).unwrap());
+
+    let callback_data = calldata::decode_callback_data(&call.data).unwrap();
+    assert_eq!(callback_data.tokenIn, pool.data.tok1);
+    assert_eq!(callback_data.tokenOut, pool.data.tok0);
+    assert_eq!(callback_data.payer, payer);
+
+    let ret = (I256::try_from(-990).unwrap(), I256::try_from(1000).unwrap());
+    let encoded_ret = swapCall::abi_encode_returns(&ret);
+    assert_eq!(calldata::decode_swap_return(&encoded_ret).unwrap(), ret);
+}
+
+#[test]
+fn test_fee_growth() {
+    use alloy::primitives::{Address, U256};
+    use crate::uniswapv3pool::fees;
+
+    let mut pool = UniswapV3Pool::synthetic(Address::repeat_byte(0), Address::repeat_byte(1), 3000);
+    pool.state.fee_growth_global_0_x128 = U256::from(12345);
+
+    // A wide position and a narrow one that the swap leaves
+    let liquidity = 10u128.pow(18);
+    let (pool, _, _) = pool.with_position(-600, 600, liquidity).unwrap();
+    let (mut pool, _, _) = pool.with_position(-60, 60, liquidity).unwrap();
+    let inside_before = pool.fee_growth_inside(-600, 600);
+    let narrow_before = pool.fee_growth_inside(-60, 60);
+    assert_eq!(inside_before, (U256::ZERO, U256::ZERO));
+
+    let amount_in = U256::from(10u64.pow(16));
+    let expected = pool.calc_position_fees(amount_in, pool.data.tok0, -600, 600, liquidity).unwrap();
+    let swap = pool.apply_swap(amount_in, pool.data.tok0, None).unwrap();
+    assert!(swap.tick < -60);
+    assert_eq!(pool.state.slot0.tick, swap.tick);
+    assert_eq!(pool.state.liquidity, liquidity);
+
+    let (fees0, fees1) = fees::fees_owed(liquidity, inside_before, pool.fee_growth_inside(-600, 600)).unwrap();
+    assert_eq!(fees0, expected);
+    assert_eq!(fees1, U256::ZERO);
+
+    // The narrow position only earned while the price was inside it
+    let (narrow0, _) = fees::fees_owed(liquidity, narrow_before, pool.fee_growth_inside(-60, 60)).unwrap();
+    assert!(narrow0 > U256::ZERO && narrow0 < fees0);
+}
+
+#[tokio::test]
+async fn test_discover_pair_pools() {
+    use crate::uniswapv3pool::factory;
+
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .try_init();
+
+    let cfg = Config::default();
+    let net = config::Network::Ethereum;
+    let provider = cfg.providers.get(&net).unwrap().clone();
+    let addresses = cfg.addresses.get(&net).unwrap().clone();
+    let provider = Arc::new(ProviderBuilder::new().on_http(provider.api.parse().unwrap()));
+
+    let block = provider
+        .get_block_number()
+        .await
+        .unwrap();
+
+    let configured = UniswapV3Pool::new(
+        addresses.uniswap_v3.pools.get("USDC_WETH").unwrap().clone(),
+        addresses.uniswap_v3.periphery.clone());
+    let data = UniswapV3Pool::fetch_data(
+        &configured.metadata,
+        provider.clone(),
+        block.into(),
+    ).await.unwrap();
+
+    let pools = factory::discover_pair_pools(
+        &addresses.uniswap_v3.core,
+        &addresses.uniswap_v3.periphery,
+        data.tok1,
+        data.tok0,
+        provider.clone(),
+        block.into(),
+    ).await.unwrap();
+
+    let discovered = pools.iter().find(|p| p.metadata.pool == configured.metadata.pool).unwrap();
+    assert_eq!(discovered.data.tok0, data.tok0);
+    assert_eq!(discovered.data.tok1, data.tok1);
+    assert_eq!(discovered.data.fee, data.fee);
+    assert_eq!(discovered.data.tick_spacing, data.tick_spacing);
+    assert_eq!(discovered.data.factory, data.factory);
+}
+
+#[test]
+fn test_storage_to_slot0_layouts() {
+    use alloy::primitives::U256;
+    use super::storage;
+
+    let sqrt_price_x96 = U256::from(1) << 96;
+    let tick_bits = U256::from(0xfffffbu32) << 160; // -5 as int24
+    let packed = sqrt_price_x96 | tick_bits | (U256::from(7) << 184) | (U256::from(10) << 200) | (U256::from(12) << 216);
+
+    // Uniswap V3: feeProtocol 1/4 on token0 and 1/5 on token1, unlocked
+    let value = packed | (U256::from(0x54) << 232) | (U256::from(1) << 240);
+    let slot0 = storage::storage_to_slot0(&storage::UNISWAP_V3_LAYOUT, &[value]);
+    assert_eq!(slot0.sqrt_price_x96, sqrt_price_x96);
+    assert_eq!(slot0.tick, -5);
+    assert_eq!(slot0.observation_index, 7);
+    assert_eq!(slot0.observation_cardinality, 10);
+    assert_eq!(slot0.observation_cardinality_next, 12);
+    assert_eq!(slot0.fee_protocol, 0x54);
+    assert!(slot0.unlocked);
+    assert_eq!(slot0.protocol_fee(true).amount(U256::from(1000)), U256::from(250));
+    assert_eq!(slot0.protocol_fee(false).amount(U256::from(1000)), U256::from(200));
+
+    // PancakeSwap V3: uint32 feeProtocol of 32% on token0 and 25% on token1, unlocked in the next word
+    let fee_protocol = 3200u32 | (2500u32 << 16);
+    let next = U256::from(fee_protocol) | (U256::from(1) << 32);
+    let slot0 = storage::storage_to_slot0(&storage::PANCAKESWAP_V3_LAYOUT, &[packed, next]);
+    assert_eq!(slot0.tick, -5);
+    assert_eq!(slot0.observation_cardinality_next, 12);
+    assert_eq!(slot0.fee_protocol, fee_protocol);
+    assert!(slot0.unlocked);
+    assert_eq!(slot0.protocol_fee(true).amount(U256::from(1000)), U256::from(320));
+    assert_eq!(slot0.protocol_fee(false).amount(U256::from(1000)), U256::from(250));
+
+    assert_ne!(storage::tick_slot(&storage::UNISWAP_V3_LAYOUT, -5), storage::tick_slot(&storage::PANCAKESWAP_V3_LAYOUT, -5));
+}
+
+#[test]
+fn test_tick_spacing_table() {
+    assert_eq!(UniswapV3Pool::get_price_step(2500), Some(50));
+    assert_eq!(UniswapV3Pool::get_price_step(3000), Some(60));
+    assert_eq!(UniswapV3Pool::get_price_step(1234), None);
+    assert!(UniswapV3Pool::tick_spacing(1234).is_err());
+}
+
+#[test]
+fn test_tick_bitmap_index() {
+    // Compressed ticks round towards negative infinity: -1 / 60 lands in word -1, not 0
+    assert_eq!(UniswapV3Pool::get_tick_bitmap_index(0, 60).unwrap(), 0);
+    assert_eq!(UniswapV3Pool::get_tick_bitmap_index(-1, 60).unwrap(), -1);
+    assert_eq!(UniswapV3Pool::get_tick_bitmap_index(-60, 60).unwrap(), -1);
+    assert_eq!(UniswapV3Pool::get_tick_bitmap_index(256 * 60 - 1, 60).unwrap(), 0);
+    assert_eq!(UniswapV3Pool::get_tick_bitmap_index(256 * 60, 60).unwrap(), 1);
+    assert_eq!(UniswapV3Pool::get_tick_bitmap_index(-256 * 60, 60).unwrap(), -1);
+    assert_eq!(UniswapV3Pool::get_tick_bitmap_index(-256 * 60 - 1, 60).unwrap(), -2);
+    assert!(UniswapV3Pool::get_tick_bitmap_index(0, 0).is_err());
+}
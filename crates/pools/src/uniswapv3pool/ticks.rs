@@ -0,0 +1,78 @@
+use crate::uniswapv3pool::pricing::local::TickInfo;
+
+/// Initialized ticks sorted by index in one contiguous buffer, cheap to borrow and search while quoting.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Ticks {
+    ticks: Vec<(i32, TickInfo)>,
+}
+
+impl Ticks {
+    pub fn len(&self) -> usize {
+        self.ticks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ticks.is_empty()
+    }
+
+    pub fn get(&self, tick: i32) -> Option<&TickInfo> {
+        self.search(tick).ok().map(|idx| &self.ticks[idx].1)
+    }
+
+    pub fn get_mut(&mut self, tick: i32) -> Option<&mut TickInfo> {
+        self.search(tick).ok().map(|idx| &mut self.ticks[idx].1)
+    }
+
+    /// Returns the tick info, inserting a default one first if the tick is not initialized.
+    pub fn get_mut_or_default(&mut self, tick: i32) -> &mut TickInfo {
+        let idx = match self.search(tick) {
+            Ok(idx) => idx,
+            Err(idx) => {
+                self.ticks.insert(idx, (tick, TickInfo::default()));
+                idx
+            }
+        };
+        &mut self.ticks[idx].1
+    }
+
+    /// Inserts or replaces the tick info, returning the previous one.
+    pub fn insert(&mut self, tick: i32, info: TickInfo) -> Option<TickInfo> {
+        match self.search(tick) {
+            Ok(idx) => Some(std::mem::replace(&mut self.ticks[idx].1, info)),
+            Err(idx) => {
+                self.ticks.insert(idx, (tick, info));
+                None
+            }
+        }
+    }
+
+    /// Iterates in ascending tick order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &(i32, TickInfo)> + ExactSizeIterator {
+        self.ticks.iter()
+    }
+
+    pub fn as_slice(&self) -> &[(i32, TickInfo)] {
+        &self.ticks
+    }
+
+    fn search(&self, tick: i32) -> Result<usize, usize> {
+        self.ticks.binary_search_by_key(&tick, |(t, _)| *t)
+    }
+}
+
+/// Like collecting into a map, the last entry of a repeated tick wins.
+impl FromIterator<(i32, TickInfo)> for Ticks {
+    fn from_iter<I: IntoIterator<Item = (i32, TickInfo)>>(iter: I) -> Self {
+        let mut ticks: Vec<(i32, TickInfo)> = iter.into_iter().collect();
+        ticks.sort_by_key(|(tick, _)| *tick);
+        // Stable sort keeps repeats in input order, move each later one into the kept slot
+        ticks.dedup_by(|later, kept| {
+            if later.0 != kept.0 {
+                return false;
+            }
+            std::mem::swap(later, kept);
+            true
+        });
+        Self { ticks }
+    }
+}
@@ -0,0 +1,179 @@
+use alloy::network::Network;
+use alloy::primitives::{Address, B256, U256};
+use alloy::providers::Provider;
+use alloy::rpc::types::{Filter, Log};
+use alloy::sol_types::SolEvent;
+use alloy::transports::Transport;
+use eyre::eyre;
+use tracing::instrument;
+use types::pool::Pool;
+use crate::rpc::get_logs_chunked;
+use crate::uniswapv3pool::{UniswapV3Pool, TICK_BITMAP_WORD_RANGE};
+use crate::uniswapv4pool::abi::IPoolManager;
+use crate::uniswapv4pool::{PoolKey, UniswapV4Pool};
+
+/// PoolManager events that change the state of a pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoolManagerEvent {
+    Initialize(IPoolManager::Initialize),
+    ModifyLiquidity(IPoolManager::ModifyLiquidity),
+    Swap(IPoolManager::Swap),
+}
+
+impl PoolManagerEvent {
+    /// Decodes a PoolManager log, None for the events which do not touch pool state.
+    pub fn decode(log: &Log) -> eyre::Result<Option<Self>> {
+        let event = match log.topic0() {
+            Some(topic) if *topic == IPoolManager::Initialize::SIGNATURE_HASH => {
+                Self::Initialize(log.log_decode::<IPoolManager::Initialize>()?.inner.data)
+            }
+            Some(topic) if *topic == IPoolManager::ModifyLiquidity::SIGNATURE_HASH => {
+                Self::ModifyLiquidity(log.log_decode::<IPoolManager::ModifyLiquidity>()?.inner.data)
+            }
+            Some(topic) if *topic == IPoolManager::Swap::SIGNATURE_HASH => {
+                Self::Swap(log.log_decode::<IPoolManager::Swap>()?.inner.data)
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(event))
+    }
+
+    /// PoolId of the pool the event belongs to.
+    pub fn id(&self) -> B256 {
+        match self {
+            Self::Initialize(event) => event.id,
+            Self::ModifyLiquidity(event) => event.id,
+            Self::Swap(event) => event.id,
+        }
+    }
+}
+
+impl TryFrom<&IPoolManager::Initialize> for PoolKey {
+    type Error = eyre::Error;
+
+    fn try_from(event: &IPoolManager::Initialize) -> eyre::Result<Self> {
+        Ok(PoolKey {
+            currency0: event.currency0,
+            currency1: event.currency1,
+            fee: event.fee.to::<u32>(),
+            tick_spacing: event.tickSpacing.try_into()?,
+            hooks: event.hooks,
+        })
+    }
+}
+
+impl UniswapV4Pool {
+    /// Pool as created by its Initialize event, without liquidity yet.
+    ///
+    /// Dynamic fee pools start at a zero LP fee until the hooks set one, which the next Swap event reveals.
+    pub fn from_initialize(pool_manager: Address, event: &IPoolManager::Initialize) -> eyre::Result<Self> {
+        let key = PoolKey::try_from(event)?;
+        let mut pool = UniswapV4Pool::new(pool_manager, key);
+        if pool.metadata.id != event.id {
+            return Err(eyre!("POOL_ID_MISMATCH {}", event.id));
+        }
+
+        let state = &mut pool.state;
+        state.v3.slot0.sqrt_price_x96 = event.sqrtPriceX96.to();
+        state.v3.slot0.tick = event.tick.try_into()?;
+        state.v3.slot0.unlocked = true;
+        state.lp_fee = if key.is_dynamic_fee() { 0 } else { key.fee };
+        // A new pool holds no liquidity, so the window around its tick is known to be empty
+        let tick_bitmap_index = UniswapV3Pool::get_tick_bitmap_index(state.v3.slot0.tick, u32::try_from(key.tick_spacing)?)?;
+        for i in TICK_BITMAP_WORD_RANGE {
+            state.v3.tick_bitmap.insert(tick_bitmap_index + i, U256::ZERO);
+        }
+        Ok(pool)
+    }
+
+    /// Applies an event of this pool to the state, events of other pools are ignored.
+    pub fn apply_event(&mut self, event: &PoolManagerEvent) -> eyre::Result<()> {
+        if event.id() != self.get_id() {
+            return Ok(());
+        }
+
+        match event {
+            PoolManagerEvent::Initialize(event) => {
+                *self = UniswapV4Pool::from_initialize(self.metadata.pool_manager, event)?;
+            }
+            PoolManagerEvent::ModifyLiquidity(event) => {
+                self.modify_liquidity(event.tickLower.try_into()?, event.tickUpper.try_into()?, event.liquidityDelta.try_into()?)?;
+            }
+            PoolManagerEvent::Swap(event) => {
+                let slot0 = &mut self.state.v3.slot0;
+                slot0.sqrt_price_x96 = event.sqrtPriceX96.to();
+                slot0.tick = event.tick.try_into()?;
+                self.state.v3.liquidity = event.liquidity;
+                // The event reports the whole swap fee, which is the LP fee only without protocol fee
+                if self.state.protocol_fee == 0 {
+                    self.state.lp_fee = event.fee.to::<u32>();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds or removes `liquidity_delta` in [tick_lower, tick_upper), flipping the bitmap of ticks that get (un)initialized.
+    fn modify_liquidity(&mut self, tick_lower: i32, tick_upper: i32, liquidity_delta: i128) -> eyre::Result<()> {
+        if tick_lower >= tick_upper {
+            return Err(eyre!("TLU"));
+        }
+        let tick_spacing = self.metadata.key.tick_spacing;
+        if tick_spacing <= 0 {
+            return Err(eyre!("INVALID_TICK_SPACING {tick_spacing}"));
+        }
+        let state = &mut self.state.v3;
+
+        for (tick, liquidity_net) in [(tick_lower, liquidity_delta), (tick_upper, -liquidity_delta)] {
+            let info = state.ticks.get_mut_or_default(tick);
+            let was_initialized = info.liquidity_gross != 0;
+            info.liquidity_gross = info.liquidity_gross.checked_add_signed(liquidity_delta).ok_or(eyre!("LO"))?;
+            info.liquidity_net = info.liquidity_net.checked_add(liquidity_net).ok_or(eyre!("LO"))?;
+            let initialized = info.liquidity_gross != 0;
+
+            if initialized && !was_initialized && tick <= state.slot0.tick {
+                // By convention all growth before a tick was initialized happened below it
+                info.fee_growth_outside_0_x128 = state.fee_growth_global_0_x128;
+                info.fee_growth_outside_1_x128 = state.fee_growth_global_1_x128;
+            } else if !initialized {
+                *info = Default::default();
+            }
+
+            if initialized != was_initialized {
+                let (word_pos, bit_pos) = uniswap_v3_math::tick_bitmap::position(tick / tick_spacing);
+                // Creating a word outside the fetched window would hide the other ticks in it, the tick is still recorded
+                if let Some(word) = state.tick_bitmap.get_mut(&word_pos) {
+                    *word ^= U256::from(1) << bit_pos;
+                }
+            }
+        }
+
+        if tick_lower <= state.slot0.tick && state.slot0.tick < tick_upper {
+            state.liquidity = state.liquidity.checked_add_signed(liquidity_delta).ok_or(eyre!("LA"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Pools initialized in the PoolManager in [from_block, to_block], from its Initialize events.
+///
+/// The returned pools only need their state synced.
+#[instrument(skip(provider), level = "debug")]
+pub async fn discover_pools<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+    pool_manager: Address,
+    from_block: u64,
+    to_block: u64,
+    provider: P,
+) -> eyre::Result<Vec<UniswapV4Pool>> {
+    let mut pools = Vec::new();
+
+    let filter = Filter::new()
+        .address(pool_manager)
+        .event_signature(IPoolManager::Initialize::SIGNATURE_HASH);
+    for log in get_logs_chunked(&provider, &filter, from_block, to_block).await? {
+        let initialize = log.log_decode::<IPoolManager::Initialize>()?.inner.data;
+        pools.push(UniswapV4Pool::from_initialize(pool_manager, &initialize)?);
+    }
+
+    Ok(pools)
+}
@@ -0,0 +1,323 @@
+mod abi;
+pub mod events;
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use alloy::eips::BlockId;
+use alloy::network::Network;
+use alloy::primitives::{keccak256, Address, B256, I256, U256};
+use alloy::providers::Provider;
+use alloy::transports::Transport;
+use eyre::eyre;
+use tracing::instrument;
+use types::pool::{check_pair, Pool, PoolClass, PoolProtocol};
+use crate::uniswapv3pool::pricing::local::{self, SwapResult};
+use crate::uniswapv3pool::slot0::Slot0;
+use crate::uniswapv3pool::storage;
+use crate::uniswapv3pool::ticks::Ticks;
+use crate::uniswapv3pool::{UniswapV3Pool, UniswapV3PoolState, TICK_BITMAP_WORD_RANGE};
+use crate::uniswapv4pool::abi::IPoolManager;
+
+/// Slot of `mapping(PoolId => Pool.State) _pools` in PoolManager.
+const POOLS_SLOT: U256 = U256::from_limbs([6, 0, 0, 0]);
+/// Offsets of the Pool.State fields from the slot of the pool state, slot0 being at 0.
+const FEE_GROWTH_GLOBAL_0_OFFSET: u64 = 1;
+const FEE_GROWTH_GLOBAL_1_OFFSET: u64 = 2;
+const LIQUIDITY_OFFSET: u64 = 3;
+const TICKS_OFFSET: u64 = 4;
+const TICK_BITMAP_OFFSET: u64 = 5;
+
+/// `PoolKey.fee` of pools whose LP fee is set by their hooks and read from slot0.
+pub const DYNAMIC_FEE_FLAG: u32 = 0x800000;
+/// Hook permissions, found in the lowest 14 bits of the hooks address.
+const BEFORE_SWAP_FLAG: u16 = 1 << 7;
+const AFTER_SWAP_FLAG: u16 = 1 << 6;
+const HOOK_FLAGS_MASK: u16 = (1 << 14) - 1;
+const PIPS_DENOMINATOR: u32 = 1_000_000;
+
+/// Identifies a pool within the PoolManager.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct PoolKey {
+    /// Sorted currencies, Address::ZERO being the native token.
+    pub currency0: Address,
+    pub currency1: Address,
+    /// Static LP fee in hundredths of a bip, or [`DYNAMIC_FEE_FLAG`].
+    pub fee: u32,
+    pub tick_spacing: i32,
+    pub hooks: Address,
+}
+
+impl PoolKey {
+    /// PoolId as computed by PoolIdLibrary.toId, keccak256(abi.encode(key)).
+    pub fn id(&self) -> B256 {
+        let mut buf = [0u8; 160];
+        buf[12..32].copy_from_slice(self.currency0.as_slice());
+        buf[44..64].copy_from_slice(self.currency1.as_slice());
+        buf[64..96].copy_from_slice(&U256::from(self.fee).to_be_bytes::<32>());
+        buf[96..128].copy_from_slice(&I256::try_from(self.tick_spacing as i64).unwrap().to_be_bytes::<32>());
+        buf[140..160].copy_from_slice(self.hooks.as_slice());
+        keccak256(buf)
+    }
+
+    pub fn is_dynamic_fee(&self) -> bool {
+        self.fee == DYNAMIC_FEE_FLAG
+    }
+
+    fn hook_flags(&self) -> u16 {
+        let bytes = self.hooks.as_slice();
+        u16::from_be_bytes([bytes[18], bytes[19]]) & HOOK_FLAGS_MASK
+    }
+
+    /// Whether swaps call into the hooks, which may then move amounts or override the fee out of sight of the V3 engine.
+    ///
+    /// Hooks that only update the dynamic LP fee outside of swaps are fine, the fee is read from slot0.
+    pub fn has_swap_hooks(&self) -> bool {
+        self.hook_flags() & (BEFORE_SWAP_FLAG | AFTER_SWAP_FLAG) != 0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UniswapV4PoolMetadata {
+    pub pool_manager: Address,
+    pub key: PoolKey,
+    pub id: B256,
+    pub protocol: PoolProtocol,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UniswapV4PoolState {
+    /// Pool.State, which keeps the V3 layout, so the V3 engine prices it.
+    pub v3: UniswapV3PoolState,
+    /// Raw protocolFee, 12 bits per direction in hundredths of a bip, zero for one in the low bits.
+    pub protocol_fee: u32,
+    /// LP fee in hundredths of a bip, the current one for dynamic fee pools.
+    pub lp_fee: u32,
+}
+
+/// Pool of the Uniswap V4 singleton PoolManager, keyed by its PoolId.
+#[derive(Debug, Clone)]
+pub struct UniswapV4Pool {
+    pub metadata: UniswapV4PoolMetadata,
+    pub state: UniswapV4PoolState,
+}
+
+impl Pool for UniswapV4Pool {
+    fn get_class(&self) -> PoolClass {
+        PoolClass::UniswapV4
+    }
+
+    fn get_protocol(&self) -> PoolProtocol {
+        self.metadata.protocol
+    }
+
+    /// The PoolManager holding the pool, see [`Pool::get_id`] for the pool itself.
+    fn get_address(&self) -> Address {
+        self.metadata.pool_manager
+    }
+
+    fn get_id(&self) -> B256 {
+        self.metadata.id
+    }
+
+    fn get_fee(&self) -> u32 {
+        self.state.lp_fee
+    }
+
+    fn get_tokens(&self) -> Vec<Address> {
+        vec![
+            self.metadata.key.currency0,
+            self.metadata.key.currency1
+        ]
+    }
+
+    fn calc_amount_out(&self, amount_in: U256, tok_in: Address, tok_out: Address) -> eyre::Result<U256> {
+        check_pair(self.metadata.key.currency0, self.metadata.key.currency1, tok_in, tok_out)?;
+        Ok(self.calc_swap(amount_in, tok_in, None)?.amount_out)
+    }
+}
+
+impl UniswapV4Pool {
+    pub fn new(pool_manager: Address, key: PoolKey) -> Self {
+        UniswapV4Pool {
+            metadata: UniswapV4PoolMetadata {
+                pool_manager,
+                key,
+                id: key.id(),
+                protocol: PoolProtocol::UniswapV4,
+            },
+            state: Default::default(),
+        }
+    }
+
+    #[instrument(skip_all, level = "debug", ret)]
+    pub async fn sync<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        &mut self,
+        provider: P,
+        block: BlockId
+    ) -> eyre::Result<()> {
+        self.state = UniswapV4Pool::fetch_state(
+            &self.metadata,
+            provider.clone(),
+            block,
+        ).await?;
+
+        Ok(())
+    }
+
+    /// Reads Pool.State from the PoolManager storage with extsload, the way StateView does.
+    #[instrument(skip(provider), level = "debug", ret)]
+    pub async fn fetch_state<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+        metadata: &UniswapV4PoolMetadata,
+        provider: P,
+        block: BlockId,
+    ) -> eyre::Result<UniswapV4PoolState> {
+        if metadata.key.tick_spacing <= 0 {
+            return Err(eyre!("INVALID_TICK_SPACING {}", metadata.key.tick_spacing));
+        }
+        let tick_spacing = metadata.key.tick_spacing as u32;
+        let state_slot = pool_state_slot(metadata.id);
+
+        let values = extsload(
+            metadata.pool_manager,
+            &[
+                state_slot,
+                state_slot + U256::from(FEE_GROWTH_GLOBAL_0_OFFSET),
+                state_slot + U256::from(FEE_GROWTH_GLOBAL_1_OFFSET),
+                state_slot + U256::from(LIQUIDITY_OFFSET),
+            ],
+            provider.clone(),
+            block,
+        ).await?;
+        let (slot0, protocol_fee, lp_fee) = storage_to_slot0(values[0]);
+        if slot0.sqrt_price_x96.is_zero() {
+            return Err(eyre!("POOL_NOT_INITIALIZED"));
+        }
+        let liquidity = storage::storage_to_liquidity(values[3]);
+
+        let tick_bitmap_index = UniswapV3Pool::get_tick_bitmap_index(slot0.tick, tick_spacing)?;
+        let word_positions: Vec<i16> = TICK_BITMAP_WORD_RANGE.map(|i| tick_bitmap_index + i).collect();
+        let tick_bitmap_slot = state_slot + U256::from(TICK_BITMAP_OFFSET);
+        let words = extsload(
+            metadata.pool_manager,
+            &word_positions.iter().map(|w| storage::mapping_slot(*w as i32, tick_bitmap_slot)).collect::<Vec<_>>(),
+            provider.clone(),
+            block,
+        ).await?;
+        let tick_bitmap: HashMap<i16, U256> = word_positions.into_iter().zip(words).collect();
+
+        let mut initialized_ticks: Vec<i32> = Vec::new();
+        for (word_pos, word) in tick_bitmap.iter() {
+            for bit_pos in 0..256usize {
+                if word.bit(bit_pos) {
+                    let compressed = ((*word_pos as i32) << 8) + bit_pos as i32;
+                    initialized_ticks.push(compressed * tick_spacing as i32);
+                }
+            }
+        }
+        // Tick.Info is the V3 one without the oracle fields
+        let ticks_slot = state_slot + U256::from(TICKS_OFFSET);
+        let tick_slots: Vec<U256> = initialized_ticks
+            .iter()
+            .flat_map(|t| {
+                let slot = storage::mapping_slot(*t, ticks_slot);
+                [slot, slot + U256::from(1), slot + U256::from(2)]
+            })
+            .collect();
+        let tick_values = extsload(
+            metadata.pool_manager,
+            &tick_slots,
+            provider.clone(),
+            block,
+        ).await?;
+        let ticks: Ticks = initialized_ticks
+            .into_iter()
+            .zip(tick_values.chunks(3))
+            .map(|(tick, values)| (tick, storage::storage_to_tick_info(values[0], values[1], values[2])))
+            .collect();
+
+        Ok(UniswapV4PoolState {
+            v3: UniswapV3PoolState {
+                slot0,
+                liquidity,
+                fee_growth_global_0_x128: values[1],
+                fee_growth_global_1_x128: values[2],
+                tick_bitmap,
+                ticks,
+            },
+            protocol_fee,
+            lp_fee,
+        })
+    }
+
+    /// Fee charged on the input, in hundredths of a bip, as ProtocolFeeLibrary.calculateSwapFee.
+    pub fn swap_fee(&self, zero_for_one: bool) -> u32 {
+        let protocol_fee = if zero_for_one { self.state.protocol_fee & 0xfff } else { self.state.protocol_fee >> 12 };
+        if protocol_fee == 0 {
+            return self.state.lp_fee;
+        }
+        protocol_fee + self.state.lp_fee - protocol_fee * self.state.lp_fee / PIPS_DENOMINATOR
+    }
+
+    /// Simulates an exact input swap with the V3 engine, for pools whose hooks stay out of swaps.
+    pub fn calc_swap(&self, amount_in: U256, tok_in: Address, sqrt_price_limit_x96: Option<U256>) -> eyre::Result<SwapResult> {
+        let key = &self.metadata.key;
+        if key.has_swap_hooks() {
+            return Err(eyre!("UNSUPPORTED_HOOKS {}", key.hooks));
+        }
+        let zero_for_one = tok_in == key.currency0;
+
+        local::calc_amount_out(
+            amount_in,
+            tok_in,
+            sqrt_price_limit_x96,
+            local::PoolData {
+                tok0: key.currency0,
+                fee: self.swap_fee(zero_for_one),
+                tick_spacing: key.tick_spacing.try_into()?,
+            },
+            (&self.state.v3).into(),
+        )
+    }
+}
+
+/// Slot of the Pool.State of the pool, keccak256(abi.encodePacked(poolId, POOLS_SLOT)).
+fn pool_state_slot(id: B256) -> U256 {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(id.as_slice());
+    buf[32..].copy_from_slice(&POOLS_SLOT.to_be_bytes::<32>());
+    U256::from_be_bytes(keccak256(buf).0)
+}
+
+/// Decodes slot0: `uint160 sqrtPriceX96 | int24 tick | uint24 protocolFee | uint24 lpFee`.
+///
+/// The protocol fee is left out of [`Slot0::protocol_fee`], V4 charges it on the input and it is part of the swap fee instead.
+fn storage_to_slot0(value: U256) -> (Slot0, u32, u32) {
+    let tick_raw = ((value >> 160) & U256::from(0xffffff)).to::<u32>();
+    let protocol_fee = ((value >> 184) & U256::from(0xffffff)).to::<u32>();
+    let lp_fee = ((value >> 208) & U256::from(0xffffff)).to::<u32>();
+    let slot0 = Slot0 {
+        sqrt_price_x96: value & ((U256::from(1) << 160) - U256::from(1)),
+        // sign extend int24
+        tick: ((tick_raw << 8) as i32) >> 8,
+        fee_protocol: protocol_fee,
+        unlocked: true,
+        ..Default::default()
+    };
+    (slot0, protocol_fee, lp_fee)
+}
+
+async fn extsload<T: Transport + Clone, N: Network, P: Provider<T, N> + Send + Sync + Clone + 'static>(
+    pool_manager: Address,
+    slots: &[U256],
+    provider: P,
+    block: BlockId,
+) -> eyre::Result<Vec<U256>> {
+    if slots.is_empty() {
+        return Ok(Vec::new());
+    }
+    let pool_manager = IPoolManager::new(pool_manager, provider);
+    let slots: Vec<B256> = slots.iter().map(|slot| B256::from(slot.to_be_bytes::<32>())).collect();
+    let values = pool_manager.extsload(slots).block(block).call().await?._0;
+    Ok(values.into_iter().map(|value| U256::from_be_bytes(value.0)).collect())
+}
@@ -0,0 +1,164 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use alloy::primitives::aliases::{I24, U24};
+use alloy::primitives::{Address, B256, I256, U160, U256};
+use alloy::providers::{Provider, ProviderBuilder};
+use tracing::info;
+use config::Config;
+use types::pool::Pool;
+use crate::uniswapv3pool::UniswapV3Pool;
+use crate::uniswapv4pool::abi::IPoolManager;
+use crate::uniswapv4pool::events::PoolManagerEvent;
+use crate::uniswapv4pool::{PoolKey, UniswapV4Pool, DYNAMIC_FEE_FLAG};
+
+#[tokio::test]
+async fn test_fetch_state() {
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .try_init();
+
+    let cfg = Config::default();
+    let net = config::Network::Ethereum;
+    let provider = cfg.providers.get(&net).unwrap().clone();
+    let addresses = cfg.addresses.get(&net).unwrap().clone();
+    let provider = Arc::new(ProviderBuilder::new().on_http(provider.api.parse().unwrap()));
+
+    let block = provider
+        .get_block_number()
+        .await
+        .unwrap();
+
+    // ETH/USDC 0.05%, hookless
+    let key = PoolKey {
+        currency0: Address::ZERO,
+        currency1: Address::from_str("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").unwrap(),
+        fee: 500,
+        tick_spacing: 10,
+        hooks: Address::ZERO,
+    };
+    let mut pool = UniswapV4Pool::new(addresses.uniswap_v4.pool_manager.unwrap(), key);
+    pool.sync(provider.clone(), block.into()).await.unwrap();
+
+    assert_eq!(pool.state.lp_fee, 500);
+    assert!(pool.state.v3.liquidity > 0);
+    assert!(!pool.state.v3.ticks.is_empty());
+
+    let amount_out = pool.calc_amount_out(U256::from(10).pow(U256::from(18)), Address::ZERO, key.currency1).unwrap();
+    info!(%amount_out, "1 ETH -> USDC");
+    assert!(amount_out > U256::ZERO);
+}
+
+#[test]
+fn test_pool_key() {
+    let key = PoolKey {
+        currency0: Address::ZERO,
+        currency1: Address::repeat_byte(1),
+        fee: 3000,
+        tick_spacing: 60,
+        hooks: Address::ZERO,
+    };
+    assert_ne!(key.id(), PoolKey { fee: 500, ..key }.id());
+    assert!(!key.is_dynamic_fee());
+    assert!(!key.has_swap_hooks());
+
+    // beforeSwap is bit 7 of the hooks address
+    let swap_hooks = PoolKey { hooks: Address::from_str("0000000000000000000000000000000000000080").unwrap(), ..key };
+    assert!(swap_hooks.has_swap_hooks());
+    // afterInitialize only, e.g. a hook setting the dynamic fee once
+    let fee_hooks = PoolKey { fee: DYNAMIC_FEE_FLAG, hooks: Address::from_str("0000000000000000000000000000000000001000").unwrap(), ..key };
+    assert!(fee_hooks.is_dynamic_fee());
+    assert!(!fee_hooks.has_swap_hooks());
+
+    let mut pool = UniswapV4Pool::new(Address::ZERO, swap_hooks);
+    pool.state.v3.slot0.sqrt_price_x96 = uniswap_v3_math::tick_math::get_sqrt_ratio_at_tick(0).unwrap();
+    assert!(pool.calc_amount_out(U256::from(1000), Address::ZERO, key.currency1).is_err());
+}
+
+#[test]
+fn test_swap_fee() {
+    let mut pool = UniswapV4Pool::new(Address::ZERO, PoolKey::default());
+    pool.state.lp_fee = 3000;
+    assert_eq!(pool.swap_fee(true), 3000);
+
+    // 0.1% protocol fee on zero for one only
+    pool.state.protocol_fee = 1000;
+    assert_eq!(pool.swap_fee(true), 1000 + 3000 - 3);
+    assert_eq!(pool.swap_fee(false), 3000);
+}
+
+#[test]
+fn test_apply_events() {
+    let key = PoolKey {
+        currency0: Address::repeat_byte(0),
+        currency1: Address::repeat_byte(1),
+        fee: 3000,
+        tick_spacing: 60,
+        hooks: Address::ZERO,
+    };
+    let id = key.id();
+    let initialize = IPoolManager::Initialize {
+        id,
+        currency0: key.currency0,
+        currency1: key.currency1,
+        fee: U24::from(key.fee),
+        tickSpacing: I24::try_from(key.tick_spacing).unwrap(),
+        hooks: key.hooks,
+        sqrtPriceX96: uniswap_v3_math::tick_math::get_sqrt_ratio_at_tick(0).unwrap().to::<U160>(),
+        tick: I24::ZERO,
+    };
+    let mut pool = UniswapV4Pool::from_initialize(Address::ZERO, &initialize).unwrap();
+    assert_eq!(pool.get_id(), id);
+    assert_eq!(pool.state.lp_fee, 3000);
+
+    let liquidity = 10u128.pow(18);
+    let modify_liquidity = |tick_lower: i32, tick_upper: i32, liquidity_delta: i128| PoolManagerEvent::ModifyLiquidity(IPoolManager::ModifyLiquidity {
+        id,
+        sender: Address::ZERO,
+        tickLower: I24::try_from(tick_lower).unwrap(),
+        tickUpper: I24::try_from(tick_upper).unwrap(),
+        liquidityDelta: I256::try_from(liquidity_delta).unwrap(),
+        salt: B256::ZERO,
+    });
+    pool.apply_event(&modify_liquidity(-600, 600, liquidity as i128)).unwrap();
+    assert_eq!(pool.state.v3.liquidity, liquidity);
+    assert_eq!(pool.state.v3.ticks.get(600).unwrap().liquidity_net, -(liquidity as i128));
+
+    // Same quote as a V3 pool holding the same position
+    let mut v3_pool = UniswapV3Pool::synthetic(key.currency0, key.currency1, 3000);
+    v3_pool.state = pool.state.v3.clone();
+    let amount_in = U256::from(10).pow(U256::from(16));
+    let v4_swap = pool.calc_swap(amount_in, key.currency0, None).unwrap();
+    assert_eq!(v4_swap, v3_pool.calc_swap(amount_in, key.currency0, None).unwrap());
+
+    // Events of other pools are ignored
+    let mut other = modify_liquidity(-600, 600, liquidity as i128);
+    if let PoolManagerEvent::ModifyLiquidity(event) = &mut other {
+        event.id = B256::repeat_byte(1);
+    }
+    pool.apply_event(&other).unwrap();
+    assert_eq!(pool.state.v3.liquidity, liquidity);
+
+    pool.apply_event(&PoolManagerEvent::Swap(IPoolManager::Swap {
+        id,
+        sender: Address::ZERO,
+        amount0: 0,
+        amount1: 0,
+        sqrtPriceX96: v4_swap.sqrt_price_x96.to::<U160>(),
+        liquidity: v4_swap.liquidity,
+        tick: I24::try_from(v4_swap.tick).unwrap(),
+        fee: U24::from(3000),
+    })).unwrap();
+    assert_eq!(pool.state.v3.slot0.sqrt_price_x96, v4_swap.sqrt_price_x96);
+    assert_eq!(pool.state.v3.slot0.tick, v4_swap.tick);
+
+    // Ticks beyond the tracked window are recorded without creating a partial bitmap word
+    let words = pool.state.v3.tick_bitmap.len();
+    pool.apply_event(&modify_liquidity(180_000, 180_060, liquidity as i128)).unwrap();
+    assert_eq!(pool.state.v3.tick_bitmap.len(), words);
+    assert_eq!(pool.state.v3.ticks.get(180_000).unwrap().liquidity_gross, liquidity);
+
+    // Removing the position uninitializes both ticks
+    pool.apply_event(&modify_liquidity(-600, 600, -(liquidity as i128))).unwrap();
+    assert_eq!(pool.state.v3.liquidity, 0);
+    assert!(pool.state.v3.tick_bitmap.values().all(|word| word.is_zero()));
+}
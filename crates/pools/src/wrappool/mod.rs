@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod tests;
+
+use alloy::primitives::{Address, U256};
+use types::pool::{check_pair, Pool, PoolClass, PoolProtocol};
+
+/// Native currency, as V4 pools denote it.
+pub const NATIVE: Address = Address::ZERO;
+/// Gas of a WETH9 deposit or withdraw call.
+pub const WRAP_GAS: u64 = 30_000;
+
+/// Virtual pool converting the native currency to its wrapped token and back, 1:1 without fee.
+///
+/// The wrapped token contract stands for the pool, deposit() wraps and withdraw() unwraps.
+#[derive(Debug, Clone)]
+pub struct WrapPool {
+    pub native: Address,
+    pub wrapped: Address,
+    pub gas: u64,
+}
+
+impl Pool for WrapPool {
+    fn get_class(&self) -> PoolClass {
+        PoolClass::Wrap
+    }
+
+    fn get_protocol(&self) -> PoolProtocol {
+        PoolProtocol::WrappedNative
+    }
+
+    fn get_address(&self) -> Address {
+        self.wrapped
+    }
+
+    fn get_tokens(&self) -> Vec<Address> {
+        vec![
+            self.native,
+            self.wrapped
+        ]
+    }
+
+    fn get_gas(&self) -> Option<u64> {
+        Some(self.gas)
+    }
+
+    fn calc_amount_out(&self, amount_in: U256, tok_in: Address, tok_out: Address) -> eyre::Result<U256> {
+        check_pair(self.native, self.wrapped, tok_in, tok_out)?;
+        Ok(amount_in)
+    }
+}
+
+impl WrapPool {
+    pub fn new(wrapped: Address) -> Self {
+        WrapPool {
+            native: NATIVE,
+            wrapped,
+            gas: WRAP_GAS,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use alloy::primitives::{Address, U256};
+use config::Config;
+use types::pool::{Pool, PoolProtocol};
+use crate::wrappool::{WrapPool, NATIVE, WRAP_GAS};
+
+#[test]
+fn test_calc_amount_out() {
+    let cfg = Config::default();
+    let addresses = cfg.addresses.get(&config::Network::Ethereum).unwrap().clone();
+    let weth = addresses.wrapped_native.unwrap();
+    let pool = WrapPool::new(weth);
+
+    let amount = U256::from(10).pow(U256::from(18));
+    assert_eq!(pool.calc_amount_out(amount, NATIVE, weth).unwrap(), amount);
+    assert_eq!(pool.calc_amount_out(amount, weth, NATIVE).unwrap(), amount);
+    assert!(pool.calc_amount_out(amount, weth, Address::repeat_byte(1)).is_err());
+
+    assert_eq!(pool.get_fee(), 0);
+    assert_eq!(pool.get_gas(), Some(WRAP_GAS));
+    assert_eq!(pool.get_protocol(), PoolProtocol::WrappedNative);
+    assert_eq!(pool.get_tokens(), vec![NATIVE, weth]);
+}
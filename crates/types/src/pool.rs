@@ -1,5 +1,5 @@
 use std::fmt::{Display, Formatter};
-use alloy::primitives::{Address, U256};
+use alloy::primitives::{Address, B256, U256};
 use serde::{Deserialize, Serialize};
 
 pub trait Pool: Sync + Send {
@@ -13,21 +13,40 @@ pub trait Pool: Sync + Send {
 
     fn get_address(&self) -> Address;
 
+    /// Identifies the pool, its address left padded unless it lives in a singleton contract like the V4 PoolManager.
+    fn get_id(&self) -> B256 {
+        self.get_address().into_word()
+    }
+
     fn get_fee(&self) -> u32 { 0 }
 
+    /// Fixed gas cost of a swap through the pool, None when it depends on the swap.
+    fn get_gas(&self) -> Option<u64> { None }
+
     fn get_tokens(&self) -> Vec<Address> {
         Vec::new()
     }
 
+    /// Amount of `tok_out` received for `amount_in` of `tok_in`, which pools with more than two tokens need both of.
     fn calc_amount_out(
         &self,
         amount_in: U256,
         tok_in: Address,
+        tok_out: Address,
     ) -> eyre::Result<U256>;
 
     fn can_flash_swap(&self) -> bool { false }
 }
 
+/// Checks that `tok_in` and `tok_out` are the two tokens of a pair, in either order.
+pub fn check_pair(tok0: Address, tok1: Address, tok_in: Address, tok_out: Address) -> eyre::Result<()> {
+    if (tok_in == tok0 && tok_out == tok1) || (tok_in == tok1 && tok_out == tok0) {
+        Ok(())
+    } else {
+        Err(eyre::eyre!("TOKEN_NOT_IN_POOL"))
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PoolProtocol {
     Unknown,
@@ -35,6 +54,17 @@ pub enum PoolProtocol {
     UniswapV2Like,
     UniswapV3,
     UniswapV3Like,
+    PancakeSwapV3,
+    UniswapV4,
+    Algebra,
+    Solidly,
+    Curve,
+    CurveCrypto,
+    BalancerV2,
+    WrappedNative,
+    Erc4626,
+    LiquidityBook,
+    DodoV2,
 }
 
 impl Default for PoolProtocol {
@@ -51,6 +81,17 @@ impl Display for PoolProtocol {
             Self::UniswapV2Like => "UniswapV2Like",
             Self::UniswapV3 => "UniswapV3",
             Self::UniswapV3Like => "UniswapV3Like",
+            Self::PancakeSwapV3 => "PancakeSwapV3",
+            Self::UniswapV4 => "UniswapV4",
+            Self::Algebra => "Algebra",
+            Self::Solidly => "Solidly",
+            Self::Curve => "Curve",
+            Self::CurveCrypto => "CurveCrypto",
+            Self::BalancerV2 => "BalancerV2",
+            Self::WrappedNative => "WrappedNative",
+            Self::Erc4626 => "Erc4626",
+            Self::LiquidityBook => "LiquidityBook",
+            Self::DodoV2 => "DodoV2",
         };
         write!(f, "{}", protocol_name)
     }
@@ -61,5 +102,13 @@ pub enum PoolClass {
     Unknown,
     UniswapV2,
     UniswapV3,
+    UniswapV4,
+    Curve,
+    CurveCrypto,
+    Balancer,
+    Wrap,
+    Erc4626,
+    LiquidityBook,
+    Dodo,
 }
 